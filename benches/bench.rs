@@ -211,6 +211,40 @@ fn vec_u_vn_s_prealloc(bencher: &mut Bencher) {
     );
 }
 
+fn empty_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![(); 1024]);
+}
+fn u64_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![0u64; 1024]);
+}
+fn u32x2_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![(0u32, 0u32); 1024]);
+}
+fn u8_u64_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![(0u8, 0u64); 512]);
+}
+fn str10_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec!["grawwwwrr!"; 1024]);
+}
+fn string10_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![format!("grawwwwrr!"); 1024]);
+}
+fn string20_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(bencher, vec![format!("grawwwwrr!!!!!!!!!!!"); 512]);
+}
+fn vec_u_s_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(
+        bencher,
+        vec![vec![(0u64, "grawwwwrr!".to_string()); 32]; 32],
+    );
+}
+fn vec_u_vn_s_extend_reserved(bencher: &mut Bencher) {
+    _bench_extend_reserved(
+        bencher,
+        vec![vec![(0u64, vec![(); 1 << 40], "grawwwwrr!".to_string()); 32]; 32],
+    );
+}
+
 fn empty_copy_flat(bencher: &mut Bencher) {
     _bench_copy_flat_preference(bencher, vec![(); 1024]);
 }
@@ -335,9 +369,9 @@ where
     set_bytes(&mut bencher.bytes, siz);
 }
 
-fn _bench_copy_region<R: Region, T>(bencher: &mut Bencher, record: T)
+fn _bench_copy_region<R, T>(bencher: &mut Bencher, record: T)
 where
-    for<'a> R: Push<&'a T>,
+    for<'a> R: Region + Push<&'a T>,
 {
     // prepare encoded data for bencher.bytes
     let mut arena = FlatStack::<R>::default();
@@ -408,6 +442,60 @@ where
     set_bytes(&mut bencher.bytes, siz);
 }
 
+fn _bench_extend_reserved<T: RegionPreference + Eq>(bencher: &mut Bencher, record: T)
+where
+    for<'a> <T as RegionPreference>::Region: ReserveItems<&'a T> + Push<&'a T>,
+{
+    let mut arena = FlatStack::default_impl::<T>();
+    bencher.iter(|| {
+        arena = FlatStack::default_impl::<T>();
+        arena.extend_reserved(std::iter::repeat(&record).take(1024));
+    });
+    let (mut siz, mut cap) = (0, 0);
+    arena.heap_size(|this_siz, this_cap| {
+        siz += this_siz;
+        cap += this_cap
+    });
+    set_bytes(&mut bencher.bytes, siz);
+}
+
+fn bytes_iter(bencher: &mut Bencher) {
+    let arena = _bytes_arena();
+    bencher.iter(|| arena.iter().map(<[u8]>::len).sum::<usize>());
+    set_bytes(&mut bencher.bytes, arena.iter().map(<[u8]>::len).sum());
+}
+fn bytes_iter_slices(bencher: &mut Bencher) {
+    let arena = _bytes_arena();
+    bencher.iter(|| arena.iter_slices().map(<[u8]>::len).sum::<usize>());
+    set_bytes(&mut bencher.bytes, arena.iter().map(<[u8]>::len).sum());
+}
+fn bytes_iter_consecutive(bencher: &mut Bencher) {
+    let arena = _bytes_arena_consecutive();
+    bencher.iter(|| arena.iter().map(<[u8]>::len).sum::<usize>());
+    set_bytes(&mut bencher.bytes, arena.iter().map(<[u8]>::len).sum());
+}
+fn bytes_iter_consecutive_sequential(bencher: &mut Bencher) {
+    let arena = _bytes_arena_consecutive();
+    bencher.iter(|| arena.iter_consecutive().map(<[u8]>::len).sum::<usize>());
+    set_bytes(&mut bencher.bytes, arena.iter().map(<[u8]>::len).sum());
+}
+
+fn _bytes_arena() -> FlatStack<OwnedRegion<u8>> {
+    let mut arena = FlatStack::default();
+    for i in 0..1024 {
+        arena.copy(&b"grawwwwrr!grawwwwrr!"[..i % 20]);
+    }
+    arena
+}
+
+fn _bytes_arena_consecutive() -> FlatStack<ConsecutiveIndexPairs<OwnedRegion<u8>>> {
+    let mut arena = FlatStack::default();
+    for i in 0..1024 {
+        arena.copy(&b"grawwwwrr!grawwwwrr!"[..i % 20]);
+    }
+    arena
+}
+
 fn _bench_copy_flat_preference<T>(bencher: &mut Bencher, record: T)
 where
     T: RegionPreference,
@@ -504,23 +592,39 @@ benchmark_group!(
 );
 benchmark_group!(
     alloc,
+    empty_extend_reserved,
     empty_prealloc,
     empty_realloc,
+    str10_extend_reserved,
     str10_prealloc,
     str10_realloc,
+    string10_extend_reserved,
     string10_prealloc,
     string10_realloc,
+    string20_extend_reserved,
     string20_prealloc,
     string20_realloc,
+    u32x2_extend_reserved,
     u32x2_prealloc,
     u32x2_realloc,
+    u64_extend_reserved,
     u64_prealloc,
     u64_realloc,
+    u8_u64_extend_reserved,
     u8_u64_prealloc,
     u8_u64_realloc,
+    vec_u_s_extend_reserved,
     vec_u_s_prealloc,
     vec_u_s_realloc,
+    vec_u_vn_s_extend_reserved,
     vec_u_vn_s_prealloc,
     vec_u_vn_s_realloc,
 );
-benchmark_main!(clone, copy, copy_flat, copy_region, alloc);
+benchmark_group!(
+    iter,
+    bytes_iter,
+    bytes_iter_consecutive,
+    bytes_iter_consecutive_sequential,
+    bytes_iter_slices
+);
+benchmark_main!(clone, copy, copy_flat, copy_region, alloc, iter);
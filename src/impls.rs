@@ -1,16 +1,58 @@
 //! Various region implementations.
 
+pub mod aggregate;
+pub mod assoc;
+pub mod bitpack;
+pub mod bounded;
+pub mod bytes;
+pub mod choose;
+#[cfg(feature = "chrono")]
+pub mod chrono;
+pub mod cidr;
 pub mod codec;
 pub mod columns;
+pub mod cow;
+pub mod csr;
+pub mod decimal;
 pub mod deduplicate;
+pub mod delta;
+pub mod discriminant;
+pub mod enum_columns;
+pub mod frame_of_reference;
+pub mod geo;
 pub mod huffman_container;
 pub mod index;
+pub mod interleaved;
+pub mod interned;
+pub mod ip;
+pub mod length_delimited;
+pub mod map;
+pub mod matrix;
 pub mod mirror;
+pub mod morton;
+pub mod nullable_columns;
+pub mod nullable_slice;
 pub mod option;
+pub mod overlay;
+pub mod pooled;
+pub mod quantized;
 pub mod result;
+pub mod rle;
+pub mod roaring;
+pub mod set;
+pub mod shared;
 pub mod slice;
+pub mod slice_delta;
 pub mod slice_owned;
+pub mod socket;
+pub mod sparse;
 pub mod storage;
 pub mod string;
+pub mod suffix;
+pub mod tombstone;
+pub mod tracked;
+pub mod transparent;
 pub mod tuple;
+pub mod union;
 mod vec;
+pub mod zoned;
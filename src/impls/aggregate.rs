@@ -0,0 +1,373 @@
+//! A region that aggregates values per key.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::collections::TryReserveError;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::tuple::TupleABRegion;
+use crate::{Index, IntoOwned, MirrorRegion, Push, Region, ReserveItems};
+
+/// Fold values into an accumulator.
+///
+/// Implemented for primitive numbers as a sum of values of the same type.
+pub trait Combine<V>: Sized {
+    /// Creates an accumulator from the first value of a key.
+    fn init(value: V) -> Self;
+
+    /// Folds `value` into the accumulator.
+    fn combine(&mut self, value: V);
+}
+
+macro_rules! implement_sum {
+    ($($t:ty),*) => {
+        $(
+            impl Combine<$t> for $t {
+                #[inline]
+                fn init(value: $t) -> Self {
+                    value
+                }
+
+                #[inline]
+                fn combine(&mut self, value: $t) {
+                    *self += value;
+                }
+            }
+        )*
+    };
+}
+
+implement_sum!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+/// A region that maintains an accumulator per distinct key.
+///
+/// Pushing a `(key, value)` pair looks up the key, and [combines](Combine) the value into the
+/// key's accumulator, or stores the key with a new accumulator if it is absent. The index
+/// identifies the key, so pushing the same key again returns the same index, and reading
+/// an index presents the key with its current accumulator.
+///
+/// Keys are stored in region `K`, and accumulators inline in the side index, which
+/// requires the accumulator `A` to be an [`Index`]. Keys that are pushed need to hash
+/// equally to and compare equal with their read item, similar to
+/// [`Dedup`](crate::impls::deduplicate::Dedup).
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::aggregate::AggRegion;
+/// use flatcontainer::{Push, Region, StringRegion};
+/// let mut r = <AggRegion<StringRegion, u64>>::default();
+///
+/// let index = r.push(("a", 1_u64));
+/// assert_eq!(index, r.push(("a", 2_u64)));
+/// assert_eq!(("a", 3), r.index(index));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            K: Serialize + for<'a> Deserialize<'a>,
+            K::Index: Serialize + for<'a> Deserialize<'a>,
+            A: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct AggRegion<K: Region, A> {
+    /// Storage for keys.
+    inner: TupleABRegion<K, MirrorRegion<A>>,
+    /// The key index and accumulator per distinct key, in the order of first appearance.
+    slots: Vec<(K::Index, A)>,
+    /// The previous slot whose key has the same hash, per slot.
+    chain: Vec<Option<usize>>,
+    /// Maps the hash of a key to the most recent slot with this hash.
+    heads: HashMap<u64, usize>,
+}
+
+impl<K, A> Clone for AggRegion<K, A>
+where
+    K: Region + Clone,
+    for<'a> A: Index + IntoOwned<'a, Owned = A>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            slots: self.slots.clone(),
+            chain: self.chain.clone(),
+            heads: self.heads.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.slots.clone_from(&source.slots);
+        self.chain.clone_from(&source.chain);
+        self.heads.clone_from(&source.heads);
+    }
+}
+
+impl<K: Region, A> Default for AggRegion<K, A> {
+    fn default() -> Self {
+        Self {
+            inner: TupleABRegion::default(),
+            slots: Vec::default(),
+            chain: Vec::default(),
+            heads: HashMap::default(),
+        }
+    }
+}
+
+impl<K, A> AggRegion<K, A>
+where
+    K: Region,
+    for<'a> A: Index + IntoOwned<'a, Owned = A>,
+{
+    /// Returns the number of distinct keys.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if the region contains no keys.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Iterates the keys and their accumulators, in the order keys first appeared.
+    pub fn iter(&self) -> impl Iterator<Item = (K::ReadItem<'_>, A)> + '_ {
+        self.slots.iter().map(|slot| self.inner.index(*slot))
+    }
+
+    /// Returns the hash of a key.
+    #[inline]
+    fn hash<T: Hash>(key: &T) -> u64 {
+        let mut hasher = BuildHasherDefault::<DefaultHasher>::default().build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<K, A> Region for AggRegion<K, A>
+where
+    K: Region,
+    for<'a> A: Index + IntoOwned<'a, Owned = A>,
+{
+    type Owned = (K::Owned, A);
+    type ReadItem<'a> = (K::ReadItem<'a>, A) where Self: 'a;
+    /// The position of the key's slot.
+    type Index = usize;
+
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let len = regions.clone().map(Self::len).sum();
+        Self {
+            inner: TupleABRegion::merge_regions(regions.map(|r| &r.inner)),
+            slots: Vec::with_capacity(len),
+            chain: Vec::with_capacity(len),
+            heads: HashMap::with_capacity(len),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(self.slots[index])
+    }
+
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = regions.clone().map(Self::len).sum();
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+        self.slots.reserve(len);
+        self.chain.reserve(len);
+        self.heads.reserve(len);
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = regions.clone().map(Self::len).sum();
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))?;
+        self.slots.try_reserve(len)?;
+        self.chain.try_reserve(len)?;
+        self.heads.try_reserve(len)
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.slots.clear();
+        self.chain.clear();
+        self.heads.clear();
+    }
+
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_slot = std::mem::size_of::<(K::Index, A)>();
+        callback(
+            self.slots.len() * size_of_slot,
+            self.slots.capacity() * size_of_slot,
+        );
+        let size_of_link = std::mem::size_of::<Option<usize>>();
+        callback(
+            self.chain.len() * size_of_link,
+            self.chain.capacity() * size_of_link,
+        );
+        let size_of_head = std::mem::size_of::<(u64, usize)>();
+        callback(
+            self.heads.len() * size_of_head,
+            self.heads.capacity() * size_of_head,
+        );
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        (K::reborrow(item.0), item.1)
+    }
+}
+
+impl<K, A, KT, V> Push<(KT, V)> for AggRegion<K, A>
+where
+    K: Push<KT>,
+    for<'a> A: Index + IntoOwned<'a, Owned = A> + Combine<V>,
+    KT: Hash,
+    for<'a> KT: PartialEq<K::ReadItem<'a>>,
+{
+    fn push(&mut self, (key, value): (KT, V)) -> usize {
+        let hash = Self::hash(&key);
+        let head = self.heads.get(&hash).copied();
+        let mut next = head;
+        while let Some(slot) = next {
+            let (key_index, accumulator) = &mut self.slots[slot];
+            if key == self.inner.index((*key_index, *accumulator)).0 {
+                accumulator.combine(value);
+                return slot;
+            }
+            next = self.chain[slot];
+        }
+
+        let slot = self.slots.len();
+        self.slots.push(self.inner.push((key, A::init(value))));
+        self.chain.push(head);
+        self.heads.insert(hash, slot);
+        slot
+    }
+}
+
+impl<K, A, KT, V> ReserveItems<(KT, V)> for AggRegion<K, A>
+where
+    K: Region,
+    for<'a> A: Index + IntoOwned<'a, Owned = A>,
+    TupleABRegion<K, MirrorRegion<A>>: ReserveItems<(KT, V)>,
+{
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = (KT, V)> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::StringRegion;
+
+    use super::*;
+
+    #[test]
+    fn test_sum_per_key() {
+        let mut r = <AggRegion<StringRegion, i64>>::default();
+        let items = [("a", 1), ("b", 2), ("a", 3)];
+        r.reserve_items(items.iter().copied());
+        let indices: Vec<_> = items.into_iter().map(|item| r.push(item)).collect();
+
+        assert_eq!(indices[0], indices[2]);
+        assert_eq!(2, r.len());
+        assert_eq!(("a", 4), r.index(indices[0]));
+        assert_eq!(("b", 2), r.index(indices[1]));
+        assert!(r.iter().eq([("a", 4), ("b", 2)]));
+        assert_eq!(("a".to_string(), 4), r.index(indices[0]).into_owned());
+
+        r.clear();
+        assert!(r.is_empty());
+        let index = r.push(("b".to_string(), -1));
+        assert_eq!(("b", -1), r.index(index));
+    }
+
+    /// A key whose hash ignores its value, which forces all keys into one chain.
+    #[derive(PartialEq)]
+    struct Colliding<'a>(&'a str);
+
+    impl Hash for Colliding<'_> {
+        fn hash<H: Hasher>(&self, _state: &mut H) {}
+    }
+
+    impl<'a> PartialEq<&'a str> for Colliding<'_> {
+        fn eq(&self, other: &&'a str) -> bool {
+            self.0 == *other
+        }
+    }
+
+    impl Push<Colliding<'_>> for StringRegion {
+        fn push(&mut self, item: Colliding<'_>) -> (usize, usize) {
+            self.push(item.0)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    struct MinMax(u8, u8);
+
+    impl<'a> IntoOwned<'a> for MinMax {
+        type Owned = Self;
+
+        fn into_owned(self) -> Self::Owned {
+            self
+        }
+
+        fn clone_onto(self, other: &mut Self::Owned) {
+            *other = self;
+        }
+
+        fn borrow_as(owned: &'a Self::Owned) -> Self {
+            *owned
+        }
+    }
+
+    impl Combine<u8> for MinMax {
+        fn init(value: u8) -> Self {
+            Self(value, value)
+        }
+
+        fn combine(&mut self, value: u8) {
+            self.0 = self.0.min(value);
+            self.1 = self.1.max(value);
+        }
+    }
+
+    #[test]
+    fn test_collisions() {
+        let mut r = <AggRegion<StringRegion, MinMax>>::default();
+        for (key, value) in [("x", 5), ("y", 1), ("z", 9), ("x", 2), ("y", 4), ("x", 7)] {
+            let _ = r.push((Colliding(key), value));
+        }
+        assert!(r.iter().eq([
+            ("x", MinMax(2, 7)),
+            ("y", MinMax(1, 4)),
+            ("z", MinMax(9, 9))
+        ]));
+    }
+}
@@ -0,0 +1,467 @@
+//! A region that stores association lists.
+
+use std::cmp::Ordering;
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::slice::{ReadSlice, ReadSliceIter};
+use crate::impls::tuple::TupleABRegion;
+use crate::{IntoOwned, Push, PushIter, Region, SliceRegion};
+
+/// The default index container of an [`AssocRegion`].
+type Entries<K, V> = Vec<<TupleABRegion<K, V> as Region>::Index>;
+
+/// A region to hold association lists, which are small maps from keys to values.
+///
+/// Stores each list as a slice of key-value pairs in a [`SliceRegion`], sorted by key. The read
+/// item is a [`ReadAssoc`], which looks up keys in logarithmic time and iterates entries, keys
+/// and values in key order. For few entries, this is more compact than a hash map.
+///
+/// Pushing a list sorts its entries by key, which requires a temporary allocation unless the
+/// list is an owned vector. Entries with equal keys are retained in the order they were pushed.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::assoc::AssocRegion;
+/// use flatcontainer::{MirrorRegion, Push, Region, StringRegion};
+/// let mut r = <AssocRegion<MirrorRegion<u32>, StringRegion>>::default();
+///
+/// let index = r.push(&[(2, "b"), (1, "a")]);
+///
+/// assert_eq!(Some("a"), r.index(index).get(&1));
+/// assert_eq!(None, r.index(index).get(&3));
+/// assert!(r.index(index).keys().eq([1, 2]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            K: Serialize + for<'a> Deserialize<'a>,
+            V: Serialize + for<'a> Deserialize<'a>,
+            O: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct AssocRegion<K: Region, V: Region, O = Entries<K, V>> {
+    /// Entries of each list, sorted by key.
+    inner: SliceRegion<TupleABRegion<K, V>, O>,
+}
+
+impl<K, V, O> Clone for AssocRegion<K, V, O>
+where
+    K: Region + Clone,
+    V: Region + Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<K, V, O> Region for AssocRegion<K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Owned = Vec<(K::Owned, V::Owned)>;
+    type ReadItem<'a> = ReadAssoc<'a, K, V, O> where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: SliceRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadAssoc(Ok(self.inner.index(index)))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<K, V, O> Default for AssocRegion<K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            inner: SliceRegion::default(),
+        }
+    }
+}
+
+/// An association list read from an [`AssocRegion`].
+///
+/// Entries read from the region are sorted by key, while a list borrowed from an owned vector
+/// can be in any order, so lookups in it scan all entries.
+#[allow(clippy::type_complexity)]
+pub struct ReadAssoc<'a, K, V, O = Entries<K, V>>(
+    Result<ReadSlice<'a, TupleABRegion<K, V>, O>, &'a [(K::Owned, V::Owned)]>,
+)
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>;
+
+impl<'a, K, V, O> ReadAssoc<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    /// Returns the value associated with `key`, or `None` if the list does not contain the key.
+    #[must_use]
+    pub fn get(&self, key: &K::Owned) -> Option<V::ReadItem<'_>>
+    where
+        K::Owned: Eq,
+        for<'b> K::ReadItem<'b>: Ord,
+    {
+        match &self.0 {
+            Ok(slice) => {
+                let needle = K::reborrow(IntoOwned::borrow_as(key));
+                let (mut lower, mut upper) = (0, slice.len());
+                while lower < upper {
+                    let middle = lower + (upper - lower) / 2;
+                    let (candidate, value) = slice.get(middle);
+                    match K::reborrow(candidate).cmp(&needle) {
+                        Ordering::Less => lower = middle + 1,
+                        Ordering::Equal => return Some(value),
+                        Ordering::Greater => upper = middle,
+                    }
+                }
+                None
+            }
+            Err(entries) => entries
+                .iter()
+                .find(|(candidate, _)| candidate == key)
+                .map(|(_, value)| IntoOwned::borrow_as(value)),
+        }
+    }
+
+    /// The number of entries in this list.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Ok(slice) => slice.len(),
+            Err(entries) => entries.len(),
+        }
+    }
+
+    /// Returns `true` if the list has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Ok(slice) => slice.is_empty(),
+            Err(entries) => entries.is_empty(),
+        }
+    }
+
+    /// Returns an iterator over the key-value pairs.
+    #[must_use]
+    pub fn iter(&self) -> ReadAssocIter<'a, K, V, O> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the keys.
+    pub fn keys(&self) -> impl Iterator<Item = K::ReadItem<'a>> + Clone {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values.
+    pub fn values(&self) -> impl Iterator<Item = V::ReadItem<'a>> + Clone {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K, V, O> Clone for ReadAssoc<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, O> Copy for ReadAssoc<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+}
+
+impl<K, V, O> Debug for ReadAssoc<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+    for<'a> K::ReadItem<'a>: Debug,
+    for<'a> V::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, O> IntoOwned<'a> for ReadAssoc<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Owned = Vec<(K::Owned, V::Owned)>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().map(IntoOwned::into_owned).collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend(self.iter().map(IntoOwned::into_owned));
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a, K, V, O> IntoIterator for ReadAssoc<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Item = (K::ReadItem<'a>, V::ReadItem<'a>);
+    type IntoIter = ReadAssocIter<'a, K, V, O>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Ok(slice) => ReadAssocIter(Ok(slice.into_iter())),
+            Err(entries) => ReadAssocIter(Err(entries.iter())),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`ReadAssoc`].
+#[allow(clippy::type_complexity)]
+pub struct ReadAssocIter<'a, K, V, O>(
+    Result<ReadSliceIter<'a, TupleABRegion<K, V>, O>, std::slice::Iter<'a, (K::Owned, V::Owned)>>,
+)
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>;
+
+impl<K, V, O> Clone for ReadAssocIter<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, K, V, O> Iterator for ReadAssocIter<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Item = (K::ReadItem<'a>, V::ReadItem<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Ok(iter) => iter.next(),
+            Err(iter) => iter
+                .next()
+                .map(|(key, value)| (IntoOwned::borrow_as(key), IntoOwned::borrow_as(value))),
+        }
+    }
+}
+
+impl<K, V, O, KT, VT> Push<Vec<(KT, VT)>> for AssocRegion<K, V, O>
+where
+    K: Region + Push<KT>,
+    V: Region + Push<VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+{
+    #[inline]
+    fn push(&mut self, mut item: Vec<(KT, VT)>) -> <AssocRegion<K, V, O> as Region>::Index {
+        item.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.inner.push(PushIter(item))
+    }
+}
+
+impl<'a, K, V, O, KT, VT> Push<&'a [(KT, VT)]> for AssocRegion<K, V, O>
+where
+    K: Region + Push<&'a KT>,
+    V: Region + Push<&'a VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+{
+    #[inline]
+    fn push(&mut self, item: &'a [(KT, VT)]) -> <AssocRegion<K, V, O> as Region>::Index {
+        let mut sorted: Vec<_> = item.iter().collect();
+        sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.inner.push(PushIter(sorted))
+    }
+}
+
+impl<'a, K, V, O, KT, VT, const N: usize> Push<&'a [(KT, VT); N]> for AssocRegion<K, V, O>
+where
+    K: Region + Push<&'a KT>,
+    V: Region + Push<&'a VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+{
+    #[inline]
+    fn push(&mut self, item: &'a [(KT, VT); N]) -> <AssocRegion<K, V, O> as Region>::Index {
+        self.push(&item[..])
+    }
+}
+
+impl<'a, K, V, O, KT, VT> Push<&'a Vec<(KT, VT)>> for AssocRegion<K, V, O>
+where
+    K: Region + Push<&'a KT>,
+    V: Region + Push<&'a VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+{
+    #[inline]
+    fn push(&mut self, item: &'a Vec<(KT, VT)>) -> <AssocRegion<K, V, O> as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl<'a, K, V, O> Push<ReadAssoc<'a, K, V, O>> for AssocRegion<K, V, O>
+where
+    K: Region + Push<<K as Region>::ReadItem<'a>>,
+    V: Region + Push<<V as Region>::ReadItem<'a>>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    K::ReadItem<'a>: Ord,
+{
+    #[inline]
+    fn push(&mut self, item: ReadAssoc<'a, K, V, O>) -> <AssocRegion<K, V, O> as Region>::Index {
+        match item.0 {
+            // Entries read from a region are sorted already.
+            Ok(_) => self.inner.push(PushIter(item)),
+            Err(_) => self.push(item.iter().collect::<Vec<_>>()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_sorted_lookup() {
+        let mut r = <AssocRegion<MirrorRegion<u32>, StringRegion>>::default();
+        let index = r.push(&[(1, "a"), (3, "c"), (2, "b")]);
+        let read = r.index(index);
+
+        assert_eq!(3, read.len());
+        assert!(read.iter().eq([(1, "a"), (2, "b"), (3, "c")]));
+        assert!(read.keys().eq([1, 2, 3]));
+        assert!(read.values().eq(["a", "b", "c"]));
+        assert_eq!(Some("b"), read.get(&2));
+        assert_eq!(None, read.get(&0));
+        assert_eq!(None, read.get(&4));
+        assert_eq!(
+            vec![
+                (1, "a".to_string()),
+                (2, "b".to_string()),
+                (3, "c".to_string())
+            ],
+            read.into_owned()
+        );
+        assert_eq!(r#"{1: "a", 2: "b", 3: "c"}"#, format!("{read:?}"));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let owned = vec![(5_u32, 50_u64), (4, 40)];
+        let read =
+            <ReadAssoc<MirrorRegion<u32>, MirrorRegion<u64>> as IntoOwned>::borrow_as(&owned);
+        assert_eq!(Some(40), read.get(&4));
+        assert_eq!(None, read.get(&6));
+
+        let mut c = FlatStack::<AssocRegion<MirrorRegion<u32>, MirrorRegion<u64>>>::default();
+        c.copy(read);
+        c.copy(Vec::<(u32, u64)>::new());
+        assert!(c.get(0).iter().eq([(4, 40), (5, 50)]));
+        assert!(c.get(1).is_empty());
+
+        c.copy(c.get(0).into_owned());
+        assert_eq!(Some(50), c.get(2).get(&5));
+    }
+}
@@ -0,0 +1,346 @@
+//! A region that bit-packs blocks of unsigned integers.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// The number of values in a block.
+const BLOCK_LEN: usize = 128;
+
+/// Describes a packed block of values.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Block {
+    /// The smallest value in the block, which we subtract from all values before packing.
+    min: u64,
+    /// The number of bits per packed value.
+    width: u32,
+    /// The offset of the first word of the block.
+    offset: usize,
+}
+
+/// A region for unsigned integers that packs blocks of values to the bits they require.
+///
+/// The region buffers values until it has a block of 128, and then determines the block's
+/// minimum and the number of bits required to represent the difference of each value to the
+/// minimum. It packs the differences into `u64` words using exactly as many bits, which is
+/// effective for columns where nearby values have similar magnitudes. Reading a value
+/// locates the block and unpacks the value.
+///
+/// The index is the position of the value in the region, which counts up from zero. An index
+/// container that detects strides, such as [`IndexOptimized`](super::index::IndexOptimized),
+/// stores such indexes in constant space.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::bitpack::BitPackRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <BitPackRegion<u64>>::default();
+///
+/// let indices: Vec<_> = (0..1000).map(|i| r.push(1_000_000 + i % 16)).collect();
+/// for (i, index) in indices.into_iter().enumerate() {
+///     assert_eq!(1_000_000 + i as u64 % 16, r.index(index));
+/// }
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BitPackRegion<T> {
+    /// Packed blocks.
+    blocks: Vec<Block>,
+    /// Storage for the packed values of all blocks.
+    words: Vec<u64>,
+    /// Values that do not yet form a complete block.
+    pending: Vec<T>,
+}
+
+impl<T> Default for BitPackRegion<T> {
+    fn default() -> Self {
+        Self {
+            blocks: Vec::default(),
+            words: Vec::default(),
+            pending: Vec::default(),
+        }
+    }
+}
+
+impl<T: Clone> Clone for BitPackRegion<T> {
+    fn clone(&self) -> Self {
+        Self {
+            blocks: self.blocks.clone(),
+            words: self.words.clone(),
+            pending: self.pending.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.blocks.clone_from(&source.blocks);
+        self.words.clone_from(&source.words);
+        self.pending.clone_from(&source.pending);
+    }
+}
+
+impl<T> BitPackRegion<T> {
+    /// Returns the number of values in the region.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.blocks.len() * BLOCK_LEN + self.pending.len()
+    }
+
+    /// Returns `true` if the region contains no values.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty() && self.pending.is_empty()
+    }
+
+    /// Packs `values` into a new block.
+    fn pack(&mut self, values: impl Iterator<Item = u64> + Clone) {
+        let min = values.clone().min().unwrap_or(0);
+        let max = values.clone().max().unwrap_or(0);
+        let width = u64::BITS - (max - min).leading_zeros();
+        let offset = self.words.len();
+        self.words.resize(offset + BLOCK_LEN * width as usize / 64, 0);
+        // A block of equal values has no packed representation.
+        if width > 0 {
+            for (position, value) in values.enumerate() {
+                let delta = value - min;
+                let bit = position * width as usize;
+                let (word, shift) = (offset + bit / 64, bit % 64);
+                self.words[word] |= delta << shift;
+                if shift + width as usize > 64 {
+                    self.words[word + 1] |= delta >> (64 - shift);
+                }
+            }
+        }
+        self.blocks.push(Block { min, width, offset });
+    }
+
+    /// Unpacks the value at `position` of `block`.
+    #[inline]
+    fn unpack(&self, block: &Block, position: usize) -> u64 {
+        let width = block.width as usize;
+        if width == 0 {
+            return block.min;
+        }
+        let bit = position * width;
+        let (word, shift) = (block.offset + bit / 64, bit % 64);
+        let mut delta = self.words[word] >> shift;
+        if shift + width > 64 {
+            delta |= self.words[word + 1] << (64 - shift);
+        }
+        let mask = u64::MAX >> (64 - width);
+        block.min + (delta & mask)
+    }
+}
+
+macro_rules! implement_for {
+    ($index_type:ty) => {
+        impl Region for BitPackRegion<$index_type> {
+            type Owned = $index_type;
+            type ReadItem<'a> = $index_type;
+            type Index = usize;
+
+            fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+            where
+                Self: 'a,
+            {
+                let mut this = Self::default();
+                this.reserve_regions(regions);
+                this
+            }
+
+            #[inline]
+            fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+                let block = index / BLOCK_LEN;
+                if let Some(block) = self.blocks.get(block) {
+                    // The value fits in the type because the region only packs values of it.
+                    self.unpack(block, index % BLOCK_LEN) as $index_type
+                } else {
+                    self.pending[index - self.blocks.len() * BLOCK_LEN]
+                }
+            }
+
+            fn reserve_regions<'a, I>(&mut self, regions: I)
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                self.blocks
+                    .reserve(regions.clone().map(|r| r.blocks.len()).sum());
+                self.words.reserve(regions.map(|r| r.words.len()).sum());
+                self.pending.reserve(BLOCK_LEN);
+            }
+
+            fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                self.blocks
+                    .try_reserve(regions.clone().map(|r| r.blocks.len()).sum())?;
+                self.words
+                    .try_reserve(regions.map(|r| r.words.len()).sum())?;
+                self.pending.try_reserve(BLOCK_LEN)
+            }
+
+            fn clear(&mut self) {
+                self.blocks.clear();
+                self.words.clear();
+                self.pending.clear();
+            }
+
+            fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+                let size_of_block = std::mem::size_of::<Block>();
+                callback(
+                    self.blocks.len() * size_of_block,
+                    self.blocks.capacity() * size_of_block,
+                );
+                let size_of_word = std::mem::size_of::<u64>();
+                callback(
+                    self.words.len() * size_of_word,
+                    self.words.capacity() * size_of_word,
+                );
+                let size_of_t = std::mem::size_of::<$index_type>();
+                callback(
+                    self.pending.len() * size_of_t,
+                    self.pending.capacity() * size_of_t,
+                );
+            }
+
+            #[inline]
+            fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+            where
+                Self: 'a,
+            {
+                item
+            }
+        }
+
+        impl Push<$index_type> for BitPackRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: $index_type) -> usize {
+                let index = self.len();
+                self.pending.push(item);
+                if self.pending.len() == BLOCK_LEN {
+                    let pending = std::mem::take(&mut self.pending);
+                    self.pack(pending.iter().map(|value| u64::from(*value)));
+                    self.pending = pending;
+                    self.pending.clear();
+                }
+                index
+            }
+        }
+
+        impl Push<&$index_type> for BitPackRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: &$index_type) -> usize {
+                self.push(*item)
+            }
+        }
+
+        impl Push<&&$index_type> for BitPackRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: &&$index_type) -> usize {
+                self.push(**item)
+            }
+        }
+
+        impl ReserveItems<$index_type> for BitPackRegion<$index_type> {
+            #[inline]
+            fn reserve_items<I>(&mut self, items: I)
+            where
+                I: Iterator<Item = $index_type> + Clone,
+            {
+                self.blocks.reserve(items.count() / BLOCK_LEN + 1);
+                self.pending.reserve(BLOCK_LEN);
+            }
+        }
+
+        impl<'a> ReserveItems<&'a $index_type> for BitPackRegion<$index_type> {
+            #[inline]
+            fn reserve_items<I>(&mut self, items: I)
+            where
+                I: Iterator<Item = &'a $index_type> + Clone,
+            {
+                self.reserve_items(items.copied());
+            }
+        }
+    };
+}
+
+implement_for!(u8);
+implement_for!(u16);
+implement_for!(u32);
+implement_for!(u64);
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::index::IndexOptimized;
+    use crate::{FlatStack, MirrorRegion};
+
+    use super::*;
+
+    /// Values whose magnitude changes from block to block, ending in a partial block.
+    fn column() -> Vec<u64> {
+        let mut values = Vec::new();
+        // Constant block, zero bits.
+        values.extend(std::iter::repeat(42).take(BLOCK_LEN));
+        // Small values.
+        values.extend((0..BLOCK_LEN as u64).map(|i| i % 5));
+        // Large values with a small spread, straddling word boundaries.
+        values.extend((0..BLOCK_LEN as u64).map(|i| (1 << 40) + i * 3));
+        // Full range.
+        values.extend((0..BLOCK_LEN as u64).map(|i| u64::MAX - i * (u64::MAX / 200)));
+        // Pending values.
+        values.extend(0..17);
+        values
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let values = column();
+        let mut r = <BitPackRegion<u64>>::default();
+        let indices: Vec<_> = values.iter().map(|value| r.push(value)).collect();
+        assert_eq!(values.len(), r.len());
+        assert_eq!(4, r.blocks.len());
+        assert_eq!([0, 3, 9, 64], [0, 1, 2, 3].map(|b| r.blocks[b].width));
+
+        for (value, index) in values.iter().zip(indices) {
+            assert_eq!(*value, r.index(index));
+        }
+    }
+
+    #[test]
+    fn test_small_types() {
+        let mut r = <BitPackRegion<u8>>::default();
+        let indices: Vec<_> = (0..=u8::MAX).map(|value| r.push(value)).collect();
+        for (value, index) in (0..=u8::MAX).zip(indices) {
+            assert_eq!(value, r.index(index));
+        }
+    }
+
+    #[test]
+    fn test_heap_size() {
+        let values = column();
+
+        let mut packed = FlatStack::<BitPackRegion<u64>, IndexOptimized>::default();
+        packed.extend(values.iter());
+        assert!(values.iter().copied().eq(packed.iter()));
+
+        let mut mirror = FlatStack::<MirrorRegion<u64>>::default();
+        mirror.extend(values.iter());
+
+        let (mut packed_size, mut mirror_size) = (0, 0);
+        packed.heap_size(|siz, _| packed_size += siz);
+        mirror.heap_size(|siz, _| mirror_size += siz);
+        assert!(
+            packed_size * 2 < mirror_size,
+            "{packed_size} not less than half of {mirror_size}"
+        );
+    }
+}
@@ -0,0 +1,338 @@
+//! A region that rejects items instead of growing past a bound.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::choose::CanMeasure;
+use crate::{Push, Region, ReserveItems, TryPush};
+
+/// A region that accepts at most a fixed number of items and bytes.
+///
+/// Wraps a region `R`, and rejects an item if storing it would exceed the maximum number of
+/// items or the byte budget. The bytes of the region are the bytes in use as reported by
+/// [`Region::heap_size`], and the region [measures](CanMeasure) an item before storing it.
+/// Byte, string, and slice regions, and regions of tuples, options, and results of them, can
+/// measure items. The budget applies to the bytes in use: the capacity of the inner region can
+/// exceed the budget, unless the capacity is reserved upfront.
+///
+/// [`TryPush`] returns the rejected item, while [`Push`] panics. A default region is unbounded.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::bounded::BoundedRegion;
+/// use flatcontainer::{OwnedRegion, Region, TryPush};
+/// let mut r = BoundedRegion::<OwnedRegion<u8>>::new(usize::MAX, 4);
+///
+/// let index = r.try_push(b"abc".as_slice()).unwrap();
+/// assert_eq!(Err(b"de".as_slice()), r.try_push(b"de".as_slice()));
+/// assert_eq!(b"abc", r.index(index));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BoundedRegion<R> {
+    /// Inner region.
+    inner: R,
+    /// The number of items in the region.
+    len: usize,
+    /// The maximum number of items.
+    max_len: usize,
+    /// The maximum number of bytes in use.
+    max_bytes: usize,
+}
+
+impl<R> BoundedRegion<R> {
+    /// Constructs an empty region that accepts at most `max_len` items and `max_bytes` bytes.
+    #[must_use]
+    pub fn new(max_len: usize, max_bytes: usize) -> Self
+    where
+        R: Default,
+    {
+        Self {
+            inner: R::default(),
+            len: 0,
+            max_len,
+            max_bytes,
+        }
+    }
+
+    /// Returns the number of items in the region.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the region contains no items.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the maximum number of items.
+    #[inline]
+    #[must_use]
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Returns the maximum number of bytes in use.
+    #[inline]
+    #[must_use]
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Returns the number of bytes the inner region uses.
+    #[must_use]
+    pub fn bytes(&self) -> usize
+    where
+        R: Region,
+    {
+        let mut bytes = 0;
+        self.inner.heap_size(|size, _| bytes += size);
+        bytes
+    }
+}
+
+impl<R: Default> Default for BoundedRegion<R> {
+    fn default() -> Self {
+        Self::new(usize::MAX, usize::MAX)
+    }
+}
+
+impl<R: Clone> Clone for BoundedRegion<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            len: self.len,
+            max_len: self.max_len,
+            max_bytes: self.max_bytes,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.len = source.len;
+        self.max_len = source.max_len;
+        self.max_bytes = source.max_bytes;
+    }
+}
+
+impl<R: Region> Region for BoundedRegion<R> {
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    type Index = R::Index;
+
+    /// Constructs a region with bounds large enough to absorb the contents of all `regions`,
+    /// which are the sums of their bounds.
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let sum =
+            |bound: fn(&Self) -> usize| regions.clone().map(bound).fold(0, usize::saturating_add);
+        Self {
+            inner: R::merge_regions(regions.clone().map(|r| &r.inner)),
+            len: 0,
+            max_len: sum(|r| r.max_len),
+            max_bytes: sum(|r| r.max_bytes),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        self.inner.last_index()
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.len = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+impl<R, T> TryPush<T> for BoundedRegion<R>
+where
+    R: Push<T> + CanMeasure<T>,
+{
+    /// The rejected item.
+    type Error = T;
+
+    #[inline]
+    fn try_push(&mut self, item: T) -> Result<Self::Index, Self::Error> {
+        let bytes = self.bytes().saturating_add(self.inner.measure(&item));
+        if self.len >= self.max_len || bytes > self.max_bytes {
+            return Err(item);
+        }
+        self.len += 1;
+        Ok(self.inner.push(item))
+    }
+}
+
+impl<R, T> Push<T> for BoundedRegion<R>
+where
+    R: Push<T> + CanMeasure<T>,
+{
+    /// Pushes an item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item exceeds the bounds of the region.
+    #[inline]
+    fn push(&mut self, item: T) -> Self::Index {
+        let Ok(index) = self.try_push(item) else {
+            panic!(
+                "BoundedRegion: item exceeds bounds of {} items and {} bytes",
+                self.max_len, self.max_bytes
+            );
+        };
+        index
+    }
+}
+
+impl<R, T> ReserveItems<T> for BoundedRegion<R>
+where
+    R: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::tuple::TupleABRegion;
+    use crate::{MirrorRegion, OptionRegion, OwnedRegion, SliceRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_bounds() {
+        let mut r = BoundedRegion::<OwnedRegion<u16>>::new(4, 20);
+        let mut indices = Vec::new();
+        let item = [1, 2, 3].as_slice();
+        loop {
+            match r.try_push(item) {
+                Ok(index) => indices.push(index),
+                Err(rejected) => {
+                    assert_eq!(item, rejected);
+                    break;
+                }
+            }
+            assert!(r.bytes() <= r.max_bytes());
+        }
+        // Three items of six bytes each, the fourth exceeds the byte budget.
+        assert_eq!(3, r.len());
+        assert_eq!(18, r.bytes());
+
+        // A small item fits the budget, but exceeds the number of items afterwards.
+        assert!(r.try_push([4].as_slice()).is_ok());
+        assert_eq!(Err([].as_slice()), r.try_push([].as_slice()));
+        assert_eq!(4, r.len());
+        assert!(r.bytes() <= r.max_bytes());
+        for index in indices {
+            assert_eq!(item, r.index(index));
+        }
+
+        r.clear();
+        assert!(r.is_empty());
+        assert!(r.try_push(item).is_ok());
+    }
+
+    #[test]
+    fn test_bounds_composite() {
+        type R = BoundedRegion<
+            TupleABRegion<SliceRegion<StringRegion>, OptionRegion<MirrorRegion<u64>>>,
+        >;
+        // Four bytes of strings, two slice indices, and nothing for the mirrored value.
+        let item = (["ab", "cd"].as_slice(), Some(7_u64));
+        let size = 4 + 2 * std::mem::size_of::<(usize, usize)>();
+        let mut r = R::new(usize::MAX, 3 * size + size / 2);
+        assert_eq!(size, r.inner.measure(&item));
+
+        let index = r.try_push(item).unwrap();
+        assert_eq!(size, r.bytes());
+        assert!(r.try_push(item).is_ok());
+        assert!(r.try_push(item).is_ok());
+        assert_eq!(3 * size, r.bytes());
+        assert_eq!(Err(item), r.try_push(item));
+        assert!(r.try_push(([""; 0].as_slice(), None)).is_ok());
+        assert_eq!(4, r.len());
+
+        let (strings, value) = r.index(index);
+        assert!(strings.iter().eq(["ab", "cd"]));
+        assert_eq!(Some(7), value);
+    }
+
+    #[test]
+    #[should_panic(expected = "BoundedRegion: item exceeds bounds of 1 items and 16 bytes")]
+    fn test_push_exceeds() {
+        let mut r = BoundedRegion::<OwnedRegion<u8>>::new(1, 16);
+        let _ = r.push(b"a".as_slice());
+        let _ = r.push(b"b".as_slice());
+    }
+
+    #[test]
+    fn test_merge() {
+        let a = BoundedRegion::<OwnedRegion<u8>>::new(1, 10);
+        let b = BoundedRegion::<OwnedRegion<u8>>::new(2, usize::MAX);
+        let merged = BoundedRegion::merge_regions([&a, &b].into_iter());
+        assert_eq!(3, merged.max_len());
+        assert_eq!(usize::MAX, merged.max_bytes());
+    }
+}
@@ -0,0 +1,165 @@
+//! A region that exposes the bytes of a byte region to generic code.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::choose::CanMeasure;
+use crate::{OwnedRegion, Push, Region, RegionLen, ReserveItems};
+
+/// A region for byte slices that reports them through [`Region::index_raw_bytes`].
+///
+/// A generic region such as [`OwnedRegion`] cannot tell that its elements are bytes, and
+/// returns `None` from [`Region::index_raw_bytes`] even when it stores `u8`. Wrapping a region
+/// that reads byte slices in this region provides the byte view to generic code, without
+/// changing how the inner region stores the slices.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::bytes::BytesRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <BytesRegion>::default();
+///
+/// let index = r.push(b"abc");
+/// assert_eq!(b"abc", r.index(index));
+/// assert_eq!(Some(b"abc".as_slice()), r.index_raw_bytes(index));
+/// ```
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BytesRegion<R = OwnedRegion<u8>> {
+    /// Inner region.
+    inner: R,
+}
+
+impl<R: Clone> Clone for BytesRegion<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<R> Region for BytesRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
+{
+    type Owned = Vec<u8>;
+    type ReadItem<'a> = &'a [u8] where Self: 'a;
+    type Index = R::Index;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: R::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    /// Returns the bytes of the slice.
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        Some(self.inner.index(index))
+    }
+
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        self.inner.last_index()
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<R> RegionLen for BytesRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + RegionLen + 'a,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<R, T> Push<T> for BytesRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + Push<T> + 'a,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> Self::Index {
+        self.inner.push(item)
+    }
+}
+
+impl<R, T> CanMeasure<T> for BytesRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + CanMeasure<T> + 'a,
+{
+    #[inline]
+    fn measure(&self, item: &T) -> usize {
+        self.inner.measure(item)
+    }
+}
+
+impl<R, T> ReserveItems<T> for BytesRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + ReserveItems<T> + 'a,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
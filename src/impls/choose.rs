@@ -0,0 +1,226 @@
+//! A region that chooses between two representations per item.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// Measure the storage a region requires for an item.
+///
+/// Used by [`ChooseRegion`] to compare representations before pushing an item.
+pub trait CanMeasure<T>: Region {
+    /// Returns the number of bytes the region would occupy to store `item`, without storing it.
+    fn measure(&self, item: &T) -> usize;
+}
+
+/// A region that stores each item in the one of two regions that represents it more compactly.
+///
+/// On push, the region asks both regions to [measure](CanMeasure) the item, and pushes it
+/// into `A` if it is strictly smaller there, and into `B` otherwise. The index records the
+/// choice. Both regions need to present items as the same read item type.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::choose::{ChooseIndex, ChooseRegion};
+/// use flatcontainer::impls::codec::{CodecRegion, DictionaryCodec};
+/// use flatcontainer::{OwnedRegion, Push, Region};
+/// let mut r = <ChooseRegion<CodecRegion<DictionaryCodec>, OwnedRegion<u8>>>::default();
+///
+/// // A codec without statistics cannot compress, so the region stores the data raw.
+/// let index = r.push(b"abc".as_slice());
+/// assert!(matches!(index, ChooseIndex::B(_)));
+/// assert_eq!(b"abc", r.index(index));
+/// ```
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChooseRegion<A, B> {
+    /// Items that are smaller in the first representation.
+    a: A,
+    /// All other items.
+    b: B,
+}
+
+/// The index of a [`ChooseRegion`], recording which region stores the item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChooseIndex<A, B> {
+    /// The item is stored in the first region.
+    A(A),
+    /// The item is stored in the second region.
+    B(B),
+}
+
+impl<A: Clone, B: Clone> Clone for ChooseRegion<A, B> {
+    fn clone(&self) -> Self {
+        Self {
+            a: self.a.clone(),
+            b: self.b.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.a.clone_from(&source.a);
+        self.b.clone_from(&source.b);
+    }
+}
+
+impl<A, B> Region for ChooseRegion<A, B>
+where
+    A: Region,
+    for<'a> B: Region<Owned = A::Owned, ReadItem<'a> = A::ReadItem<'a>> + 'a,
+{
+    type Owned = A::Owned;
+    type ReadItem<'a> = A::ReadItem<'a> where Self: 'a;
+    type Index = ChooseIndex<A::Index, B::Index>;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            a: A::merge_regions(regions.clone().map(|r| &r.a)),
+            b: B::merge_regions(regions.map(|r| &r.b)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        match index {
+            ChooseIndex::A(index) => self.a.index(index),
+            ChooseIndex::B(index) => self.b.index(index),
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.a.reserve_regions(regions.clone().map(|r| &r.a));
+        self.b.reserve_regions(regions.map(|r| &r.b));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.a.try_reserve_regions(regions.clone().map(|r| &r.a))?;
+        self.b.try_reserve_regions(regions.map(|r| &r.b))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.a.clear();
+        self.b.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.a.heap_size(&mut callback);
+        self.b.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        A::reborrow(item)
+    }
+}
+
+impl<A, B, T> Push<T> for ChooseRegion<A, B>
+where
+    A: Push<T> + CanMeasure<T>,
+    for<'a> B:
+        Region<Owned = A::Owned, ReadItem<'a> = A::ReadItem<'a>> + Push<T> + CanMeasure<T> + 'a,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> <ChooseRegion<A, B> as Region>::Index {
+        if self.a.measure(&item) < self.b.measure(&item) {
+            ChooseIndex::A(self.a.push(item))
+        } else {
+            ChooseIndex::B(self.b.push(item))
+        }
+    }
+}
+
+impl<A, B, T> ReserveItems<T> for ChooseRegion<A, B>
+where
+    A: ReserveItems<T>,
+    for<'a> B: Region<Owned = A::Owned, ReadItem<'a> = A::ReadItem<'a>> + 'a,
+{
+    /// Reserves space in the first region, which we expect to store most items.
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.a.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::codec::{CodecRegion, DictionaryCodec};
+    use crate::{FlatStack, OwnedRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_choose_codec() {
+        let repetitive = b"repetitive".as_slice();
+        let incompressible = b"\x7f\x03incompressible".as_slice();
+
+        // Train a dictionary on repetitive data.
+        let mut training = CodecRegion::<DictionaryCodec>::default();
+        for _ in 0..16 {
+            let _ = training.push(repetitive);
+        }
+        let mut r = ChooseRegion::<CodecRegion<DictionaryCodec>, OwnedRegion<u8>> {
+            a: CodecRegion::merge_regions(std::iter::once(&training)),
+            b: OwnedRegion::default(),
+        };
+
+        let first = r.push(repetitive);
+        let second = r.push(incompressible);
+        let third = r.push(repetitive);
+        assert!(matches!(first, ChooseIndex::A(_)));
+        assert!(matches!(second, ChooseIndex::B(_)));
+        assert!(matches!(third, ChooseIndex::A(_)));
+        assert_eq!(repetitive, r.index(first));
+        assert_eq!(incompressible, r.index(second));
+        assert_eq!(repetitive, r.index(third));
+
+        // The repetitive items occupy one byte each.
+        let (mut a, mut b) = (0, 0);
+        r.a.heap_size(|siz, _| a += siz);
+        r.b.heap_size(|siz, _| b += siz);
+        assert_eq!(2, a);
+        assert_eq!(incompressible.len(), b);
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let mut c =
+            FlatStack::<ChooseRegion<CodecRegion<DictionaryCodec>, OwnedRegion<u8>>>::default();
+        c.copy([1_u8, 2, 3].as_slice());
+        c.copy([4_u8].as_slice());
+        assert!(c.iter().eq([[1, 2, 3].as_slice(), &[4]]));
+        // Ties go to the second region.
+        assert!(c
+            .indices
+            .iter()
+            .all(|index| matches!(index, ChooseIndex::B(_))));
+
+        c.clear();
+        assert!(c.iter().next().is_none());
+    }
+}
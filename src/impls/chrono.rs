@@ -0,0 +1,279 @@
+//! Regions for [`chrono`] date and time types.
+//!
+//! Dates and date-times are stored as integers relative to the Unix epoch, which is more
+//! compact than chrono's internal representation and supports serialization without enabling
+//! chrono's `serde` feature.
+
+use ::chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, Push, Region, RegionPreference, ReserveItems};
+
+/// Days between 0001-01-01 and 1970-01-01.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+/// A region for [`NaiveDate`]s. Stores dates as days since the Unix epoch, which is negative
+/// for dates before 1970-01-01.
+///
+/// Like [`MirrorRegion`](crate::MirrorRegion), the region does not own any storage, and
+/// the index is the encoded value.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use flatcontainer::impls::chrono::NaiveDateRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = NaiveDateRegion;
+///
+/// let date = NaiveDate::from_ymd_opt(1969, 7, 20).unwrap();
+/// let index = r.push(date);
+/// assert_eq!(-165, index);
+/// assert_eq!(date, r.index(index));
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NaiveDateRegion;
+
+impl NaiveDateRegion {
+    #[inline]
+    fn encode(date: NaiveDate) -> i32 {
+        date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+    }
+
+    #[inline]
+    fn decode(days: i32) -> NaiveDate {
+        NaiveDate::from_num_days_from_ce_opt(days + UNIX_EPOCH_DAYS_FROM_CE)
+            .expect("Index must originate from a valid date")
+    }
+}
+
+/// A region for [`NaiveDateTime`]s. Stores date-times as a pair of seconds since the Unix epoch
+/// and nanoseconds within the second, which covers the whole range of [`NaiveDateTime`],
+/// including leap seconds.
+///
+/// Like [`MirrorRegion`](crate::MirrorRegion), the region does not own any storage, and
+/// the index is the encoded value.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use flatcontainer::impls::chrono::NaiveDateTimeRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = NaiveDateTimeRegion;
+///
+/// let date_time = NaiveDate::from_ymd_opt(1970, 1, 1)
+///     .unwrap()
+///     .and_hms_nano_opt(0, 0, 1, 500)
+///     .unwrap();
+/// let index = r.push(date_time);
+/// assert_eq!((1, 500), index);
+/// assert_eq!(date_time, r.index(index));
+/// ```
+#[derive(Default, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NaiveDateTimeRegion;
+
+impl NaiveDateTimeRegion {
+    #[inline]
+    fn encode(date_time: NaiveDateTime) -> (i64, u32) {
+        let date_time = date_time.and_utc();
+        (date_time.timestamp(), date_time.timestamp_subsec_nanos())
+    }
+
+    #[inline]
+    fn decode((secs, nanos): (i64, u32)) -> NaiveDateTime {
+        DateTime::from_timestamp(secs, nanos)
+            .expect("Index must originate from a valid date-time")
+            .naive_utc()
+    }
+}
+
+macro_rules! implement_for {
+    ($region:ty, $owned:ty, $index:ty) => {
+        impl RegionPreference for $owned {
+            type Owned = Self;
+            type Region = $region;
+        }
+
+        impl Region for $region {
+            type Owned = $owned;
+            type ReadItem<'a> = $owned;
+            type Index = $index;
+
+            #[inline]
+            fn merge_regions<'a>(_regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+            where
+                Self: 'a,
+            {
+                Self
+            }
+
+            #[inline]
+            fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+                Self::decode(index)
+            }
+
+            #[inline(always)]
+            fn reserve_regions<'a, I>(&mut self, _regions: I)
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                // No storage
+            }
+
+            #[inline(always)]
+            fn clear(&mut self) {
+                // No storage
+            }
+
+            #[inline]
+            fn heap_size<F: FnMut(usize, usize)>(&self, _callback: F) {
+                // No storage
+            }
+
+            #[inline]
+            fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+            where
+                Self: 'a,
+            {
+                item
+            }
+        }
+
+        impl Push<$owned> for $region {
+            #[inline]
+            fn push(&mut self, item: $owned) -> $index {
+                Self::encode(item)
+            }
+        }
+
+        impl Push<&$owned> for $region {
+            #[inline]
+            fn push(&mut self, item: &$owned) -> $index {
+                Self::encode(*item)
+            }
+        }
+
+        impl Push<&&$owned> for $region {
+            #[inline]
+            fn push(&mut self, item: &&$owned) -> $index {
+                Self::encode(**item)
+            }
+        }
+
+        impl ReserveItems<$owned> for $region {
+            #[inline(always)]
+            fn reserve_items<I>(&mut self, _items: I)
+            where
+                I: Iterator<Item = $owned> + Clone,
+            {
+                // No storage
+            }
+        }
+
+        impl<'a> ReserveItems<&'a $owned> for $region {
+            #[inline(always)]
+            fn reserve_items<I>(&mut self, _items: I)
+            where
+                I: Iterator<Item = &'a $owned> + Clone,
+            {
+                // No storage
+            }
+        }
+
+        impl<'a> IntoOwned<'a> for $owned {
+            type Owned = $owned;
+
+            #[inline]
+            fn into_owned(self) -> Self::Owned {
+                self
+            }
+
+            #[inline]
+            fn clone_onto(self, other: &mut Self::Owned) {
+                *other = self;
+            }
+
+            #[inline]
+            fn borrow_as(owned: &'a Self::Owned) -> Self {
+                *owned
+            }
+        }
+    };
+}
+
+implement_for!(NaiveDateRegion, NaiveDate, i32);
+implement_for!(NaiveDateTimeRegion, NaiveDateTime, (i64, u32));
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_date_roundtrip() {
+        let dates = [
+            NaiveDate::from_ymd_opt(1970, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(1969, 12, 31).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap(),
+            NaiveDate::from_ymd_opt(1, 1, 1).unwrap(),
+            NaiveDate::from_ymd_opt(-44, 3, 15).unwrap(),
+            NaiveDate::MIN,
+            NaiveDate::MAX,
+        ];
+
+        let mut r = NaiveDateRegion;
+        for date in dates {
+            let index = r.push(date);
+            assert_eq!(date, r.index(index));
+        }
+        assert_eq!(0, r.push(dates[0]));
+        assert_eq!(-1, r.push(dates[1]));
+    }
+
+    #[test]
+    fn test_date_time_roundtrip() {
+        let date_times = [
+            DateTime::UNIX_EPOCH.naive_utc(),
+            NaiveDate::from_ymd_opt(1969, 12, 31)
+                .unwrap()
+                .and_hms_nano_opt(23, 59, 59, 999_999_999)
+                .unwrap(),
+            NaiveDate::from_ymd_opt(1900, 6, 15)
+                .unwrap()
+                .and_hms_milli_opt(12, 30, 0, 250)
+                .unwrap(),
+            NaiveDate::from_ymd_opt(2016, 12, 31)
+                .unwrap()
+                .and_hms_nano_opt(23, 59, 59, 1_500_000_000)
+                .unwrap(),
+            NaiveDateTime::MIN,
+            NaiveDateTime::MAX,
+        ];
+
+        let mut r = NaiveDateTimeRegion;
+        for date_time in date_times {
+            let index = r.push(date_time);
+            assert_eq!(date_time, r.index(index));
+        }
+        assert_eq!((-1, 999_999_999), r.push(date_times[1]));
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let dates = [
+            NaiveDate::from_ymd_opt(1955, 11, 5).unwrap(),
+            NaiveDate::from_ymd_opt(1985, 10, 26).unwrap(),
+            NaiveDate::from_ymd_opt(2015, 10, 21).unwrap(),
+        ];
+        let mut c = FlatStack::default_impl::<NaiveDate>();
+        c.extend(&dates);
+        assert!(dates.iter().copied().eq(c.iter()));
+    }
+}
@@ -0,0 +1,306 @@
+//! A region that stores CIDR ranges of IP addresses.
+
+use std::collections::TryReserveError;
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::tuple::TupleABRegion;
+use crate::{IntoOwned, MirrorRegion, Push, Region, ReserveItems};
+
+/// The number of bits an IPv4 address is offset by in its IPv4-mapped IPv6 address.
+const IPV4_MAPPED_OFFSET: u8 = 96;
+
+/// A CIDR range of IP addresses, which is an address and the length of its prefix in bits.
+///
+/// IPv4 ranges are represented as ranges of IPv4-mapped IPv6 addresses, which lets ranges of
+/// both families share a representation. Consequently, an IPv6 range within `::ffff:0:0/96`
+/// with a prefix of at least 96 bits reads as the equivalent IPv4 range.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use flatcontainer::impls::cidr::Cidr;
+/// let cidr = Cidr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 0)), 16);
+/// assert!(cidr.contains(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1))));
+/// assert_eq!("192.168.0.0/16", cidr.to_string());
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Cidr {
+    /// The address, with IPv4 addresses mapped to IPv6.
+    address: u128,
+    /// The length of the prefix in bits, relative to `address`.
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Constructs a range from an address and the length of its prefix in bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix length exceeds the number of bits of the address.
+    #[must_use]
+    pub fn new(address: IpAddr, prefix_len: u8) -> Self {
+        let max = match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        assert!(
+            prefix_len <= max,
+            "Cidr: prefix length {prefix_len} exceeds {max} for {address}"
+        );
+        match address {
+            IpAddr::V4(address) => Self {
+                address: u128::from(address.to_ipv6_mapped()),
+                prefix: prefix_len + IPV4_MAPPED_OFFSET,
+            },
+            IpAddr::V6(address) => Self {
+                address: u128::from(address),
+                prefix: prefix_len,
+            },
+        }
+    }
+
+    /// Returns the address of the range.
+    #[must_use]
+    pub fn addr(&self) -> IpAddr {
+        self.ipv4()
+            .map_or_else(|| IpAddr::V6(Ipv6Addr::from(self.address)), IpAddr::V4)
+    }
+
+    /// Returns the length of the prefix in bits, relative to the family of [`Cidr::addr`].
+    #[must_use]
+    pub fn prefix_len(&self) -> u8 {
+        if self.ipv4().is_some() {
+            self.prefix - IPV4_MAPPED_OFFSET
+        } else {
+            self.prefix
+        }
+    }
+
+    /// Returns `true` if the range contains `ip`.
+    #[must_use]
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = match ip {
+            IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+            IpAddr::V6(ip) => u128::from(ip),
+        };
+        let mask = u128::MAX
+            .checked_shl(128 - u32::from(self.prefix))
+            .unwrap_or(0);
+        (ip ^ self.address) & mask == 0
+    }
+
+    /// Returns the IPv4 address if this is an IPv4 range.
+    fn ipv4(&self) -> Option<Ipv4Addr> {
+        if self.prefix >= IPV4_MAPPED_OFFSET {
+            Ipv6Addr::from(self.address).to_ipv4_mapped()
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Cidr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.addr(), self.prefix_len())
+    }
+}
+
+impl<'a> IntoOwned<'a> for Cidr {
+    type Owned = (IpAddr, u8);
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        (self.addr(), self.prefix_len())
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self.into_owned();
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self::new(owned.0, owned.1)
+    }
+}
+
+/// A region for CIDR ranges of IPv4 and IPv6 addresses.
+///
+/// Stores the address and the prefix length of each range in a tuple of mirror regions, which
+/// makes the index the representation of the range. The read item is a [`Cidr`], which tests
+/// whether an address is in the range.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use flatcontainer::impls::cidr::CidrRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = CidrRegion::default();
+///
+/// let index = r.push((IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), 8));
+/// assert!(r.index(index).contains(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))));
+/// assert!(!r.index(index).contains(IpAddr::V4(Ipv4Addr::new(11, 0, 0, 1))));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CidrRegion {
+    /// Storage for the mapped addresses and prefix lengths.
+    inner: TupleABRegion<MirrorRegion<u128>, MirrorRegion<u8>>,
+}
+
+impl Region for CidrRegion {
+    type Owned = (IpAddr, u8);
+    type ReadItem<'a> = Cidr;
+    type Index = (u128, u8);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: TupleABRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let (address, prefix) = self.inner.index(index);
+        Cidr { address, prefix }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<Cidr> for CidrRegion {
+    #[inline]
+    fn push(&mut self, item: Cidr) -> (u128, u8) {
+        self.inner.push((item.address, item.prefix))
+    }
+}
+
+impl Push<(IpAddr, u8)> for CidrRegion {
+    /// Pushes the range of `address` with a prefix of the given length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefix length exceeds the number of bits of the address.
+    #[inline]
+    fn push(&mut self, (address, prefix_len): (IpAddr, u8)) -> (u128, u8) {
+        self.push(Cidr::new(address, prefix_len))
+    }
+}
+
+impl Push<&(IpAddr, u8)> for CidrRegion {
+    #[inline]
+    fn push(&mut self, item: &(IpAddr, u8)) -> (u128, u8) {
+        self.push(*item)
+    }
+}
+
+impl<T> ReserveItems<T> for CidrRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    fn v4(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+    }
+
+    #[test]
+    fn test_ipv4() {
+        let mut r = CidrRegion::default();
+        let index = r.push((v4(10, 0, 0, 0), 8));
+        let cidr = r.index(index);
+
+        assert!(cidr.contains(v4(10, 1, 2, 3)));
+        assert!(cidr.contains(v4(10, 255, 255, 255)));
+        assert!(!cidr.contains(v4(11, 0, 0, 1)));
+        assert_eq!(v4(10, 0, 0, 0), cidr.addr());
+        assert_eq!(8, cidr.prefix_len());
+        assert_eq!((v4(10, 0, 0, 0), 8), cidr.into_owned());
+        assert_eq!("10.0.0.0/8", cidr.to_string());
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let net: IpAddr = "2001:db8::".parse().unwrap();
+        let mut c = FlatStack::<CidrRegion>::default();
+        c.copy((net, 32));
+        c.copy((net, 128));
+        c.copy((v4(0, 0, 0, 0), 0));
+        c.copy((net, 0));
+
+        let inside: IpAddr = "2001:db8:1::1".parse().unwrap();
+        let outside: IpAddr = "2001:db9::1".parse().unwrap();
+        assert!(c.get(0).contains(inside));
+        assert!(!c.get(0).contains(outside));
+        assert!(c.get(1).contains(net));
+        assert!(!c.get(1).contains(inside));
+        assert_eq!("2001:db8::/32", c.get(0).to_string());
+
+        // The empty IPv4 prefix covers all IPv4 addresses, but no other IPv6 addresses.
+        assert!(c.get(2).contains(v4(255, 255, 255, 255)));
+        assert!(!c.get(2).contains(inside));
+        assert!(c.get(3).contains(v4(1, 2, 3, 4)));
+        assert!(c.get(3).contains(outside));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cidr: prefix length 33 exceeds 32 for 10.0.0.0")]
+    fn test_prefix_too_long() {
+        let mut r = CidrRegion::default();
+        let _ = r.push((v4(10, 0, 0, 0), 33));
+    }
+}
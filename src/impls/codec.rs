@@ -1,5 +1,11 @@
 //! A region that encodes its contents.
 
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::choose::CanMeasure;
 use crate::{OwnedRegion, Push, Region};
 
 pub use self::misra_gries::MisraGries;
@@ -81,6 +87,24 @@ impl<C: Clone, R: Clone> Clone for CodecRegion<C, R> {
     }
 }
 
+impl<C: Codec, R> CodecRegion<C, R> {
+    /// Marks the end of a batch of data, allowing the codec to adapt to the statistics it
+    /// observed since the last batch boundary. Data pushed previously remains readable.
+    pub fn seal_batch(&mut self) {
+        self.codec.on_batch_boundary();
+    }
+}
+
+impl<C: Codec, R> CodecRegion<C, R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
+{
+    /// Decodes the item at `index`, appending it to `output`, see [`Codec::decode_into`].
+    pub fn index_into(&self, index: <Self as Region>::Index, output: &mut Vec<u8>) {
+        self.codec.decode_into(self.inner.index(index), output);
+    }
+}
+
 impl<C: Codec, R> Region for CodecRegion<C, R>
 where
     for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
@@ -108,6 +132,11 @@ where
         self.codec.decode(self.inner.index(index))
     }
 
+    /// Returns the number of encoded bytes of the item at `index`.
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.index(index).len()
+    }
+
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
         Self: 'a,
@@ -116,6 +145,14 @@ where
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     fn clear(&mut self) {
         self.inner.clear();
         self.codec = Default::default();
@@ -143,10 +180,233 @@ where
     }
 }
 
+impl<C: Codec, R> CanMeasure<&[u8]> for CodecRegion<C, R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + Push<&'a [u8]> + 'a,
+{
+    fn measure(&self, item: &&[u8]) -> usize {
+        self.codec.encoded_len(item)
+    }
+}
+
+/// The number of raw bytes after which a [`LazyCodecRegion`] starts encoding by default.
+const LAZY_CODEC_THRESHOLD: usize = 1 << 16;
+
+/// A region that stores byte slices raw until it observed enough data to make encoding
+/// worthwhile, and encodes subsequent slices in a codec `C`.
+///
+/// The region counts the bytes it stores raw, and once they reach the threshold, it passes
+/// new slices to a [`CodecRegion`]. The codec observes the raw slices, and adapts to them when
+/// the region reaches the threshold. Previously stored slices stay raw, and the index records
+/// where the region stored a slice.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::codec::{DictionaryCodec, LazyCodecRegion};
+/// use flatcontainer::{Push, Region};
+/// let mut r = LazyCodecRegion::<DictionaryCodec>::with_threshold(4);
+///
+/// let raw = r.push(b"abc".as_slice());
+/// let raw_too = r.push(b"def".as_slice());
+/// let encoded = r.push(b"ghi".as_slice());
+///
+/// assert!(!raw.is_encoded());
+/// assert!(!raw_too.is_encoded());
+/// assert!(encoded.is_encoded());
+/// assert_eq!(b"ghi", r.index(encoded));
+/// ```
+#[derive(Debug)]
+pub struct LazyCodecRegion<C, R = OwnedRegion<u8>> {
+    /// Slices pushed before reaching the threshold.
+    raw: R,
+    /// Slices pushed after reaching the threshold.
+    encoded: CodecRegion<C, R>,
+    /// The number of bytes stored in `raw`.
+    raw_bytes: usize,
+    /// The number of raw bytes after which to start encoding.
+    threshold: usize,
+    /// Whether the threshold was reached.
+    encoding: bool,
+}
+
+/// The index of a [`LazyCodecRegion`], recording whether the slice was stored raw or encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LazyCodecIndex<I> {
+    /// The slice is stored without encoding.
+    Raw(I),
+    /// The slice is stored encoded.
+    Encoded(I),
+}
+
+impl<C: Codec, R: Default> LazyCodecRegion<C, R> {
+    /// Constructs an empty region that starts encoding once it stored `threshold` raw bytes.
+    #[must_use]
+    pub fn with_threshold(threshold: usize) -> Self {
+        Self {
+            raw: R::default(),
+            encoded: CodecRegion::default(),
+            raw_bytes: 0,
+            threshold,
+            encoding: false,
+        }
+    }
+}
+
+impl<I> LazyCodecIndex<I> {
+    /// Returns `true` if the slice is stored encoded.
+    #[must_use]
+    pub fn is_encoded(&self) -> bool {
+        matches!(self, LazyCodecIndex::Encoded(_))
+    }
+}
+
+impl<C: Codec, R> LazyCodecRegion<C, R> {
+    /// Marks the end of a batch of data, see [`CodecRegion::seal_batch`].
+    pub fn seal_batch(&mut self) {
+        self.encoded.seal_batch();
+    }
+}
+
+impl<C: Codec, R: Default> Default for LazyCodecRegion<C, R> {
+    fn default() -> Self {
+        Self::with_threshold(LAZY_CODEC_THRESHOLD)
+    }
+}
+
+impl<C: Clone, R: Clone> Clone for LazyCodecRegion<C, R> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            encoded: self.encoded.clone(),
+            raw_bytes: self.raw_bytes,
+            threshold: self.threshold,
+            encoding: self.encoding,
+        }
+    }
+    fn clone_from(&mut self, source: &Self) {
+        self.raw.clone_from(&source.raw);
+        self.encoded.clone_from(&source.encoded);
+        self.raw_bytes = source.raw_bytes;
+        self.threshold = source.threshold;
+        self.encoding = source.encoding;
+    }
+}
+
+impl<C: Codec, R> Region for LazyCodecRegion<C, R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
+{
+    type Owned = Vec<u8>;
+    type ReadItem<'a> = &'a [u8]
+    where
+        Self: 'a;
+
+    type Index = LazyCodecIndex<R::Index>;
+
+    /// Construct a region that can absorb the contents of `regions` in the future. The region
+    /// encodes from the start if any of the regions reached its threshold.
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            raw: R::merge_regions(regions.clone().map(|r| &r.raw)),
+            encoded: CodecRegion::merge_regions(regions.clone().map(|r| &r.encoded)),
+            raw_bytes: 0,
+            threshold: regions
+                .clone()
+                .map(|r| r.threshold)
+                .min()
+                .unwrap_or(LAZY_CODEC_THRESHOLD),
+            encoding: regions.into_iter().any(|r| r.encoding),
+        }
+    }
+
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        match index {
+            LazyCodecIndex::Raw(index) => self.raw.index(index),
+            LazyCodecIndex::Encoded(index) => self.encoded.index(index),
+        }
+    }
+
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        match index {
+            LazyCodecIndex::Raw(index) => self.raw.item_storage_size(index),
+            LazyCodecIndex::Encoded(index) => self.encoded.item_storage_size(index),
+        }
+    }
+
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.raw.reserve_regions(regions.clone().map(|r| &r.raw));
+        self.encoded.reserve_regions(regions.map(|r| &r.encoded));
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.raw
+            .try_reserve_regions(regions.clone().map(|r| &r.raw))?;
+        self.encoded
+            .try_reserve_regions(regions.map(|r| &r.encoded))
+    }
+
+    fn clear(&mut self) {
+        self.raw.clear();
+        self.encoded.clear();
+        self.raw_bytes = 0;
+        self.encoding = false;
+    }
+
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.raw.heap_size(&mut callback);
+        self.encoded.heap_size(callback);
+    }
+
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        C: 'a,
+    {
+        item
+    }
+}
+
+impl<C: Codec, R> Push<&[u8]> for LazyCodecRegion<C, R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + Push<&'a [u8]> + 'a,
+{
+    fn push(&mut self, item: &[u8]) -> <LazyCodecRegion<C, R> as Region>::Index {
+        if self.encoding {
+            LazyCodecIndex::Encoded(self.encoded.push(item))
+        } else {
+            self.raw_bytes += item.len();
+            self.encoded.codec.observe(item);
+            self.encoding = self.raw_bytes >= self.threshold;
+            if self.encoding {
+                self.encoded.seal_batch();
+            }
+            LazyCodecIndex::Raw(self.raw.push(item))
+        }
+    }
+}
+
 /// Encode and decode byte strings.
 pub trait Codec: Default {
     /// Decodes an input byte slice into a sequence of byte slices.
     fn decode<'a>(&'a self, bytes: &'a [u8]) -> &'a [u8];
+    /// Decodes an input byte slice, appending the result to `output`.
+    ///
+    /// The default implementation copies the result of [`Self::decode`].
+    fn decode_into(&self, bytes: &[u8], output: &mut Vec<u8>) {
+        output.extend_from_slice(self.decode(bytes));
+    }
     /// Encodes a sequence of byte slices into an output byte slice.
     fn encode<R>(&mut self, bytes: &[u8], output: &mut R) -> R::Index
     where
@@ -156,6 +416,21 @@ pub trait Codec: Default {
     fn new_from<'a, I: Iterator<Item = &'a Self> + Clone>(stats: I) -> Self
     where
         Self: 'a;
+    /// Returns the number of bytes that encoding `bytes` would produce, without recording
+    /// statistics. The default implementation assumes no compression.
+    fn encoded_len(&self, bytes: &[u8]) -> usize {
+        bytes.len()
+    }
+    /// Records statistics about `bytes` as [`Self::encode`] would, without encoding them. The
+    /// default implementation does nothing.
+    fn observe(&mut self, bytes: &[u8]) {
+        let _ = bytes;
+    }
+    /// Notifies the codec of the boundary between two batches of data.
+    ///
+    /// Codecs can incorporate the statistics gathered since the last boundary, but must
+    /// continue to decode all previously encoded data. The default implementation does nothing.
+    fn on_batch_boundary(&mut self) {}
     /// Diagnostic information about the state of the codec.
     fn report(&self) {}
 
@@ -206,13 +481,24 @@ mod dictionary {
                 self.bytes += bytes.len();
                 output.push(bytes)
             };
-            // Stats stuff.
+            self.observe(bytes);
+
+            index
+        }
+
+        fn observe(&mut self, bytes: &[u8]) {
             self.stats.0.insert(bytes.to_owned());
             let tag = bytes[0];
             let tag_idx: usize = (tag % 4).into();
             self.stats.1[tag_idx] |= 1 << (tag >> 2);
+        }
 
-            index
+        fn encoded_len(&self, bytes: &[u8]) -> usize {
+            if self.encode.contains_key(bytes) {
+                1
+            } else {
+                bytes.len()
+            }
         }
 
         /// Construct a new encoder from supplied statistics.
@@ -249,6 +535,36 @@ mod dictionary {
             }
         }
 
+        /// Extend the dictionary with the most popular byte slices of the current batch.
+        ///
+        /// Existing entries remain unchanged, and new entries only occupy tags that no
+        /// unencoded input started with, which keeps all previously encoded data decodable.
+        /// The frequency statistics restart for the next batch.
+        fn on_batch_boundary(&mut self) {
+            let mg = std::mem::take(&mut self.stats.0).done();
+            let mut mg = mg
+                .into_iter()
+                .filter(|(bytes, _count)| !self.encode.contains_key(bytes))
+                .collect::<Vec<_>>()
+                .into_iter();
+            let mut decode = BytesMap::default();
+            for tag in 0..=255 {
+                let tag_idx: usize = (tag % 4).into();
+                let shift = tag >> 2;
+                if let Some(bytes) = self.decode.get(tag.into()) {
+                    decode.push(Some(bytes));
+                } else if (self.stats.1[tag_idx] >> shift) & 0x01 != 0 {
+                    decode.push(None);
+                } else if let Some((next_bytes, _count)) = mg.next() {
+                    decode.push(Some(&next_bytes[..]));
+                    self.encode.insert(next_bytes, tag);
+                } else {
+                    decode.push(None);
+                }
+            }
+            self.decode = decode;
+        }
+
         fn report(&self) {
             let mut tags_used = 0;
             tags_used += self.stats.1[0].count_ones();
@@ -366,7 +682,7 @@ mod misra_gries {
         pub fn done(mut self) -> Vec<(T, usize)> {
             use super::consolidate;
             consolidate(&mut self.inner);
-            self.inner.sort_by(|x, y| y.1.cmp(&x.1));
+            self.inner.sort_by_key(|x| std::cmp::Reverse(x.1));
             self.inner
         }
 
@@ -376,7 +692,7 @@ mod misra_gries {
         fn tidy(&mut self) {
             use super::consolidate;
             consolidate(&mut self.inner);
-            self.inner.sort_by(|x, y| y.1.cmp(&x.1));
+            self.inner.sort_by_key(|x| std::cmp::Reverse(x.1));
             let k = self.inner.capacity() / 2;
             if self.inner.len() > k {
                 let sub_weight = self.inner[k].1 - 1;
@@ -394,7 +710,7 @@ mod misra_gries {
 
 #[cfg(test)]
 mod tests {
-    use super::{Codec, CodecRegion, DictionaryCodec};
+    use super::{Codec, CodecRegion, DictionaryCodec, LazyCodecIndex, LazyCodecRegion};
     use crate::*;
 
     #[test]
@@ -454,6 +770,98 @@ mod tests {
         merged.codec.report();
     }
 
+    #[test]
+    fn test_seal_batch() {
+        let mut r = CodecRegion::<DictionaryCodec>::default();
+
+        let mut indices = Vec::new();
+        for _ in 0..100 {
+            indices.push(("abc", r.push("abc".as_bytes())));
+        }
+        // Nothing learned yet, stored verbatim.
+        assert_eq!(3, r.inner.index(indices[0].1).len());
+
+        r.seal_batch();
+
+        let index = r.push("abc".as_bytes());
+        assert_eq!(1, r.inner.index(index).len());
+        indices.push(("abc", index));
+
+        for _ in 0..100 {
+            indices.push(("xyz", r.push("xyz".as_bytes())));
+        }
+        assert_eq!(3, r.inner.index(indices.last().unwrap().1).len());
+
+        r.seal_batch();
+
+        let index = r.push("xyz".as_bytes());
+        assert_eq!(1, r.inner.index(index).len());
+        indices.push(("xyz", index));
+        let index = r.push("abc".as_bytes());
+        assert_eq!(1, r.inner.index(index).len());
+        indices.push(("abc", index));
+
+        for (expected, index) in indices {
+            assert_eq!(expected.as_bytes(), r.index(index));
+        }
+    }
+
+    #[test]
+    fn test_lazy() {
+        let mut r = LazyCodecRegion::<DictionaryCodec>::with_threshold(1024);
+
+        let mut indices = Vec::new();
+        for i in 0..100_u8 {
+            let item = vec![i, i.wrapping_mul(7)];
+            indices.push((item.clone(), r.push(item.as_slice())));
+        }
+        assert!(indices.iter().all(|(_, index)| !index.is_encoded()));
+
+        let large = "abcdefgh".repeat(16);
+        for _ in 0..100 {
+            indices.push((large.as_bytes().to_vec(), r.push(large.as_bytes())));
+        }
+        r.seal_batch();
+        for _ in 0..100 {
+            indices.push((large.as_bytes().to_vec(), r.push(large.as_bytes())));
+        }
+        assert!(indices.last().unwrap().1.is_encoded());
+        assert!(indices
+            .iter()
+            .any(|(item, index)| item.len() > 2 && !index.is_encoded()));
+
+        for (item, index) in &indices {
+            assert_eq!(item.as_slice(), r.index(*index));
+        }
+
+        // The encoded slices require a single byte each.
+        let index = r.push(large.as_bytes());
+        let LazyCodecIndex::Encoded(encoded) = index else {
+            panic!("expected encoded index")
+        };
+        assert_eq!(1, r.encoded.inner.index(encoded).len());
+
+        r.clear();
+        assert!(!r.push(large.as_bytes()).is_encoded());
+    }
+
+    #[test]
+    fn test_lazy_trains_at_threshold() {
+        let mut r = LazyCodecRegion::<DictionaryCodec>::with_threshold(1024);
+        let large = "abcdefgh".repeat(16);
+        let mut indices = vec![r.push(large.as_bytes())];
+        while !indices.last().unwrap().is_encoded() {
+            indices.push(r.push(large.as_bytes()));
+        }
+
+        // The first encoded slice uses the dictionary trained on the raw slices.
+        let last = *indices.last().unwrap();
+        assert_eq!(1, r.item_storage_size(last));
+        for index in indices {
+            assert_eq!(large.as_bytes(), r.index(index));
+        }
+    }
+
     #[test]
     fn test_heap_size() {
         let mut regions = Vec::new();
@@ -1,5 +1,6 @@
 //! A region to contain a variable number of columns.
 
+use std::collections::TryReserveError;
 use std::fmt::Debug;
 use std::iter::Zip;
 use std::slice::Iter;
@@ -10,7 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::impls::deduplicate::ConsecutiveIndexPairs;
 use crate::impls::index::{IndexContainer, IndexOptimized};
 use crate::{IntoOwned, PushIter};
-use crate::{OwnedRegion, Push, Region};
+use crate::{OwnedRegion, Push, Region, RegionLen};
 
 /// A region that can store a variable number of elements per row.
 ///
@@ -19,6 +20,9 @@ use crate::{OwnedRegion, Push, Region};
 /// remembers the indices into each column that populated. Rows can have different
 /// lengths, which means that only the first columns will contain a value.
 ///
+/// To store rows with null cells, use a
+/// [`NullableColumnsRegion`](crate::impls::nullable_columns::NullableColumnsRegion).
+///
 /// All columns have the same type `R`.
 ///
 /// # Examples
@@ -124,6 +128,22 @@ where
         }))
     }
 
+    /// Returns the storage size of the row's values, plus the size of their indexes.
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        let index = self.indices.index(index);
+        let values: usize = self
+            .inner
+            .iter()
+            .zip(index)
+            .map(|(column, index)| column.item_storage_size(*index))
+            .sum();
+        values + std::mem::size_of_val(index)
+    }
+
+    fn last_index(&self) -> Option<Self::Index> {
+        self.indices.last_index()
+    }
+
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
         Self: 'a,
@@ -139,6 +159,22 @@ where
         }
     }
 
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        for region in regions.clone() {
+            while self.inner.len() < region.inner.len() {
+                self.inner.push(R::default());
+            }
+        }
+        for (index, inner) in self.inner.iter_mut().enumerate() {
+            inner.try_reserve_regions(regions.clone().filter_map(|r| r.inner.get(index)))?;
+        }
+        Ok(())
+    }
+
     fn clear(&mut self) {
         for inner in &mut self.inner {
             inner.clear();
@@ -147,6 +183,8 @@ where
     }
 
     fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        // The spine holds the column regions inline, which accounts for their stack size. Each
+        // column then reports the allocations it owns, which does not include itself.
         let size_of_r = std::mem::size_of::<R>();
         callback(
             self.inner.len() * size_of_r,
@@ -166,6 +204,18 @@ where
     }
 }
 
+impl<R, O> RegionLen for ColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    /// Returns the number of rows.
+    #[inline]
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+}
+
 impl<R, O> Default for ColumnsRegion<R, O>
 where
     R: Region,
@@ -179,6 +229,62 @@ where
     }
 }
 
+impl<R, O> ColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    /// Iterate the cells of `column` in row order, yielding `None` for rows shorter than
+    /// `column`. Only reads the region of `column`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{ColumnsRegion, MirrorRegion, Push};
+    /// let mut r = <ColumnsRegion<MirrorRegion<u32>>>::default();
+    ///
+    /// let _ = r.push([1, 2]);
+    /// let _ = r.push([3]);
+    /// assert!(r.iter_column(1).eq([Some(2), None]));
+    /// ```
+    pub fn iter_column(
+        &self,
+        column: usize,
+    ) -> impl Iterator<Item = Option<R::ReadItem<'_>>> + Clone + '_ {
+        (0..self.indices.len()).map(move |row| {
+            let row = self.index(row);
+            if column < row.len() {
+                Some(row.get(column))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Push the values of a row at their columns, which must be increasing, and leave the
+    /// remaining columns of the row empty. Returns the index of the row.
+    pub(crate) fn push_at<T, I>(&mut self, cells: I) -> <Self as Region>::Index
+    where
+        R: Push<T>,
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let mut index = Vec::new();
+        for (column, value) in cells {
+            // Ensure all required regions exist.
+            while self.inner.len() <= column {
+                self.inner.push(R::default());
+            }
+            index.push(self.inner[column].push(value));
+        }
+        self.indices.push(index.as_slice())
+    }
+
+    /// Returns the columns, and the indexes of the values of the row at `index`.
+    pub(crate) fn row(&self, index: <Self as Region>::Index) -> (&[R], &[R::Index]) {
+        (&self.inner, self.indices.index(index))
+    }
+}
+
 /// Read the values of a row.
 pub struct ReadColumns<'a, R>(Result<ReadColumnsInner<'a, R>, &'a [R::Owned]>)
 where
@@ -225,6 +331,23 @@ where
     }
 }
 
+impl<R> PartialEq for ReadColumns<'_, R>
+where
+    R: Region,
+    for<'a> R::ReadItem<'a>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.into_iter().eq(other)
+    }
+}
+
+impl<R> Eq for ReadColumns<'_, R>
+where
+    R: Region,
+    for<'a> R::ReadItem<'a>: Eq,
+{
+}
+
 impl<'a, R> ReadColumns<'a, R>
 where
     R: Region,
@@ -236,8 +359,17 @@ where
     }
 
     /// Get the element at `offset`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is out of bounds, i.e., it is larger or equal to the length of this row.
     #[must_use]
     pub fn get(&self, offset: usize) -> R::ReadItem<'a> {
+        assert!(
+            offset < self.len(),
+            "ReadColumns: index {offset} out of bounds for length {}",
+            self.len()
+        );
         match &self.0 {
             Ok(inner) => inner.get(offset),
             Err(slice) => IntoOwned::borrow_as(&slice[offset]),
@@ -507,6 +639,29 @@ where
     }
 }
 
+/// Pushes the cells of an iterator whose type is erased.
+impl<R, O, T> Push<&mut dyn Iterator<Item = T>> for ColumnsRegion<R, O>
+where
+    R: Region + Push<T>,
+    O: IndexContainer<usize>,
+{
+    #[inline]
+    fn push(
+        &mut self,
+        item: &mut dyn Iterator<Item = T>,
+    ) -> <ColumnsRegion<R, O> as Region>::Index {
+        let mut iter = item.enumerate().map(|(index, value)| {
+            // Ensure all required regions exist.
+            if self.inner.len() <= index {
+                self.inner.push(R::default());
+            }
+            self.inner[index].push(value)
+        });
+        self.indices
+            .push(&mut iter as &mut dyn Iterator<Item = R::Index>)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::impls::deduplicate::{CollapseSequence, ConsecutiveIndexPairs};
@@ -664,6 +819,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_ragged_dyn_iter() {
+        let data = [vec![], vec!["1"], vec!["2", "3"], vec!["4"]];
+
+        let mut r = ColumnsRegion::<StringRegion>::default();
+
+        let indices: Vec<_> = data
+            .iter()
+            .map(|row| {
+                r.push(&mut row.as_slice().iter().copied() as &mut dyn Iterator<Item = &str>)
+            })
+            .collect();
+
+        for (index, row) in indices.into_iter().zip(&data) {
+            assert!(row.as_slice().iter().copied().eq(r.index(index).iter()));
+        }
+    }
+
     #[test]
     #[should_panic]
     fn test_clear() {
@@ -737,4 +910,46 @@ mod tests {
         });
         assert!(cap2 <= cap1);
     }
+
+    #[test]
+    #[should_panic(expected = "ReadColumns: index 2 out of bounds for length 2")]
+    fn test_get_out_of_bounds() {
+        let mut r = <ColumnsRegion<MirrorRegion<u8>>>::default();
+        let _ = r.push([1, 2, 3]);
+        let index = r.push([4, 5]);
+        let _ = r.index(index).get(2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "IndexOptimized: index 2 out of bounds for length 2")]
+    fn test_index_out_of_bounds() {
+        let mut r = <ColumnsRegion<MirrorRegion<u8>>>::default();
+        let index = r.push([1, 2, 3]);
+        let _ = r.index(index + 1);
+    }
+
+    #[test]
+    fn test_heap_size() {
+        let data = [vec![[1_u8], [2]], vec![[3]], vec![[4], [5], [6]]];
+
+        let mut r = <ColumnsRegion<OwnedRegion<u8>>>::default();
+        for row in &data {
+            let _ = r.push(row);
+        }
+
+        let mut size = 0;
+        r.heap_size(|siz, _| size += siz);
+
+        // Spine of three columns.
+        let spine = 3 * std::mem::size_of::<OwnedRegion<u8>>();
+        // Columns hold three, two, and one byte, respectively.
+        let columns = 6;
+        // One `(usize, usize)` index per cell.
+        let cells = 6 * std::mem::size_of::<(usize, usize)>();
+        // Row offsets 0, 2, 3, 6: the stride 0, 2 does not continue, so 3 and 6 spill as `u32`.
+        let rows = 2 * std::mem::size_of::<u32>();
+
+        assert_eq!(spine + columns + cells + rows, size);
+    }
 }
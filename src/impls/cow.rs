@@ -0,0 +1,286 @@
+//! A region that stores owned items, or refers to borrowed items.
+
+use std::borrow::Cow;
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, Push, Region, ReserveItems};
+
+/// A region that stores owned items in a region `R`, and refers to items it borrows for the
+/// lifetime `'b` instead of copying them.
+///
+/// Pushing a `&'b B` records the reference, which avoids copying data that outlives the
+/// region, such as string literals or the contents of a memory-mapped file. The region
+/// borrows the data for `'b`, and thus cannot outlive it. Pushing a [`Cow`] borrows its
+/// borrowed variant and stores its owned variant, and [`push_owned`](CowRegion::push_owned)
+/// stores any item `R` accepts. The index records which path the region took.
+///
+/// Both paths read the same item, which is a `&B`, as `R` presents it. This requires the read
+/// item of `R` to be a reference, which is the case for [`StringRegion`](crate::StringRegion)
+/// and [`OwnedRegion`](crate::OwnedRegion), among others.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::cow::CowRegion;
+/// use flatcontainer::{Push, Region, StringRegion};
+/// let mut r = <CowRegion<StringRegion, str>>::default();
+///
+/// let borrowed = r.push("static");
+/// let owned = r.push_owned(&format!("{}-{}", 1, 2));
+/// assert!(borrowed.is_borrowed());
+/// assert_eq!("static", r.index(borrowed));
+/// assert_eq!("1-2", r.index(owned));
+/// ```
+#[derive(Debug)]
+pub struct CowRegion<'b, R, B: ?Sized> {
+    /// Owned items.
+    inner: R,
+    /// Borrowed items.
+    borrowed: Vec<&'b B>,
+}
+
+/// The index of a [`CowRegion`], recording whether the region stored or borrowed the item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CowIndex<I> {
+    /// The index of an item stored in the inner region.
+    Owned(I),
+    /// The position of a borrowed item.
+    Borrowed(usize),
+}
+
+impl<I> CowIndex<I> {
+    /// Returns `true` if the region borrows the item.
+    #[inline]
+    #[must_use]
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, CowIndex::Borrowed(_))
+    }
+}
+
+impl<'b, R: Default, B: ?Sized> Default for CowRegion<'b, R, B> {
+    fn default() -> Self {
+        Self {
+            inner: R::default(),
+            borrowed: Vec::default(),
+        }
+    }
+}
+
+impl<'b, R: Clone, B: ?Sized> Clone for CowRegion<'b, R, B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            borrowed: self.borrowed.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.borrowed.clone_from(&source.borrowed);
+    }
+}
+
+impl<'b, R, B: ?Sized> CowRegion<'b, R, B> {
+    /// Stores `item` in the inner region, regardless of whether it is borrowed.
+    #[inline]
+    pub fn push_owned<T>(&mut self, item: T) -> CowIndex<R::Index>
+    where
+        R: Push<T>,
+    {
+        CowIndex::Owned(self.inner.push(item))
+    }
+}
+
+impl<'b, R, B> Region for CowRegion<'b, R, B>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a B> + 'a,
+    for<'a> &'a B: IntoOwned<'a, Owned = <R as Region>::Owned>,
+    B: ?Sized + 'b,
+{
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    type Index = CowIndex<R::Index>;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: R::merge_regions(regions.clone().map(|r| &r.inner)),
+            borrowed: Vec::with_capacity(regions.map(|r| r.borrowed.len()).sum()),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        match index {
+            CowIndex::Owned(index) => self.inner.index(index),
+            CowIndex::Borrowed(position) => self.borrowed[position],
+        }
+    }
+
+    /// Returns the storage size of an owned item, or the size of the reference to a borrowed
+    /// item.
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        match index {
+            CowIndex::Owned(index) => self.inner.item_storage_size(index),
+            CowIndex::Borrowed(_) => std::mem::size_of::<&B>(),
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner
+            .reserve_regions(regions.clone().map(|r| &r.inner));
+        self.borrowed
+            .reserve(regions.map(|r| r.borrowed.len()).sum());
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner
+            .try_reserve_regions(regions.clone().map(|r| &r.inner))?;
+        self.borrowed
+            .try_reserve(regions.map(|r| r.borrowed.len()).sum())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.borrowed.clear();
+    }
+
+    /// Reports the heap size of the inner region and the references to borrowed items, but
+    /// not the borrowed items.
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_reference = std::mem::size_of::<&B>();
+        callback(
+            self.borrowed.len() * size_of_reference,
+            self.borrowed.capacity() * size_of_reference,
+        );
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'c, 'a: 'c>(item: Self::ReadItem<'a>) -> Self::ReadItem<'c>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+/// Borrows the item for the lifetime of the region.
+impl<'b, R, B> Push<&'b B> for CowRegion<'b, R, B>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a B> + 'a,
+    for<'a> &'a B: IntoOwned<'a, Owned = <R as Region>::Owned>,
+    B: ?Sized + 'b,
+{
+    #[inline]
+    fn push(&mut self, item: &'b B) -> <CowRegion<'b, R, B> as Region>::Index {
+        self.borrowed.push(item);
+        CowIndex::Borrowed(self.borrowed.len() - 1)
+    }
+}
+
+/// Borrows a borrowed item, and stores an owned item.
+impl<'b, R, B> Push<Cow<'b, B>> for CowRegion<'b, R, B>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a B> + Push<<B as ToOwned>::Owned> + 'a,
+    for<'a> &'a B: IntoOwned<'a, Owned = <R as Region>::Owned>,
+    B: ?Sized + ToOwned + 'b,
+{
+    #[inline]
+    fn push(&mut self, item: Cow<'b, B>) -> <CowRegion<'b, R, B> as Region>::Index {
+        match item {
+            Cow::Borrowed(item) => self.push(item),
+            Cow::Owned(item) => self.push_owned(item),
+        }
+    }
+}
+
+impl<'b, R, B> ReserveItems<&'b B> for CowRegion<'b, R, B>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a B> + 'a,
+    for<'a> &'a B: IntoOwned<'a, Owned = <R as Region>::Owned>,
+    B: ?Sized + 'b,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'b B> + Clone,
+    {
+        self.borrowed.reserve(items.count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, OwnedRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_mixed() {
+        const WORDS: [&str; 3] = ["alpha", "beta", "gamma"];
+        let mut c = FlatStack::<CowRegion<StringRegion, str>>::default();
+        let mut expected = Vec::new();
+        for i in 0..30 {
+            if i % 3 == 0 {
+                let owned = format!("owned-{i}");
+                c.copy(Cow::<str>::Owned(owned.clone()));
+                expected.push(owned);
+            } else {
+                c.copy(WORDS[i % 3]);
+                expected.push(WORDS[i % 3].to_string());
+            }
+        }
+        assert!(c.iter().eq(expected.iter().map(String::as_str)));
+        assert_eq!("owned-3".to_string(), c.get(3).into_owned());
+
+        // Only the owned strings occupy the inner region.
+        let mut size = 0;
+        c.region.inner.heap_size(|siz, _| size += siz);
+        let owned: usize = expected.iter().step_by(3).map(String::len).sum();
+        assert_eq!(owned, size);
+        assert_eq!(20, c.region.borrowed.len());
+
+        c.clear();
+        assert!(c.is_empty());
+        c.copy(Cow::Borrowed("delta"));
+        assert_eq!("delta", c.get(0));
+    }
+
+    #[test]
+    fn test_borrowed_source() {
+        // Borrows from a source that outlives the region, but is not static.
+        let source: Vec<u8> = (1..=5).collect();
+        let mut r = <CowRegion<OwnedRegion<u8>, [u8]>>::default();
+        let borrowed = r.push(&source[1..3]);
+        let owned = r.push_owned(vec![9, 9]);
+        assert!(borrowed.is_borrowed());
+        assert!(!owned.is_borrowed());
+        assert_eq!([2, 3], r.index(borrowed));
+        assert_eq!([9, 9], r.index(owned));
+        assert_eq!(std::mem::size_of::<&[u8]>(), r.item_storage_size(borrowed));
+
+        let merged = CowRegion::merge_regions(std::iter::once(&r));
+        assert_eq!(1, merged.borrowed.capacity());
+    }
+}
@@ -0,0 +1,301 @@
+//! A region to store graphs in compressed sparse row layout.
+
+use std::collections::TryReserveError;
+use std::ops::Range;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::{IndexContainer, IndexOptimized};
+use crate::impls::slice::{ReadSlice, ReadSliceIter};
+use crate::{MirrorRegion, Push, Region, ReserveItems, SliceRegion};
+
+/// A region to store the adjacency of a graph in compressed sparse row (CSR) layout.
+///
+/// Each pushed item is the list of neighbors of a node, and the index of the item is the node
+/// identifier, which counts the nodes in the order they were pushed. The neighbors of all nodes
+/// are stored in a single [`SliceRegion`], and the region records the offset at which each node's
+/// neighbors end.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::csr::CsrRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <CsrRegion>::default();
+///
+/// let nodes = r.push_adjacency(vec![vec![1, 2], vec![2], vec![]]);
+/// assert_eq!(0..3, nodes);
+///
+/// assert_eq!(2, r.degree(0));
+/// assert!(r.neighbors(0).eq([1, 2]));
+/// assert!(r.neighbors(2).next().is_none());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            O: Serialize + for<'a> Deserialize<'a>,
+            E: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct CsrRegion<
+    R: Region = MirrorRegion<u32>,
+    O = Vec<<R as Region>::Index>,
+    E = IndexOptimized,
+> {
+    /// Neighbors of all nodes.
+    neighbors: SliceRegion<R, O>,
+    /// End offsets of each node's neighbors, with a leading zero.
+    ends: E,
+}
+
+impl<R, O, E> Clone for CsrRegion<R, O, E>
+where
+    R: Region + Clone,
+    O: Clone,
+    E: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            neighbors: self.neighbors.clone(),
+            ends: self.ends.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.neighbors.clone_from(&source.neighbors);
+        self.ends.clone_from(&source.ends);
+    }
+}
+
+impl<R, O, E> CsrRegion<R, O, E>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    E: IndexContainer<usize>,
+{
+    /// Returns the number of nodes in this region.
+    #[inline]
+    #[must_use]
+    pub fn nodes(&self) -> usize {
+        self.ends.len() - 1
+    }
+
+    /// Returns the number of neighbors of `node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not a node of this region.
+    #[inline]
+    #[must_use]
+    pub fn degree(&self, node: usize) -> usize {
+        self.ends.index(node + 1) - self.ends.index(node)
+    }
+
+    /// Returns an iterator over the neighbors of `node`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not a node of this region.
+    #[inline]
+    pub fn neighbors(&self, node: usize) -> ReadSliceIter<'_, R, O> {
+        self.index(node).into_iter()
+    }
+
+    /// Pushes the neighbor lists of consecutive nodes, returning the range of node identifiers.
+    pub fn push_adjacency<I>(&mut self, adjacency: I) -> Range<usize>
+    where
+        I: IntoIterator,
+        Self: Push<I::Item, Index = usize>,
+    {
+        let start = self.nodes();
+        for neighbors in adjacency {
+            let _ = self.push(neighbors);
+        }
+        start..self.nodes()
+    }
+}
+
+impl<R, O, E> Region for CsrRegion<R, O, E>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    E: IndexContainer<usize>,
+{
+    type Owned = Vec<R::Owned>;
+    type ReadItem<'a> = ReadSlice<'a, R, O> where Self: 'a;
+    type Index = usize;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let mut ends = E::merge_regions(regions.clone().map(|r| &r.ends));
+        ends.push(0);
+        Self {
+            neighbors: SliceRegion::merge_regions(regions.map(|r| &r.neighbors)),
+            ends,
+        }
+    }
+
+    #[inline]
+    fn index(&self, node: Self::Index) -> Self::ReadItem<'_> {
+        self.neighbors
+            .index((self.ends.index(node), self.ends.index(node + 1)))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.ends.reserve_regions(regions.clone().map(|r| &r.ends));
+        self.neighbors
+            .reserve_regions(regions.map(|r| &r.neighbors));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.ends
+            .try_reserve_regions(regions.clone().map(|r| &r.ends))?;
+        self.neighbors
+            .try_reserve_regions(regions.map(|r| &r.neighbors))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.neighbors.clear();
+        self.ends.clear();
+        self.ends.push(0);
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.ends.heap_size(&mut callback);
+        self.neighbors.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<R, O, E> Default for CsrRegion<R, O, E>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    E: IndexContainer<usize>,
+{
+    #[inline]
+    fn default() -> Self {
+        let mut ends = E::default();
+        ends.push(0);
+        Self {
+            neighbors: SliceRegion::default(),
+            ends,
+        }
+    }
+}
+
+impl<T, R, O, E> Push<T> for CsrRegion<R, O, E>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    E: IndexContainer<usize>,
+    SliceRegion<R, O>: Region<Index = (usize, usize)> + Push<T>,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> <CsrRegion<R, O, E> as Region>::Index {
+        let (_start, end) = self.neighbors.push(item);
+        self.ends.push(end);
+        self.ends.len() - 2
+    }
+}
+
+impl<T, R, O, E> ReserveItems<T> for CsrRegion<R, O, E>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    E: IndexContainer<usize>,
+    SliceRegion<R, O>: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.ends.reserve(items.clone().count());
+        self.neighbors.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::IntoOwned;
+
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        // 0 -> 1, 2, 3
+        // 1 -> 2
+        // 2 -> (none)
+        // 3 -> 0, 2
+        let mut r = <CsrRegion>::default();
+        let nodes = r.push_adjacency(vec![vec![1, 2, 3], vec![2], vec![], vec![0, 2]]);
+        assert_eq!(0..4, nodes);
+        assert_eq!(4, r.nodes());
+
+        let degrees: Vec<_> = nodes.clone().map(|node| r.degree(node)).collect();
+        assert_eq!(vec![3, 1, 0, 2], degrees);
+
+        assert!(r.neighbors(0).eq([1, 2, 3]));
+        assert!(r.neighbors(1).eq([2]));
+        assert!(r.neighbors(2).eq([]));
+        assert!(r.neighbors(3).eq([0, 2]));
+
+        // Nodes whose neighbors include 2.
+        let into_two: Vec<_> = nodes
+            .filter(|&node| r.neighbors(node).any(|n| n == 2))
+            .collect();
+        assert_eq!(vec![0, 1, 3], into_two);
+
+        assert_eq!(vec![0, 2], r.index(3).into_owned());
+    }
+
+    #[test]
+    fn test_push_adjacency_appends() {
+        let mut r = <CsrRegion>::default();
+        assert_eq!(0..2, r.push_adjacency([[1, 0], [0, 1]]));
+        assert_eq!(2..3, r.push_adjacency([&[2][..]]));
+        assert!(r.neighbors(2).eq([2]));
+
+        r.clear();
+        assert_eq!(0, r.nodes());
+        assert_eq!(0..1, r.push_adjacency([vec![0]]));
+    }
+
+    #[test]
+    fn test_merge_regions() {
+        let mut r = <CsrRegion>::default();
+        let _ = r.push_adjacency(vec![vec![1, 2, 3], vec![2]]);
+
+        let mut merged = CsrRegion::merge_regions(std::iter::once(&r));
+        assert_eq!(0, merged.nodes());
+        let node = merged.push(r.index(0));
+        assert!(merged.neighbors(node).eq([1, 2, 3]));
+    }
+}
@@ -0,0 +1,303 @@
+//! A region that stores fixed-point decimals.
+
+use std::collections::TryReserveError;
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, MirrorRegion, Push, Region, ReserveItems};
+
+/// A fixed-point decimal, represented as an integer `value` scaled by `10^scale`.
+///
+/// Equality compares the representation, so `123.45` with scale 2 differs from `123.450` with
+/// scale 3. Use [`Decimal::rescale`] to compare decimals of different scales.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::decimal::Decimal;
+/// let d = Decimal::new(-12345, 2);
+/// assert_eq!("-123.45", d.to_string());
+/// assert_eq!(Some(Decimal::new(-123450, 3)), d.rescale(3));
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Decimal {
+    /// The scaled integer value.
+    value: i128,
+    /// The number of decimal digits after the decimal point.
+    scale: u32,
+}
+
+impl Decimal {
+    /// Constructs a decimal representing `value / 10^scale`.
+    #[inline]
+    #[must_use]
+    pub const fn new(value: i128, scale: u32) -> Self {
+        Self { value, scale }
+    }
+
+    /// Returns the scaled integer value.
+    #[inline]
+    #[must_use]
+    pub const fn value(&self) -> i128 {
+        self.value
+    }
+
+    /// Returns the number of decimal digits after the decimal point.
+    #[inline]
+    #[must_use]
+    pub const fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the decimal with the given scale, or `None` if the conversion loses digits or
+    /// overflows.
+    #[must_use]
+    pub fn rescale(self, scale: u32) -> Option<Self> {
+        let value = if scale >= self.scale {
+            self.value
+                .checked_mul(10_i128.checked_pow(scale - self.scale)?)?
+        } else {
+            let factor = 10_i128.checked_pow(self.scale - scale)?;
+            if self.value % factor != 0 {
+                return None;
+            }
+            self.value / factor
+        };
+        Some(Self { value, scale })
+    }
+
+    /// Returns the nearest floating-point number.
+    #[must_use]
+    pub fn to_f64(self) -> f64 {
+        self.value as f64 / 10_f64.powi(i32::try_from(self.scale).unwrap_or(i32::MAX))
+    }
+}
+
+impl Display for Decimal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let digits = self.value.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.value < 0 { "-" } else { "" };
+        if scale == 0 {
+            write!(f, "{sign}{digits}")
+        } else if digits.len() > scale {
+            let (integer, fraction) = digits.split_at(digits.len() - scale);
+            write!(f, "{sign}{integer}.{fraction}")
+        } else {
+            write!(f, "{sign}0.{digits:0>scale$}")
+        }
+    }
+}
+
+impl<'a> IntoOwned<'a> for Decimal {
+    type Owned = Decimal;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self;
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        *owned
+    }
+}
+
+/// A region for fixed-point decimals with a scale of `SCALE` decimal digits.
+///
+/// Stores the scaled integer value of each decimal in a [`MirrorRegion<i128>`], and attaches the
+/// scale when reading, which results in a [`Decimal`]. The region accepts integers that are
+/// already scaled, and [`Decimal`]s, which it converts to its scale.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::decimal::{Decimal, DecimalRegion};
+/// use flatcontainer::{Push, Region};
+/// let mut r = <DecimalRegion<2>>::default();
+///
+/// let index = r.push(12345_i128);
+/// assert_eq!(Decimal::new(12345, 2), r.index(index));
+/// assert_eq!("123.45", r.index(index).to_string());
+///
+/// let index = r.push(Decimal::new(5, 1));
+/// assert_eq!("0.50", r.index(index).to_string());
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecimalRegion<const SCALE: u32> {
+    /// Storage for the scaled values.
+    inner: MirrorRegion<i128>,
+}
+
+impl<const SCALE: u32> DecimalRegion<SCALE> {
+    /// The number of decimal digits after the decimal point of the decimals in this region.
+    pub const SCALE: u32 = SCALE;
+}
+
+impl<const SCALE: u32> Region for DecimalRegion<SCALE> {
+    type Owned = Decimal;
+    type ReadItem<'a> = Decimal;
+    type Index = i128;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: MirrorRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        Decimal::new(self.inner.index(index), SCALE)
+    }
+
+    #[inline(always)]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline(always)]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline(always)]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<const SCALE: u32> Push<i128> for DecimalRegion<SCALE> {
+    #[inline]
+    fn push(&mut self, item: i128) -> i128 {
+        self.inner.push(item)
+    }
+}
+
+impl<const SCALE: u32> Push<&i128> for DecimalRegion<SCALE> {
+    #[inline]
+    fn push(&mut self, item: &i128) -> i128 {
+        self.push(*item)
+    }
+}
+
+impl<const SCALE: u32> Push<Decimal> for DecimalRegion<SCALE> {
+    /// Pushes a decimal after converting it to the scale of the region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the decimal cannot be represented with the scale of the region.
+    #[inline]
+    fn push(&mut self, item: Decimal) -> i128 {
+        let Some(scaled) = item.rescale(SCALE) else {
+            panic!("DecimalRegion: cannot represent {item} with scale {SCALE}");
+        };
+        self.push(scaled.value)
+    }
+}
+
+impl<const SCALE: u32> Push<&Decimal> for DecimalRegion<SCALE> {
+    #[inline]
+    fn push(&mut self, item: &Decimal) -> i128 {
+        self.push(*item)
+    }
+}
+
+impl<const SCALE: u32, T> ReserveItems<T> for DecimalRegion<SCALE> {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_scaled() {
+        let mut r = <DecimalRegion<2>>::default();
+        let index = r.push(12345);
+        let decimal = r.index(index);
+        assert_eq!(12345, decimal.value());
+        assert_eq!(2, decimal.scale());
+        assert_eq!("123.45", decimal.to_string());
+        assert!((decimal.to_f64() - 123.45).abs() < 1e-9);
+        assert_eq!(decimal, decimal.into_owned());
+    }
+
+    #[test]
+    fn test_display() {
+        let cases = [
+            (Decimal::new(0, 0), "0"),
+            (Decimal::new(-7, 0), "-7"),
+            (Decimal::new(5, 2), "0.05"),
+            (Decimal::new(-5, 2), "-0.05"),
+            (Decimal::new(100, 2), "1.00"),
+            (
+                Decimal::new(i128::MIN, 38),
+                "-1.70141183460469231731687303715884105728",
+            ),
+        ];
+        for (decimal, expected) in cases {
+            assert_eq!(expected, decimal.to_string());
+        }
+    }
+
+    #[test]
+    fn test_rescale() {
+        let mut c = FlatStack::<DecimalRegion<3>>::default();
+        c.copy(Decimal::new(15, 1));
+        c.copy(&Decimal::new(-2500, 4));
+        c.copy(7_i128);
+        assert!(c.iter().map(|d| d.value()).eq([1500, -250, 7]));
+
+        assert_eq!(None, Decimal::new(12345, 4).rescale(3));
+        assert_eq!(None, Decimal::new(i128::MAX, 0).rescale(1));
+    }
+
+    #[test]
+    #[should_panic(expected = "DecimalRegion: cannot represent 0.0001 with scale 3")]
+    fn test_push_lossy() {
+        let mut r = <DecimalRegion<3>>::default();
+        let _ = r.push(Decimal::new(1, 4));
+    }
+}
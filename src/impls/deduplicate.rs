@@ -1,10 +1,15 @@
-//! Simple deduplication of equal consecutive items.
+//! Deduplication of equal items, either consecutive or across the whole region.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::collections::TryReserveError;
+use std::hash::{BuildHasher, Hash, Hasher};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::impls::index::{IndexContainer, IndexOptimized};
-use crate::{Push, Region, ReserveItems};
+use crate::{FlatStack, Push, Region, RegionLen, ReserveItems};
 
 /// A region to deduplicate consecutive equal items.
 ///
@@ -69,6 +74,18 @@ impl<R: Region> Region for CollapseSequence<R> {
         self.inner.index(index)
     }
 
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    fn last_index(&self) -> Option<Self::Index> {
+        self.last_index
+    }
+
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
         Self: 'a,
@@ -77,6 +94,14 @@ impl<R: Region> Region for CollapseSequence<R> {
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     fn clear(&mut self) {
         self.inner.clear();
         self.last_index = None;
@@ -111,6 +136,238 @@ where
     }
 }
 
+/// A region to deduplicate equal items, independently of the order in which they are pushed.
+///
+/// Remembers the index of each distinct item by the hash of the pushed item, and returns the
+/// existing index when an equal item is pushed again. Requires that equal items hash the same,
+/// including when pushing different types, such as `&str` and `String`. An item whose hash
+/// collides with a different stored item is stored again.
+///
+/// In contrast to [`CollapseSequence`], which only compares to the most recent item, this region
+/// collapses all duplicates, at the cost of a hash map entry per distinct item.
+///
+/// Items are hashed with the [`BuildHasher`] `H`, which defaults to the [`RandomState`] of
+/// [`HashMap`]. The hasher is not serialized, and a deserialized region hashes with a fresh
+/// `H::default()`. Unless `H` is deterministic, it only detects duplicates among the items
+/// pushed after deserialization.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::deduplicate::Dedup;
+/// use flatcontainer::{Push, StringRegion};
+/// let mut r = <Dedup<StringRegion>>::default();
+///
+/// let index = r.push("abc");
+/// let _ = r.push("def");
+/// assert_eq!(index, r.push("abc"));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            H: Default,
+            ")
+)]
+pub struct Dedup<R: Region, H = RandomState> {
+    /// Inner region.
+    inner: R,
+    /// Maps the hash of an item to its index in the inner region.
+    indices: HashMap<u64, R::Index>,
+    /// Hashes items.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hasher: H,
+}
+
+impl<R: Region + Clone, H: Clone> Clone for Dedup<R, H> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            indices: self.indices.clone(),
+            hasher: self.hasher.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.indices.clone_from(&source.indices);
+        self.hasher.clone_from(&source.hasher);
+    }
+}
+
+impl<R: Region, H: Default> Default for Dedup<R, H> {
+    fn default() -> Self {
+        Self::with_hasher(H::default())
+    }
+}
+
+impl<R: Region, H> Dedup<R, H> {
+    /// Constructs an empty region that hashes items with `hasher`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use flatcontainer::impls::deduplicate::Dedup;
+    /// use flatcontainer::{Push, StringRegion};
+    /// let mut r = Dedup::<StringRegion, _>::with_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    ///
+    /// let index = r.push("abc");
+    /// assert_eq!(index, r.push("abc"));
+    /// ```
+    #[must_use]
+    pub fn with_hasher(hasher: H) -> Self {
+        Self {
+            inner: R::default(),
+            indices: HashMap::default(),
+            hasher,
+        }
+    }
+
+    /// Returns the hasher of the region.
+    #[inline]
+    #[must_use]
+    pub fn hasher(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Returns the number of distinct items stored in the region.
+    #[inline]
+    #[must_use]
+    pub fn distinct(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns the hash of an item.
+    #[inline]
+    fn hash<T: Hash>(&self, item: &T) -> u64
+    where
+        H: BuildHasher,
+    {
+        let mut hasher = self.hasher.build_hasher();
+        item.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<R: Region, H: BuildHasher + Clone + Default> Region for Dedup<R, H> {
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    type Index = R::Index;
+
+    /// Constructs an empty region with room for the distinct items of all `regions`. Hashes
+    /// with the hasher of the first region, if any.
+    ///
+    /// Only presizes the region, and does not seed it with the distinct items of `regions`: the
+    /// indexes of the source regions refer to their own inner regions. Re-pushing the contents
+    /// of the source regions collapses duplicates across them.
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: R::merge_regions(regions.clone().map(|r| &r.inner)),
+            indices: HashMap::with_capacity(regions.clone().map(|r| r.indices.len()).sum()),
+            hasher: regions
+                .clone()
+                .next()
+                .map_or_else(H::default, |r| r.hasher.clone()),
+        }
+    }
+
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner
+            .reserve_regions(regions.clone().map(|r| &r.inner));
+        self.indices.reserve(regions.map(|r| r.indices.len()).sum());
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner
+            .try_reserve_regions(regions.clone().map(|r| &r.inner))?;
+        self.indices
+            .try_reserve(regions.map(|r| r.indices.len()).sum())
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.indices.clear();
+    }
+
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_entry = std::mem::size_of::<(u64, R::Index)>();
+        callback(
+            self.indices.len() * size_of_entry,
+            self.indices.capacity() * size_of_entry,
+        );
+        self.inner.heap_size(callback);
+    }
+
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+impl<R, H, T> Push<T> for Dedup<R, H>
+where
+    R: Region + Push<T>,
+    H: BuildHasher + Clone + Default,
+    T: Hash,
+    for<'a> T: PartialEq<R::ReadItem<'a>>,
+{
+    fn push(&mut self, item: T) -> <Dedup<R, H> as Region>::Index {
+        let hash = self.hash(&item);
+        match self.indices.get(&hash) {
+            Some(index) if item == self.inner.index(*index) => *index,
+            Some(_) => self.inner.push(item),
+            None => {
+                let index = self.inner.push(item);
+                self.indices.insert(hash, index);
+                index
+            }
+        }
+    }
+}
+
+impl<R, H, T> ReserveItems<T> for Dedup<R, H>
+where
+    R: Region + ReserveItems<T>,
+    H: BuildHasher + Clone + Default,
+{
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
+
 /// Transform an index of `(usize, usize)` to a sequence of `0..`. Requires the pairs to
 /// be dense, i.e., `(i, j)` is followed by `(j, k)`.
 ///
@@ -204,6 +461,11 @@ where
             .index((self.indices.index(index), self.indices.index(index + 1)))
     }
 
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        RegionLen::len(self).checked_sub(1)
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -213,6 +475,15 @@ where
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.last_index = 0;
@@ -236,6 +507,18 @@ where
     }
 }
 
+impl<R, O> RegionLen for ConsecutiveIndexPairs<R, O>
+where
+    R: Region<Index = (usize, usize)>,
+    O: IndexContainer<usize>,
+{
+    /// Returns the number of pushed items.
+    #[inline]
+    fn len(&self) -> usize {
+        self.indices.len() - 1
+    }
+}
+
 impl<R, O, T> Push<T> for ConsecutiveIndexPairs<R, O>
 where
     R: Region<Index = (usize, usize)> + Push<T>,
@@ -251,6 +534,164 @@ where
     }
 }
 
+impl<R, O> ConsecutiveIndexPairs<R, O>
+where
+    R: Region<Index = (usize, usize)>,
+    O: IndexContainer<usize>,
+{
+    /// Iterate the items in this region, in the order they were pushed.
+    ///
+    /// Walks the index container once, reusing the end of each pair as the start of the next
+    /// pair, instead of looking up both boundaries per item as [`Region::index`] does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::deduplicate::ConsecutiveIndexPairs;
+    /// use flatcontainer::{Push, OwnedRegion, Region};
+    /// let mut r = <ConsecutiveIndexPairs<OwnedRegion<u8>>>::default();
+    ///
+    /// let abc = r.push(&b"abc");
+    /// let de = r.push(&b"de");
+    /// assert!(r.iter().eq([r.index(abc), r.index(de)]));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> ConsecutiveIter<'_, R, O> {
+        let mut indices = self.indices.iter();
+        let start = indices.next().unwrap_or(0);
+        ConsecutiveIter {
+            region: &self.inner,
+            indices,
+            start,
+        }
+    }
+}
+
+/// An iterator over the items of a [`ConsecutiveIndexPairs`] region, see
+/// [`ConsecutiveIndexPairs::iter`].
+pub struct ConsecutiveIter<'a, R, O>
+where
+    O: IndexContainer<usize> + 'a,
+{
+    /// The wrapped region.
+    region: &'a R,
+    /// Iterator over the remaining ends.
+    indices: O::Iter<'a>,
+    /// The start of the next item.
+    start: usize,
+}
+
+impl<'a, R, O> Iterator for ConsecutiveIter<'a, R, O>
+where
+    R: Region<Index = (usize, usize)>,
+    O: IndexContainer<usize>,
+{
+    type Item = R::ReadItem<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.indices.next()?;
+        let start = std::mem::replace(&mut self.start, end);
+        Some(self.region.index((start, end)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<R, O> Clone for ConsecutiveIter<'_, R, O>
+where
+    O: IndexContainer<usize>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            region: self.region,
+            indices: self.indices.clone(),
+            start: self.start,
+        }
+    }
+}
+
+impl<R, O, S> FlatStack<ConsecutiveIndexPairs<R, O>, S>
+where
+    R: Region<Index = (usize, usize)>,
+    O: IndexContainer<usize>,
+    S: IndexContainer<usize>,
+{
+    /// Iterate the items in this stack.
+    ///
+    /// Produces the same items as [`FlatStack::iter`], but remembers the end of the previous
+    /// item. When an index follows the previous index, which is the case for items copied
+    /// one after the other, the iterator only looks up the end of the item.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::deduplicate::ConsecutiveIndexPairs;
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<ConsecutiveIndexPairs<StringRegion>> = ["a", "bc"].into_iter().collect();
+    ///
+    /// assert!(c.iter_consecutive().eq(c.iter()));
+    /// ```
+    #[inline]
+    pub fn iter_consecutive(&self) -> ConsecutiveStackIter<'_, R, O, S::Iter<'_>> {
+        ConsecutiveStackIter {
+            region: &self.region,
+            indices: self.indices.iter(),
+            next: None,
+        }
+    }
+}
+
+/// An iterator over the items of a [`FlatStack`] of [`ConsecutiveIndexPairs`] regions, see
+/// [`FlatStack::iter_consecutive`].
+pub struct ConsecutiveStackIter<'a, R, O, I> {
+    /// The region of the stack.
+    region: &'a ConsecutiveIndexPairs<R, O>,
+    /// Iterator over the stack's indices.
+    indices: I,
+    /// The index following the previous item, and the start of its pair.
+    next: Option<(usize, usize)>,
+}
+
+impl<'a, R, O, I> Iterator for ConsecutiveStackIter<'a, R, O, I>
+where
+    R: Region<Index = (usize, usize)>,
+    O: IndexContainer<usize>,
+    I: Iterator<Item = usize>,
+{
+    type Item = R::ReadItem<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.indices.next()?;
+        let start = match self.next {
+            Some((next, start)) if next == index => start,
+            _ => self.region.indices.index(index),
+        };
+        let end = self.region.indices.index(index + 1);
+        self.next = Some((index + 1, end));
+        Some(self.region.inner.index((start, end)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<R, O, I: Clone> Clone for ConsecutiveStackIter<'_, R, O, I> {
+    fn clone(&self) -> Self {
+        Self {
+            region: self.region,
+            indices: self.indices.clone(),
+            next: self.next,
+        }
+    }
+}
+
 impl<R, O, T> ReserveItems<T> for ConsecutiveIndexPairs<R, O>
 where
     R: Region<Index = (usize, usize)> + ReserveItems<T>,
@@ -266,9 +707,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::impls::deduplicate::{CollapseSequence, ConsecutiveIndexPairs};
+    use std::collections::hash_map::RandomState;
+    use std::hash::Hasher;
+
+    use crate::impls::deduplicate::{CollapseSequence, ConsecutiveIndexPairs, Dedup};
     use crate::impls::index::IndexOptimized;
-    use crate::{FlatStack, Push, StringRegion};
+    use crate::{FlatStack, Push, Region, StringRegion};
 
     #[test]
     fn test_dedup_flatstack() {
@@ -302,4 +746,145 @@ mod tests {
 
         println!("{r:?}");
     }
+
+    #[test]
+    fn test_dedup_global() {
+        let mut r = Dedup::<StringRegion>::default();
+
+        let abc = r.push("abc");
+        let def = r.push("def");
+        assert_eq!(abc, r.push("abc"));
+        assert_eq!(def, r.push(&"def".to_string()));
+        assert_eq!(2, r.distinct());
+        assert_eq!("abc", r.index(abc));
+    }
+
+    #[test]
+    fn test_dedup_merge() {
+        let mut a = Dedup::<StringRegion>::default();
+        let _ = a.push("x");
+        let _ = a.push("y");
+        let mut b = Dedup::<StringRegion>::default();
+        let _ = b.push("z");
+        let _ = b.push("x");
+
+        let mut merged = Dedup::merge_regions([&a, &b].into_iter());
+        assert_eq!(0, merged.distinct());
+        for source in [&a, &b] {
+            for index in source.indices.values() {
+                let _ = merged.push(source.index(*index));
+            }
+        }
+        assert_eq!(3, merged.distinct());
+
+        let mut size = 0;
+        merged.inner.heap_size(|siz, _| size += siz);
+        assert_eq!("xyz".len(), size);
+    }
+
+    /// Hashes bytes with FNV-1a.
+    #[derive(Default)]
+    struct Fnv(u64);
+
+    impl Hasher for Fnv {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for byte in bytes {
+                self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dedup_hasher() {
+        type FnvState = std::hash::BuildHasherDefault<Fnv>;
+        let mut r = Dedup::<StringRegion, FnvState>::default();
+        let abc = r.push("abc");
+        let _ = r.push("def");
+        assert_eq!(abc, r.push("abc".to_string()));
+        assert_eq!(2, r.distinct());
+
+        let mut merged = Dedup::merge_regions(std::iter::once(&r));
+        let _ = merged.push("def");
+        assert_eq!(1, merged.distinct());
+
+        let mut random = Dedup::<StringRegion>::with_hasher(RandomState::new());
+        let abc = random.push("abc");
+        assert_eq!(abc, random.push("abc"));
+        assert_eq!(1, random.distinct());
+    }
+
+    #[test]
+    fn test_consecutive_iter() {
+        let items = ["a", "", "bcd", "ef", "", "ghij"];
+        let mut r = <ConsecutiveIndexPairs<StringRegion>>::default();
+        assert_eq!(0, r.iter().count());
+        let indices: Vec<_> = items.into_iter().map(|item| r.push(item)).collect();
+        assert!(r.iter().eq(items));
+        assert!(r.iter().eq(indices.into_iter().map(|index| r.index(index))));
+
+        let mut c = FlatStack::<ConsecutiveIndexPairs<StringRegion, IndexOptimized>>::default();
+        for item in items {
+            c.copy(item);
+        }
+        assert!(c.iter_consecutive().eq(items));
+
+        // Indices that do not follow each other.
+        let mut shuffled = FlatStack::<ConsecutiveIndexPairs<StringRegion>>::default();
+        for item in ["x", "y", "z"] {
+            let _ = shuffled.region.push(item);
+        }
+        for index in [2, 0, 1, 2, 2, 1] {
+            shuffled.indices.push(index);
+        }
+        assert!(shuffled.iter_consecutive().eq(shuffled.iter()));
+        assert!(shuffled
+            .iter_consecutive()
+            .eq(["z", "x", "y", "z", "z", "y"]));
+    }
+
+    #[test]
+    fn test_last_index() {
+        let mut r = <CollapseSequence<StringRegion>>::default();
+        assert_eq!(None, r.last_index());
+        let index = r.push("abc");
+        assert_eq!(Some(index), r.last_index());
+        assert_eq!("abc", r.index(r.last_index().unwrap()));
+        assert_eq!(index, r.push("abc"));
+        assert_eq!(Some(index), r.last_index());
+        r.clear();
+        assert_eq!(None, r.last_index());
+
+        let mut r = <ConsecutiveIndexPairs<StringRegion>>::default();
+        assert_eq!(None, r.last_index());
+        let _ = r.push("abc");
+        let index = r.push("def");
+        assert_eq!(Some(index), r.last_index());
+        assert_eq!("def", r.index(r.last_index().unwrap()));
+
+        let mut r = <crate::ColumnsRegion<StringRegion>>::default();
+        let index = r.push(["a", "b"]);
+        assert_eq!(Some(index), r.last_index());
+        assert!(r.index(r.last_index().unwrap()).iter().eq(["a", "b"]));
+
+        // Regions without an order of indices cannot recover the last index.
+        let mut r = <StringRegion>::default();
+        let _ = r.push("abc");
+        assert_eq!(None, r.last_index());
+    }
+
+    #[test]
+    fn test_collapse_sequence_merge() {
+        let mut a = CollapseSequence::<StringRegion>::default();
+        let x = a.push("x");
+        let mut b = CollapseSequence::<StringRegion>::default();
+        let _ = b.push("x");
+
+        let mut merged = CollapseSequence::merge_regions([&a, &b].into_iter());
+        let first = merged.push(a.index(x));
+        assert_eq!(first, merged.push(b.index(x)));
+    }
 }
@@ -0,0 +1,376 @@
+//! A region that stores slices of integers as differences between neighboring values.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, OwnedRegion, Push, Region};
+
+/// A region for slices of `u64` values that mostly increase, such as timestamps.
+///
+/// The region stores the number of values of a slice, followed by the difference of each value
+/// to its predecessor, starting from zero. Differences are zig-zag encoded to tolerate
+/// decreasing values, and stored as variable-length integers, which take one byte for
+/// differences smaller than 64. The read item is a [`ReadDelta`], which decodes values while
+/// iterating and does not allocate.
+///
+/// Byte slices holding little-endian values need to be converted to values before pushing.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::delta::DeltaRegion;
+/// use flatcontainer::{IntoOwned, Push, Region};
+/// let mut r = DeltaRegion::default();
+///
+/// let index = r.push(&[1000_u64, 1001, 1003][..]);
+/// assert_eq!(5, r.item_storage_size(index));
+/// assert!(r.index(index).iter().eq([1000, 1001, 1003]));
+/// assert_eq!(vec![1000, 1001, 1003], r.index(index).into_owned());
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DeltaRegion {
+    /// Encoded slices.
+    encoded: OwnedRegion<u8>,
+}
+
+impl DeltaRegion {
+    /// Pushes `values`, of which there are `len`.
+    fn push_values<I>(&mut self, len: usize, values: I) -> <Self as Region>::Index
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let mut encoded = Vec::with_capacity(len + 1);
+        write_varint(len as u64, &mut encoded);
+        let mut previous = 0_u64;
+        for value in values {
+            let delta = value.wrapping_sub(previous) as i64;
+            write_varint(((delta << 1) ^ (delta >> 63)) as u64, &mut encoded);
+            previous = value;
+        }
+        self.encoded.push(encoded)
+    }
+}
+
+impl Region for DeltaRegion {
+    type Owned = Vec<u64>;
+    type ReadItem<'a> = ReadDelta<'a>;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            encoded: OwnedRegion::merge_regions(regions.map(|r| &r.encoded)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadDelta(Ok(self.encoded.index(index)))
+    }
+
+    /// Returns the number of encoded bytes of the slice at `index`.
+    #[inline]
+    fn item_storage_size(&self, (start, end): Self::Index) -> usize {
+        end - start
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.encoded.reserve_regions(regions.map(|r| &r.encoded));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.encoded
+            .try_reserve_regions(regions.map(|r| &r.encoded))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.encoded.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.encoded.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A slice of values read from a [`DeltaRegion`].
+#[derive(Clone, Copy)]
+pub struct ReadDelta<'a>(Result<&'a [u8], &'a [u64]>);
+
+impl<'a> ReadDelta<'a> {
+    /// The number of values in the slice.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self.0 {
+            Ok(mut encoded) => read_len(&mut encoded),
+            Err(values) => values.len(),
+        }
+    }
+
+    /// Returns `true` if the slice is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the values.
+    #[must_use]
+    pub fn iter(&self) -> ReadDeltaIter<'a> {
+        self.into_iter()
+    }
+}
+
+impl PartialEq for ReadDelta<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(*other)
+    }
+}
+
+impl Eq for ReadDelta<'_> {}
+
+impl Hash for ReadDelta<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for value in *self {
+            value.hash(state);
+        }
+    }
+}
+
+impl Debug for ReadDelta<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoOwned<'a> for ReadDelta<'a> {
+    type Owned = Vec<u64>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend(self.iter());
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a> IntoIterator for ReadDelta<'a> {
+    type Item = u64;
+    type IntoIter = ReadDeltaIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Ok(mut encoded) => {
+                let remaining = read_len(&mut encoded);
+                ReadDeltaIter {
+                    values: Ok((encoded, 0)),
+                    remaining,
+                }
+            }
+            Err(values) => ReadDeltaIter {
+                values: Err(values),
+                remaining: values.len(),
+            },
+        }
+    }
+}
+
+/// An iterator over the values of a [`ReadDelta`].
+#[derive(Clone)]
+pub struct ReadDeltaIter<'a> {
+    /// The remaining encoded differences and the previous value, or the remaining values.
+    values: Result<(&'a [u8], u64), &'a [u64]>,
+    /// The number of remaining values.
+    remaining: usize,
+}
+
+impl Iterator for ReadDeltaIter<'_> {
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<u64> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        match &mut self.values {
+            Ok((encoded, previous)) => {
+                let zigzag = read_varint(encoded);
+                let delta = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+                *previous = previous.wrapping_add(delta as u64);
+                Some(*previous)
+            }
+            Err(values) => {
+                let (value, rest) = values.split_first()?;
+                *values = rest;
+                Some(*value)
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for ReadDeltaIter<'_> {}
+
+impl Push<&[u64]> for DeltaRegion {
+    #[inline]
+    fn push(&mut self, item: &[u64]) -> <DeltaRegion as Region>::Index {
+        self.push_values(item.len(), item.iter().copied())
+    }
+}
+
+impl<const N: usize> Push<[u64; N]> for DeltaRegion {
+    #[inline]
+    fn push(&mut self, item: [u64; N]) -> <DeltaRegion as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl Push<Vec<u64>> for DeltaRegion {
+    #[inline]
+    fn push(&mut self, item: Vec<u64>) -> <DeltaRegion as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl Push<&Vec<u64>> for DeltaRegion {
+    #[inline]
+    fn push(&mut self, item: &Vec<u64>) -> <DeltaRegion as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl Push<ReadDelta<'_>> for DeltaRegion {
+    #[inline]
+    fn push(&mut self, item: ReadDelta<'_>) -> <DeltaRegion as Region>::Index {
+        match item.0 {
+            Ok(encoded) => self.encoded.push(encoded),
+            Err(values) => self.push(values),
+        }
+    }
+}
+
+/// Appends `value` to `output` as a variable-length integer of seven bits per byte.
+fn write_varint(mut value: u64, output: &mut Vec<u8>) {
+    while value >= 0x80 {
+        output.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push(value as u8);
+}
+
+/// Reads a variable-length integer from the start of `bytes`, and advances `bytes` past it.
+fn read_varint(bytes: &mut &[u8]) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let (byte, rest) = bytes
+            .split_first()
+            .expect("DeltaRegion: truncated variable-length integer");
+        *bytes = rest;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
+
+/// Reads the number of values of an encoded slice, and advances `bytes` past it.
+fn read_len(bytes: &mut &[u8]) -> usize {
+    usize::try_from(read_varint(bytes)).expect("Length must fit usize")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let timestamps: Vec<u64> = (0..1000)
+            .map(|i| 1_700_000_000_000 + i * 3 + i % 5)
+            .chain([0, u64::MAX, 5])
+            .collect();
+        let mut c = FlatStack::<DeltaRegion>::default();
+        c.copy(&timestamps);
+        c.copy(Vec::new());
+        c.copy([7, 3]);
+
+        // Small deltas take a single byte each.
+        assert!(c.region.item_storage_size(c.indices[0]) < timestamps.len() * 8 / 6);
+        assert_eq!(timestamps.len(), c.get(0).len());
+        assert!(c.get(0).iter().eq(timestamps.iter().copied()));
+        assert!(c.get(1).is_empty());
+        assert_eq!(vec![7, 3], c.get(2).into_owned());
+
+        assert_eq!(c.get(0), ReadDelta::borrow_as(&timestamps));
+        c.copy(ReadDelta::borrow_as(&timestamps));
+        let copy = c.clone();
+        c.copy(copy.get(0));
+        assert_eq!(c.get(0), c.get(3));
+        assert_eq!(c.get(0), c.get(4));
+        assert_eq!(3, c.distinct_count());
+
+        let mut out = Vec::new();
+        c.collect_owned_into(&mut out);
+        assert_eq!(timestamps, out[0]);
+        assert_eq!(Some(timestamps), c.pop());
+    }
+
+    #[test]
+    fn test_interleaved() {
+        // Reads between pushes observe the items even as the region reallocates.
+        let mut r = DeltaRegion::default();
+        let mut items = Vec::new();
+        for i in 0..200_u64 {
+            let values: Vec<u64> = (0..i % 7).map(|j| i * 10 + j).collect();
+            items.push((r.push(&values), values));
+            for (index, values) in &items {
+                assert!(r.index(*index).iter().eq(values.iter().copied()));
+            }
+        }
+    }
+}
@@ -0,0 +1,633 @@
+//! A store for enum discriminants that packs them into as few bits as the variants require.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::storage::Storage;
+use crate::IndexOverflow;
+
+/// A container for the discriminants of an enum with `VARIANTS` variants.
+///
+/// Stores each discriminant in `ceil(log2(VARIANTS))` bits, packed into `u64` words. A
+/// discriminant does not span two words, so widths that do not divide 64 leave the high bits
+/// of each word unused. For example, options and results require one bit per discriminant,
+/// compared to a byte or more when storing a `bool` or `u8`.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::discriminant::DiscriminantStore;
+/// use flatcontainer::impls::index::IndexContainer;
+/// let mut d = <DiscriminantStore<3>>::default();
+///
+/// d.extend([0, 2, 1, 2]);
+/// assert_eq!(2, d.index(1));
+/// assert!(d.iter().eq([0, 2, 1, 2]));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiscriminantStore<const VARIANTS: usize> {
+    /// Packed discriminants.
+    words: Vec<u64>,
+    /// The number of discriminants.
+    len: usize,
+}
+
+impl<const VARIANTS: usize> DiscriminantStore<VARIANTS> {
+    /// The number of bits per discriminant.
+    pub const BITS: u32 = if VARIANTS <= 1 {
+        0
+    } else {
+        usize::BITS - (VARIANTS - 1).leading_zeros()
+    };
+
+    /// The number of discriminants per word, which is meaningless if discriminants occupy no
+    /// bits.
+    const PER_WORD: usize = match u64::BITS.checked_div(Self::BITS) {
+        Some(per_word) => per_word as usize,
+        None => 1,
+    };
+
+    /// Returns the words that pack the discriminants, starting from the least significant bit
+    /// of the first word. Each word holds `64 / BITS` discriminants, and bits past the last
+    /// discriminant are unset.
+    #[inline]
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// The number of words required to store `len` discriminants.
+    #[inline]
+    fn words_for(len: usize) -> usize {
+        if Self::BITS == 0 {
+            0
+        } else {
+            (len + Self::PER_WORD - 1) / Self::PER_WORD
+        }
+    }
+}
+
+impl<const VARIANTS: usize> Default for DiscriminantStore<VARIANTS> {
+    fn default() -> Self {
+        Self {
+            words: Vec::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<const VARIANTS: usize> Storage<usize> for DiscriminantStore<VARIANTS> {
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(Self::words_for(capacity)),
+            len: 0,
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        let words = Self::words_for(self.len + additional);
+        self.words.reserve(words.saturating_sub(self.words.len()));
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let words = Self::words_for(self.len.saturating_add(additional));
+        self.words
+            .try_reserve(words.saturating_sub(self.words.len()))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.words.heap_size(callback);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const VARIANTS: usize> IndexContainer<usize> for DiscriminantStore<VARIANTS> {
+    type Iter<'a> = DiscriminantIter<'a, VARIANTS>;
+
+    #[inline]
+    fn index(&self, index: usize) -> usize {
+        assert!(
+            index < self.len,
+            "DiscriminantStore: index {index} out of bounds for length {}",
+            self.len
+        );
+        if Self::BITS == 0 {
+            return 0;
+        }
+        let word = self.words[index / Self::PER_WORD];
+        let shift = (index % Self::PER_WORD) as u32 * Self::BITS;
+        let mask = (1 << Self::BITS) - 1;
+        (word >> shift & mask) as usize
+    }
+
+    #[inline]
+    fn push(&mut self, item: usize) {
+        if let Err(err) = self.try_push(item) {
+            panic!("DiscriminantStore: {err}");
+        }
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<usize> {
+        let index = self.len.checked_sub(1)?;
+        let item = self.index(index);
+        if Self::BITS > 0 {
+            let shift = (index % Self::PER_WORD) as u32 * Self::BITS;
+            if shift == 0 {
+                self.words.pop();
+            } else {
+                *self.words.last_mut().unwrap() &= (1 << shift) - 1;
+            }
+        }
+        self.len = index;
+        Some(item)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.words.truncate(Self::words_for(len));
+        if Self::BITS > 0 {
+            let shift = (len % Self::PER_WORD) as u32 * Self::BITS;
+            if shift > 0 {
+                *self.words.last_mut().unwrap() &= (1 << shift) - 1;
+            }
+        }
+        self.len = len;
+    }
+
+    #[inline]
+    fn try_push(&mut self, item: usize) -> Result<(), IndexOverflow> {
+        if item >= VARIANTS.max(1) {
+            return Err(IndexOverflow {
+                max: VARIANTS.saturating_sub(1),
+            });
+        }
+        if Self::BITS > 0 {
+            let shift = (self.len % Self::PER_WORD) as u32 * Self::BITS;
+            if shift == 0 {
+                self.words.push(0);
+            }
+            *self.words.last_mut().unwrap() |= (item as u64) << shift;
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        DiscriminantIter {
+            store: self,
+            range: 0..self.len,
+        }
+    }
+}
+
+/// An iterator over the discriminants of a [`DiscriminantStore`].
+#[derive(Clone)]
+pub struct DiscriminantIter<'a, const VARIANTS: usize> {
+    /// The store to read from.
+    store: &'a DiscriminantStore<VARIANTS>,
+    /// The positions to read.
+    range: std::ops::Range<usize>,
+}
+
+impl<const VARIANTS: usize> Iterator for DiscriminantIter<'_, VARIANTS> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|index| self.store.index(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl<const VARIANTS: usize> ExactSizeIterator for DiscriminantIter<'_, VARIANTS> {}
+
+/// A container for the discriminants of an enum whose number of variants is not known up
+/// front, such as an enum that gains variants as a schema evolves.
+///
+/// Like [`DiscriminantStore`], packs the discriminants into `u64` words, but chooses the
+/// width at runtime. The store starts with one bit per discriminant, and when a pushed
+/// discriminant does not fit, widens to the bits it requires by rewriting the packed words.
+/// Early data thus stays compact, and the number of variants is only limited by the width of
+/// `usize`. The store does not narrow when removing discriminants, but clearing it resets
+/// the width to one bit.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::discriminant::GrowableDiscriminantStore;
+/// use flatcontainer::impls::index::IndexContainer;
+/// let mut d = GrowableDiscriminantStore::default();
+///
+/// d.extend([0, 1, 1]);
+/// assert_eq!(1, d.bits());
+/// d.push(5);
+/// assert_eq!(3, d.bits());
+/// assert!(d.iter().eq([0, 1, 1, 5]));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GrowableDiscriminantStore {
+    /// Packed discriminants.
+    words: Vec<u64>,
+    /// The number of discriminants.
+    len: usize,
+    /// The number of bits per discriminant.
+    bits: u32,
+}
+
+impl GrowableDiscriminantStore {
+    /// Returns the number of bits per discriminant.
+    #[inline]
+    #[must_use]
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the words that pack the discriminants, starting from the least significant bit
+    /// of the first word. Each word holds `64 / bits` discriminants, and bits past the last
+    /// discriminant are unset.
+    #[inline]
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// The number of discriminants per word of `bits` bits each.
+    #[inline]
+    fn per_word(bits: u32) -> usize {
+        (u64::BITS / bits) as usize
+    }
+
+    /// The number of words required to store `len` discriminants of `bits` bits each.
+    #[inline]
+    fn words_for(len: usize, bits: u32) -> usize {
+        let per_word = Self::per_word(bits);
+        (len + per_word - 1) / per_word
+    }
+
+    /// Reads the discriminant at `index` from `words` of `bits` bits each.
+    #[inline]
+    fn read(words: &[u64], bits: u32, index: usize) -> usize {
+        let per_word = Self::per_word(bits);
+        let shift = (index % per_word) as u32 * bits;
+        (words[index / per_word] >> shift & u64::MAX >> (u64::BITS - bits)) as usize
+    }
+
+    /// Appends `item` to `words` holding `len` discriminants of `bits` bits each.
+    #[inline]
+    fn write(words: &mut Vec<u64>, bits: u32, len: usize, item: usize) {
+        let shift = (len % Self::per_word(bits)) as u32 * bits;
+        if shift == 0 {
+            words.push(0);
+        }
+        *words.last_mut().unwrap() |= (item as u64) << shift;
+    }
+
+    /// Rewrites the packed words to store discriminants in `bits` bits each.
+    fn widen(&mut self, bits: u32) {
+        let mut words = Vec::with_capacity(Self::words_for(self.len, bits));
+        for index in 0..self.len {
+            let item = Self::read(&self.words, self.bits, index);
+            Self::write(&mut words, bits, index, item);
+        }
+        self.words = words;
+        self.bits = bits;
+    }
+
+    /// Unsets the bits past the discriminant at `len - 1` in the last word.
+    #[inline]
+    fn mask_tail(&mut self, len: usize) {
+        let shift = (len % Self::per_word(self.bits)) as u32 * self.bits;
+        if shift > 0 {
+            *self.words.last_mut().unwrap() &= (1 << shift) - 1;
+        }
+    }
+}
+
+impl Default for GrowableDiscriminantStore {
+    fn default() -> Self {
+        Self {
+            words: Vec::default(),
+            len: 0,
+            bits: 1,
+        }
+    }
+}
+
+impl Storage<usize> for GrowableDiscriminantStore {
+    /// Constructs a store with space for `capacity` discriminants of one bit.
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            words: Vec::with_capacity(Self::words_for(capacity, 1)),
+            ..Self::default()
+        }
+    }
+
+    /// Reserves space for `additional` discriminants of the current width.
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        let words = Self::words_for(self.len + additional, self.bits);
+        self.words.reserve(words.saturating_sub(self.words.len()));
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let words = Self::words_for(self.len.saturating_add(additional), self.bits);
+        self.words
+            .try_reserve(words.saturating_sub(self.words.len()))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+        self.bits = 1;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.words.heap_size(callback);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl IndexContainer<usize> for GrowableDiscriminantStore {
+    type Iter<'a> = GrowableDiscriminantIter<'a>;
+
+    #[inline]
+    fn index(&self, index: usize) -> usize {
+        assert!(
+            index < self.len,
+            "GrowableDiscriminantStore: index {index} out of bounds for length {}",
+            self.len
+        );
+        Self::read(&self.words, self.bits, index)
+    }
+
+    #[inline]
+    fn push(&mut self, item: usize) {
+        let bits = usize::BITS - item.leading_zeros();
+        if bits > self.bits {
+            self.widen(bits);
+        }
+        Self::write(&mut self.words, self.bits, self.len, item);
+        self.len += 1;
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<usize> {
+        let index = self.len.checked_sub(1)?;
+        let item = self.index(index);
+        if index % Self::per_word(self.bits) == 0 {
+            self.words.pop();
+        } else {
+            self.mask_tail(index);
+        }
+        self.len = index;
+        Some(item)
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.words.truncate(Self::words_for(len, self.bits));
+        self.mask_tail(len);
+        self.len = len;
+    }
+
+    /// Pushes `item`, which always succeeds, as the store widens to fit any discriminant.
+    #[inline]
+    fn try_push(&mut self, item: usize) -> Result<(), IndexOverflow> {
+        self.push(item);
+        Ok(())
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        GrowableDiscriminantIter {
+            store: self,
+            range: 0..self.len,
+        }
+    }
+}
+
+/// An iterator over the discriminants of a [`GrowableDiscriminantStore`].
+#[derive(Clone)]
+pub struct GrowableDiscriminantIter<'a> {
+    /// The store to read from.
+    store: &'a GrowableDiscriminantStore,
+    /// The positions to read.
+    range: std::ops::Range<usize>,
+}
+
+impl Iterator for GrowableDiscriminantIter<'_> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range.next().map(|index| self.store.index(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for GrowableDiscriminantIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths() {
+        assert_eq!(0, DiscriminantStore::<1>::BITS);
+        assert_eq!(1, DiscriminantStore::<2>::BITS);
+        assert_eq!(2, DiscriminantStore::<3>::BITS);
+        assert_eq!(2, DiscriminantStore::<4>::BITS);
+        assert_eq!(3, DiscriminantStore::<5>::BITS);
+        assert_eq!(8, DiscriminantStore::<256>::BITS);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let items = (0..1000).map(|i| i * 7 % 5);
+        let mut d = DiscriminantStore::<5>::with_capacity(items.len());
+        d.extend(items.clone());
+        assert_eq!(items.len(), d.len());
+        assert!(d.iter().eq(items));
+
+        // 21 discriminants of 3 bits fit into a word.
+        assert_eq!((1000 + 20) / 21, d.words.len());
+
+        let mut unit = DiscriminantStore::<1>::default();
+        unit.extend([0, 0, 0]);
+        assert!(unit.words.is_empty());
+        assert!(unit.iter().eq([0, 0, 0]));
+    }
+
+    #[test]
+    fn test_pop() {
+        let items: Vec<_> = (0..50).map(|i| i * 7 % 5).collect();
+        let mut d = DiscriminantStore::<5>::default();
+        d.extend(items.clone());
+        for len in (0..items.len()).rev() {
+            assert_eq!(Some(items[len]), d.pop());
+            assert_eq!(DiscriminantStore::<5>::words_for(len), d.words.len());
+        }
+        assert_eq!(None, d.pop());
+
+        // Pushing after popping doesn't see stale bits.
+        d.extend([4, 4]);
+        d.pop();
+        d.push(1);
+        assert!(d.iter().eq([4, 1]));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let items: Vec<_> = (0..50).map(|i| i * 7 % 5).collect();
+        let mut d = DiscriminantStore::<5>::default();
+        d.extend(items.clone());
+        d.truncate(60);
+        assert_eq!(50, d.len());
+        d.truncate(23);
+        assert!(d.iter().eq(items[..23].iter().copied()));
+        assert_eq!(DiscriminantStore::<5>::words_for(23), d.words.len());
+
+        // Pushing after truncating doesn't see stale bits.
+        d.push(0);
+        assert_eq!(0, d.index(23));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut d = DiscriminantStore::<2>::default();
+        assert_eq!(Err(IndexOverflow { max: 1 }), d.try_push(2));
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "DiscriminantStore: index exceeds the maximum of 2")]
+    fn test_push_overflow() {
+        let mut d = DiscriminantStore::<3>::default();
+        d.push(3);
+    }
+
+    #[test]
+    fn test_growable_widening() {
+        // Introduce a new variant every 100 discriminants.
+        let items: Vec<_> = (0..1000).map(|i| i % (i / 100 + 1)).collect();
+        let mut d = GrowableDiscriminantStore::default();
+        for (i, item) in items.clone().into_iter().enumerate() {
+            d.push(item);
+            if i == 99 {
+                assert_eq!(1, d.bits());
+                assert_eq!(2, d.words.len());
+            }
+        }
+        assert_eq!(4, d.bits());
+        assert_eq!(items.len(), d.len());
+        assert!(d.iter().eq(items.clone()));
+
+        // 16 discriminants of 4 bits fit into a word.
+        assert_eq!((1000 + 15) / 16, d.words.len());
+
+        d.push(usize::MAX);
+        assert_eq!(usize::BITS, d.bits());
+        assert_eq!(Some(usize::MAX), d.pop());
+        assert!(d.iter().eq(items.clone()));
+
+        d.clear();
+        assert_eq!(1, d.bits());
+        d.extend([1, 0]);
+        assert!(d.iter().eq([1, 0]));
+    }
+
+    #[test]
+    fn test_growable_truncate() {
+        let items: Vec<_> = (0..50).map(|i| i * 7 % 5).collect();
+        let mut d = GrowableDiscriminantStore::default();
+        d.extend(items.clone());
+        d.truncate(23);
+        assert!(d.iter().eq(items[..23].iter().copied()));
+        assert_eq!(GrowableDiscriminantStore::words_for(23, 3), d.words.len());
+
+        // Pushing after truncating or popping doesn't see stale bits.
+        d.push(0);
+        assert_eq!(0, d.index(23));
+        d.push(7);
+        d.pop();
+        d.push(1);
+        assert!(d.iter().skip(23).eq([0, 1]));
+    }
+}
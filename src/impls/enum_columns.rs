@@ -0,0 +1,400 @@
+//! A region that stores enums with fields shared by all variants in columns.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::columns::{ColumnsRegion, ReadColumns};
+use crate::impls::discriminant::DiscriminantStore;
+use crate::impls::index::{IndexContainer, IndexOptimized};
+use crate::impls::union::UnionColumnRegion;
+use crate::{Push, Region};
+
+/// A region to hold a column of an enum with `N` variants that all carry the same fields,
+/// such as a timestamp, in addition to a payload specific to the variant.
+///
+/// Combines three parts, each of which can be read on its own:
+/// * the discriminants of all items, in an index container `D` that defaults to a
+///   [`DiscriminantStore`],
+/// * the shared fields of all items, one row per item in a [`ColumnsRegion`] with columns
+///   of type `S`, and
+/// * the payloads, separated by variant in a [`UnionColumnRegion`] with regions of type `R`.
+///
+/// A [`GrowableDiscriminantStore`](crate::impls::discriminant::GrowableDiscriminantStore)
+/// as `D` uses one bit per discriminant until an item of a variant that does not fit arrives,
+/// which suits enums with many but rarely used variants.
+///
+/// [`iter_shared`](EnumColumnsRegion::iter_shared) scans a shared field across all variants
+/// without reading the payloads. Items are pushed as a tuple of the variant, the row of
+/// shared fields, and the payload. An index consists of the row of the item, and the
+/// position of the payload within the variant.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::enum_columns::EnumColumnsRegion;
+/// use flatcontainer::{MirrorRegion, Push, Region, StringRegion};
+/// let mut r = <EnumColumnsRegion<MirrorRegion<u64>, StringRegion, 2>>::default();
+///
+/// let index = r.push((1, [20], "b"));
+/// let _ = r.push((0, [10], "a"));
+/// let (variant, shared, payload) = r.index(index);
+/// assert_eq!((1, 20, "b"), (variant, shared.get(0), payload));
+/// assert!(r.iter_shared(0).eq([Some(20), Some(10)]));
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            S: Serialize + for<'a> Deserialize<'a>,
+            S::Index: Serialize + for<'a> Deserialize<'a>,
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            O: Serialize + for<'a> Deserialize<'a>,
+            D: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct EnumColumnsRegion<
+    S: Region,
+    R: Region,
+    const N: usize,
+    O = IndexOptimized,
+    D = DiscriminantStore<N>,
+> {
+    /// The discriminant per row.
+    discriminants: D,
+    /// The shared fields per row.
+    shared: ColumnsRegion<S, O>,
+    /// The payloads per variant.
+    payloads: UnionColumnRegion<R, N>,
+}
+
+impl<S, R, const N: usize, O, D> Debug for EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region + Debug,
+    S::Index: Debug,
+    R: Region + Debug,
+    R::Index: Debug,
+    O: Debug,
+    D: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnumColumnsRegion")
+            .field("discriminants", &self.discriminants)
+            .field("shared", &self.shared)
+            .field("payloads", &self.payloads)
+            .finish()
+    }
+}
+
+impl<S, R, const N: usize, O, D> Clone for EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region + Clone,
+    R: Region + Clone,
+    O: Clone,
+    D: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            discriminants: self.discriminants.clone(),
+            shared: self.shared.clone(),
+            payloads: self.payloads.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.discriminants.clone_from(&source.discriminants);
+        self.shared.clone_from(&source.shared);
+        self.payloads.clone_from(&source.payloads);
+    }
+}
+
+impl<S, R, const N: usize, O, D> Default for EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region,
+    R: Region,
+    O: IndexContainer<usize>,
+    D: IndexContainer<usize>,
+{
+    fn default() -> Self {
+        Self {
+            discriminants: D::default(),
+            shared: ColumnsRegion::default(),
+            payloads: UnionColumnRegion::default(),
+        }
+    }
+}
+
+impl<S, R, const N: usize, O, D> EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region,
+    R: Region,
+    O: IndexContainer<usize>,
+    D: IndexContainer<usize>,
+{
+    /// Returns the discriminants of all items, in insertion order.
+    #[inline]
+    #[must_use]
+    pub fn discriminants(&self) -> &D {
+        &self.discriminants
+    }
+
+    /// Returns the shared fields of all items, with one row per item in insertion order.
+    #[inline]
+    #[must_use]
+    pub fn shared(&self) -> &ColumnsRegion<S, O> {
+        &self.shared
+    }
+
+    /// Returns the payloads of all items, separated by variant.
+    #[inline]
+    #[must_use]
+    pub fn payloads(&self) -> &UnionColumnRegion<R, N> {
+        &self.payloads
+    }
+
+    /// Iterate the shared field `column` of all items in insertion order, without reading
+    /// the payloads. Yields `None` for items that do not have the field.
+    pub fn iter_shared(
+        &self,
+        column: usize,
+    ) -> impl Iterator<Item = Option<S::ReadItem<'_>>> + Clone + '_ {
+        self.shared.iter_column(column)
+    }
+}
+
+impl<S, R, const N: usize, O, D> Region for EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region,
+    R: Region,
+    O: IndexContainer<usize>,
+    D: IndexContainer<usize>,
+{
+    type Owned = (usize, Vec<S::Owned>, R::Owned);
+    type ReadItem<'a> = (usize, ReadColumns<'a, S>, R::ReadItem<'a>) where Self: 'a;
+    /// The row of the item, and the position of the payload within the variant.
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            discriminants: D::with_capacity(regions.clone().map(|r| r.discriminants.len()).sum()),
+            shared: ColumnsRegion::merge_regions(regions.clone().map(|r| &r.shared)),
+            payloads: UnionColumnRegion::merge_regions(regions.map(|r| &r.payloads)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (row, position): Self::Index) -> Self::ReadItem<'_> {
+        let variant = self.discriminants.index(row);
+        let (_, payload) = self.payloads.index((variant, position));
+        (variant, self.shared.index(row), payload)
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.discriminants
+            .reserve(regions.clone().map(|r| r.discriminants.len()).sum());
+        self.shared
+            .reserve_regions(regions.clone().map(|r| &r.shared));
+        self.payloads.reserve_regions(regions.map(|r| &r.payloads));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.discriminants
+            .try_reserve(regions.clone().map(|r| r.discriminants.len()).sum())?;
+        self.shared
+            .try_reserve_regions(regions.clone().map(|r| &r.shared))?;
+        self.payloads
+            .try_reserve_regions(regions.map(|r| &r.payloads))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.discriminants.clear();
+        self.shared.clear();
+        self.payloads.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.discriminants.heap_size(&mut callback);
+        self.shared.heap_size(&mut callback);
+        self.payloads.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        (
+            item.0,
+            ColumnsRegion::<S, O>::reborrow(item.1),
+            R::reborrow(item.2),
+        )
+    }
+}
+
+impl<S, R, const N: usize, O, D, Sh, T> Push<(usize, Sh, T)> for EnumColumnsRegion<S, R, N, O, D>
+where
+    S: Region,
+    R: Push<T>,
+    O: IndexContainer<usize>,
+    D: IndexContainer<usize>,
+    ColumnsRegion<S, O>: Push<Sh, Index = usize>,
+{
+    /// Pushes the variant, the row of shared fields, and the payload of an item.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variant is out of bounds.
+    #[inline]
+    fn push(&mut self, (variant, shared, item): (usize, Sh, T)) -> Self::Index {
+        let (_, position) = self.payloads.push((variant, item));
+        self.discriminants.push(variant);
+        let row = self.shared.push(shared);
+        (row, position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::discriminant::GrowableDiscriminantStore;
+    use crate::{FlatStack, IntoOwned, MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Event {
+        Login { timestamp: u64, user: String },
+        Logout { timestamp: u64, user: String },
+        Error { timestamp: u64, message: String },
+    }
+
+    impl Event {
+        fn split(&self) -> (usize, [u64; 1], &str) {
+            match self {
+                Event::Login { timestamp, user } => (0, [*timestamp], user),
+                Event::Logout { timestamp, user } => (1, [*timestamp], user),
+                Event::Error { timestamp, message } => (2, [*timestamp], message),
+            }
+        }
+
+        fn from_parts((variant, shared, payload): (usize, Vec<u64>, String)) -> Self {
+            let timestamp = shared[0];
+            match variant {
+                0 => Event::Login {
+                    timestamp,
+                    user: payload,
+                },
+                1 => Event::Logout {
+                    timestamp,
+                    user: payload,
+                },
+                _ => Event::Error {
+                    timestamp,
+                    message: payload,
+                },
+            }
+        }
+    }
+
+    type EventRegion = EnumColumnsRegion<MirrorRegion<u64>, StringRegion, 3>;
+
+    #[test]
+    fn test_shared_timestamps() {
+        let events = [
+            Event::Login {
+                timestamp: 10,
+                user: "ann".to_string(),
+            },
+            Event::Error {
+                timestamp: 12,
+                message: "disk full".to_string(),
+            },
+            Event::Login {
+                timestamp: 15,
+                user: "bob".to_string(),
+            },
+            Event::Logout {
+                timestamp: 20,
+                user: "ann".to_string(),
+            },
+        ];
+        let mut c = FlatStack::<EventRegion>::default();
+        for event in &events {
+            c.copy(event.split());
+        }
+
+        // Reconstruct the full enums.
+        assert!(c
+            .iter()
+            .map(|item| Event::from_parts(item.into_owned()))
+            .eq(events.iter().cloned()));
+
+        // Scan the shared timestamps and the discriminants without reading the payloads.
+        assert!(c.region.iter_shared(0).eq([10, 12, 15, 20].map(Some)));
+        assert!(c.region.discriminants().iter().eq([0, 2, 0, 1]));
+        assert!(c.region.payloads().iter_variant(0).eq(["ann", "bob"]));
+
+        let copy = c.clone();
+        c.copy(copy.get(1));
+        assert_eq!(events[1], Event::from_parts(c.get(4).into_owned()));
+
+        let merged = EventRegion::merge_regions(std::iter::once(&c.region));
+        assert!(merged.iter_shared(0).next().is_none());
+        c.clear();
+        assert!(c.region.iter_shared(0).next().is_none());
+    }
+
+    #[test]
+    fn test_growable_discriminants() {
+        type R = EnumColumnsRegion<
+            MirrorRegion<u64>,
+            StringRegion,
+            8,
+            IndexOptimized,
+            GrowableDiscriminantStore,
+        >;
+        let mut r = R::default();
+        let mut indices: Vec<_> = (0..100).map(|i| r.push((i % 2, [i as u64], "x"))).collect();
+        assert_eq!(1, r.discriminants().bits());
+
+        // The first item of variant 5 widens the discriminants to three bits.
+        indices.push(r.push((5, [100], "y")));
+        assert_eq!(3, r.discriminants().bits());
+        for (i, index) in indices.into_iter().enumerate() {
+            let (variant, shared, payload) = r.index(index);
+            let expected = if i < 100 { (i % 2, "x") } else { (5, "y") };
+            assert_eq!(expected, (variant, payload));
+            assert_eq!(i as u64, shared.get(0));
+        }
+
+        let mut merged = R::merge_regions(std::iter::once(&r));
+        assert_eq!(1, merged.discriminants().bits());
+        let index = merged.push((7, [0], "z"));
+        assert_eq!((7, "z"), (merged.index(index).0, merged.index(index).2));
+    }
+
+    #[test]
+    #[should_panic(expected = "UnionColumnRegion: variant 3 out of bounds for 3 variants")]
+    fn test_variant_out_of_bounds() {
+        let mut r = EventRegion::default();
+        let _ = r.push((3, [0], "x"));
+    }
+}
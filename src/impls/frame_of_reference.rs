@@ -0,0 +1,311 @@
+//! A region that stores integers relative to a base value.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// Largest zig-zag encoded delta that we store without re-basing, which is the largest value
+/// that fits in two variable-length encoded bytes.
+const MAX_DELTA: u128 = (1 << 14) - 1;
+
+/// A region for integers clustered around a common value, using frame-of-reference encoding.
+///
+/// The region picks the first pushed value as its base and stores the difference of subsequent
+/// values to the base as variable-length encoded integers. Small differences occupy a single
+/// byte, independently of the width of `T`. When a value is too far from the current base, the
+/// region records a checkpoint with the value as the new base. The index is the offset of the
+/// encoded difference, which is sufficient to find the checkpoint applicable to it.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::frame_of_reference::ForRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <ForRegion<u64>>::default();
+///
+/// let indices: Vec<_> = (1000..1010).map(|value| r.push(value)).collect();
+/// for (value, index) in (1000..1010).zip(indices) {
+///     assert_eq!(value, r.index(index));
+/// }
+///
+/// // One byte per value, and a single base.
+/// let mut size = 0;
+/// r.heap_size(|siz, _| size += siz);
+/// assert_eq!(10 + std::mem::size_of::<(usize, u64)>(), size);
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ForRegion<T> {
+    /// Checkpoints of the offset at which a base starts, and the base.
+    bases: Vec<(usize, T)>,
+    /// Variable-length encoded, zig-zagged differences to the base.
+    deltas: Vec<u8>,
+}
+
+impl<T> Default for ForRegion<T> {
+    fn default() -> Self {
+        Self {
+            bases: Vec::default(),
+            deltas: Vec::default(),
+        }
+    }
+}
+
+impl<T: Clone> Clone for ForRegion<T> {
+    fn clone(&self) -> Self {
+        Self {
+            bases: self.bases.clone(),
+            deltas: self.deltas.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.bases.clone_from(&source.bases);
+        self.deltas.clone_from(&source.deltas);
+    }
+}
+
+impl<T> ForRegion<T> {
+    /// Returns the base applicable to the delta at `offset`.
+    #[inline]
+    fn base(&self, offset: usize) -> &T {
+        let position = self.bases.partition_point(|(start, _)| *start <= offset);
+        &self.bases[position - 1].1
+    }
+
+    /// Appends `delta` as a variable-length encoded integer.
+    #[inline]
+    fn encode(&mut self, mut delta: u128) {
+        while delta >= 0x80 {
+            self.deltas.push((delta as u8) | 0x80);
+            delta >>= 7;
+        }
+        self.deltas.push(delta as u8);
+    }
+
+    /// Reads the variable-length encoded integer at `offset`.
+    #[inline]
+    fn decode(&self, offset: usize) -> u128 {
+        let mut delta = 0;
+        for (shift, byte) in self.deltas[offset..].iter().enumerate() {
+            delta |= u128::from(byte & 0x7f) << (7 * shift);
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        delta
+    }
+}
+
+/// Zig-zag encodes `value` so that small negative numbers map to small unsigned numbers.
+#[inline]
+fn zigzag(value: i128) -> u128 {
+    ((value << 1) ^ (value >> 127)) as u128
+}
+
+/// Reverses [`zigzag`].
+#[inline]
+fn unzigzag(value: u128) -> i128 {
+    ((value >> 1) as i128) ^ -((value & 1) as i128)
+}
+
+macro_rules! implement_for {
+    ($index_type:ty) => {
+        impl Region for ForRegion<$index_type> {
+            type Owned = $index_type;
+            type ReadItem<'a> = $index_type;
+            type Index = usize;
+
+            fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+            where
+                Self: 'a,
+            {
+                Self {
+                    bases: Vec::with_capacity(regions.clone().map(|r| r.bases.len()).sum()),
+                    deltas: Vec::with_capacity(regions.map(|r| r.deltas.len()).sum()),
+                }
+            }
+
+            #[inline]
+            fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+                let base = i128::from(*self.base(index));
+                (base + unzigzag(self.decode(index))) as $index_type
+            }
+
+            fn reserve_regions<'a, I>(&mut self, regions: I)
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                self.bases
+                    .reserve(regions.clone().map(|r| r.bases.len()).sum());
+                self.deltas.reserve(regions.map(|r| r.deltas.len()).sum());
+            }
+
+            fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                self.bases
+                    .try_reserve(regions.clone().map(|r| r.bases.len()).sum())?;
+                self.deltas
+                    .try_reserve(regions.map(|r| r.deltas.len()).sum())
+            }
+
+            fn clear(&mut self) {
+                self.bases.clear();
+                self.deltas.clear();
+            }
+
+            fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+                let size_of_base = std::mem::size_of::<(usize, $index_type)>();
+                callback(
+                    self.bases.len() * size_of_base,
+                    self.bases.capacity() * size_of_base,
+                );
+                callback(self.deltas.len(), self.deltas.capacity());
+            }
+
+            #[inline]
+            fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+            where
+                Self: 'a,
+            {
+                item
+            }
+        }
+
+        impl Push<$index_type> for ForRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: $index_type) -> usize {
+                let offset = self.deltas.len();
+                let mut delta = self
+                    .bases
+                    .last()
+                    .map(|(_, base)| zigzag(i128::from(item) - i128::from(*base)));
+                if delta.map_or(true, |delta| delta > MAX_DELTA) {
+                    self.bases.push((offset, item));
+                    delta = Some(0);
+                }
+                self.encode(delta.unwrap_or_default());
+                offset
+            }
+        }
+
+        impl Push<&$index_type> for ForRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: &$index_type) -> usize {
+                self.push(*item)
+            }
+        }
+
+        impl Push<&&$index_type> for ForRegion<$index_type> {
+            #[inline]
+            fn push(&mut self, item: &&$index_type) -> usize {
+                self.push(**item)
+            }
+        }
+
+        impl ReserveItems<$index_type> for ForRegion<$index_type> {
+            #[inline]
+            fn reserve_items<I>(&mut self, items: I)
+            where
+                I: Iterator<Item = $index_type> + Clone,
+            {
+                // Assume that values are close to their base, requiring one byte each.
+                self.deltas.reserve(items.count());
+            }
+        }
+
+        impl<'a> ReserveItems<&'a $index_type> for ForRegion<$index_type> {
+            #[inline]
+            fn reserve_items<I>(&mut self, items: I)
+            where
+                I: Iterator<Item = &'a $index_type> + Clone,
+            {
+                self.reserve_items(items.copied());
+            }
+        }
+    };
+}
+
+implement_for!(u8);
+implement_for!(u16);
+implement_for!(u32);
+implement_for!(u64);
+
+implement_for!(i8);
+implement_for!(i16);
+implement_for!(i32);
+implement_for!(i64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compact() {
+        let mut r = <ForRegion<i64>>::default();
+        let values: Vec<i64> = (0..100).map(|i| 1000 + (i * 7) % 11).collect();
+        let indices: Vec<_> = values.iter().map(|value| r.push(value)).collect();
+
+        for (value, index) in values.iter().zip(indices) {
+            assert_eq!(*value, r.index(index));
+        }
+
+        let mut size = 0;
+        r.heap_size(|siz, _| size += siz);
+        assert_eq!(100 + std::mem::size_of::<(usize, i64)>(), size);
+    }
+
+    #[test]
+    fn test_rebase() {
+        let mut r = <ForRegion<i64>>::default();
+        let values = [
+            0,
+            -1,
+            1,
+            i64::MIN,
+            i64::MIN + 1,
+            i64::MAX,
+            i64::MAX - 8000,
+            42,
+            42 - 8191,
+            42 + 8192,
+        ];
+        let indices: Vec<_> = values.iter().map(|value| r.push(value)).collect();
+
+        for (value, index) in values.iter().zip(indices.iter().copied()) {
+            assert_eq!(*value, r.index(index));
+        }
+        // Reading in reverse order finds the correct base.
+        for (value, index) in values.iter().zip(indices).rev() {
+            assert_eq!(*value, r.index(index));
+        }
+        assert_eq!(5, r.bases.len());
+    }
+
+    #[test]
+    fn test_unsigned() {
+        let mut r = <ForRegion<u8>>::default();
+        let indices: Vec<_> = (0..=u8::MAX).rev().map(|value| r.push(value)).collect();
+        for (value, index) in (0..=u8::MAX).rev().zip(indices) {
+            assert_eq!(value, r.index(index));
+        }
+        assert_eq!(1, r.bases.len());
+    }
+
+    #[test]
+    fn test_zigzag() {
+        for value in [0, 1, -1, 2, -2, i128::MAX, i128::MIN] {
+            assert_eq!(value, unzigzag(zigzag(value)));
+        }
+        assert_eq!(0, zigzag(0));
+        assert_eq!(1, zigzag(-1));
+        assert_eq!(2, zigzag(1));
+    }
+}
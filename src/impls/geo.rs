@@ -0,0 +1,252 @@
+//! A region that stores geographic points.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::tuple::TupleABRegion;
+use crate::{FlatStack, IntoOwned, MirrorRegion, Push, Region, ReserveItems};
+
+/// A geographic point, which is a latitude and a longitude in degrees.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::geo::GeoPoint;
+/// let zurich = GeoPoint::new(47.37, 8.54);
+/// assert!(zurich.within(GeoPoint::new(45.8, 5.9), GeoPoint::new(47.8, 10.5)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// Latitude in degrees.
+    lat: f64,
+    /// Longitude in degrees.
+    lon: f64,
+}
+
+impl GeoPoint {
+    /// Constructs a point from a latitude and a longitude in degrees.
+    #[must_use]
+    pub fn new(lat: f64, lon: f64) -> Self {
+        Self { lat, lon }
+    }
+
+    /// Returns the latitude in degrees.
+    #[inline]
+    #[must_use]
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    /// Returns the longitude in degrees.
+    #[inline]
+    #[must_use]
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Returns `true` if the point is inside the bounding box spanned by the corners `min` and
+    /// `max`, including its boundary.
+    ///
+    /// The box does not wrap around the antimeridian, so `min.lon()` must not exceed
+    /// `max.lon()` for a point to be inside.
+    #[inline]
+    #[must_use]
+    pub fn within(&self, min: GeoPoint, max: GeoPoint) -> bool {
+        (min.lat..=max.lat).contains(&self.lat) && (min.lon..=max.lon).contains(&self.lon)
+    }
+}
+
+impl<'a> IntoOwned<'a> for GeoPoint {
+    type Owned = (f64, f64);
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        (self.lat, self.lon)
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self.into_owned();
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self::new(owned.0, owned.1)
+    }
+}
+
+/// A region for geographic points.
+///
+/// Stores the latitude and longitude of each point in a tuple of mirror regions, which makes
+/// the index the representation of the point. The read item is a [`GeoPoint`].
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::geo::GeoPointRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = GeoPointRegion::default();
+///
+/// let index = r.push((47.37, 8.54));
+/// assert_eq!(47.37, r.index(index).lat());
+/// assert_eq!(8.54, r.index(index).lon());
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GeoPointRegion {
+    /// Storage for latitudes and longitudes.
+    inner: TupleABRegion<MirrorRegion<f64>, MirrorRegion<f64>>,
+}
+
+impl Region for GeoPointRegion {
+    type Owned = (f64, f64);
+    type ReadItem<'a> = GeoPoint;
+    type Index = (f64, f64);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: TupleABRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let (lat, lon) = self.inner.index(index);
+        GeoPoint { lat, lon }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<GeoPoint> for GeoPointRegion {
+    #[inline]
+    fn push(&mut self, item: GeoPoint) -> (f64, f64) {
+        self.inner.push((item.lat, item.lon))
+    }
+}
+
+impl Push<(f64, f64)> for GeoPointRegion {
+    /// Pushes a point given as a latitude and a longitude.
+    #[inline]
+    fn push(&mut self, (lat, lon): (f64, f64)) -> (f64, f64) {
+        self.push(GeoPoint::new(lat, lon))
+    }
+}
+
+impl Push<&(f64, f64)> for GeoPointRegion {
+    #[inline]
+    fn push(&mut self, item: &(f64, f64)) -> (f64, f64) {
+        self.push(*item)
+    }
+}
+
+impl<T> ReserveItems<T> for GeoPointRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+impl<S: IndexContainer<(f64, f64)>> FlatStack<GeoPointRegion, S> {
+    /// Returns the offsets of the points inside the bounding box spanned by the corners `min`
+    /// and `max`, in ascending order. See [`GeoPoint::within`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::geo::{GeoPoint, GeoPointRegion};
+    /// use flatcontainer::FlatStack;
+    /// let mut c = FlatStack::<GeoPointRegion>::default();
+    /// c.copy((47.37, 8.54));
+    /// c.copy((51.51, -0.13));
+    ///
+    /// let inside = c.within_bbox(GeoPoint::new(45.8, 5.9), GeoPoint::new(47.8, 10.5));
+    /// assert!(inside.eq([0]));
+    /// ```
+    pub fn within_bbox(
+        &self,
+        min: GeoPoint,
+        max: GeoPoint,
+    ) -> impl Iterator<Item = usize> + Clone + '_ {
+        self.iter()
+            .enumerate()
+            .filter(move |(_, point)| point.within(min, max))
+            .map(|(offset, _)| offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_within_bbox() {
+        let points = [
+            (47.37, 8.54),
+            (46.95, 7.45),
+            (51.51, -0.13),
+            (46.2, 6.14),
+            (-33.87, 151.21),
+            (47.8, 10.5),
+        ];
+        let mut c = FlatStack::<GeoPointRegion>::default();
+        for point in &points {
+            c.copy(point);
+        }
+        assert_eq!(points.len(), c.len());
+        assert_eq!(points[2], c.get(2).into_owned());
+
+        let min = GeoPoint::new(45.8, 5.9);
+        let max = GeoPoint::new(47.8, 10.5);
+        assert!(c.within_bbox(min, max).eq([0, 1, 3, 5]));
+
+        // A box spanning the antimeridian is empty.
+        let min = GeoPoint::new(-90.0, 170.0);
+        let max = GeoPoint::new(90.0, -170.0);
+        assert_eq!(0, c.within_bbox(min, max).count());
+    }
+}
@@ -1,13 +1,35 @@
 //! A slice container that Huffman encodes its contents.
 
 use std::collections::BTreeMap;
+use std::collections::TryReserveError;
+use std::fmt::{Display, Formatter};
 
-use crate::{Push, Region};
+use crate::{Push, Region, ReserveItems};
 
 use self::encoded::Encoded;
 use self::huffman::Huffman;
 use self::wrapper::Wrapped;
 
+/// An error that occurs when decoding malformed Huffman-encoded data.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DecodeError {
+    /// The decoding map has no symbol for the bits.
+    InvalidMap,
+    /// The data ends within the code of a symbol.
+    Incomplete,
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::InvalidMap => write!(f, "invalid decoding map"),
+            DecodeError::Incomplete => write!(f, "malformed data: decode incomplete"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
 /// A container that contains slices `[B]` as items.
 pub struct HuffmanContainer<B: Ord + Clone> {
     /// Either encoded data or raw data.
@@ -21,6 +43,38 @@ impl<B> HuffmanContainer<B>
 where
     B: Ord + Clone,
 {
+    /// Returns the number of bytes if encoded, or symbols if raw, to reserve for the contents
+    /// of `regions`.
+    fn additional<'a, I>(&self, regions: I) -> usize
+    where
+        B: 'a,
+        I: Iterator<Item = &'a Self>,
+    {
+        match &self.inner {
+            Ok((huffman, _bytes, _bits)) => regions
+                .map(|region| match &region.inner {
+                    Ok((_huffman, bytes, _bits)) => bytes.len(),
+                    Err(_raw) => {
+                        let bits: usize = region
+                            .stats
+                            .iter()
+                            .filter_map(|(symbol, count)| {
+                                Some(huffman.bits(symbol)? * usize::try_from(*count).ok()?)
+                            })
+                            .sum();
+                        (bits + 7) / 8
+                    }
+                })
+                .sum(),
+            Err(_raw) => regions
+                .map(|region| match &region.inner {
+                    Ok(_) => region.stats.values().sum::<i64>() as usize,
+                    Err(raw) => raw.len(),
+                })
+                .sum(),
+        }
+    }
+
     /// Prints statistics about encoded containers.
     pub fn print(&self) {
         if let Ok((_huff, _bytes, bits)) = &self.inner {
@@ -88,12 +142,40 @@ where
         }
     }
 
-    fn reserve_regions<'a, I>(&mut self, _regions: I)
+    /// Returns the number of bytes spanned by the encoded bits of the item at `index`, or the
+    /// size of its symbols if the region is not encoded.
+    fn item_storage_size(&self, (lower, upper): Self::Index) -> usize {
+        match &self.inner {
+            Ok(_) => (upper - lower + 7) / 8,
+            Err(_) => (upper - lower) * std::mem::size_of::<B>(),
+        }
+    }
+
+    /// Reserves space for the contents of `regions`. An encoded region reserves the encoded
+    /// bytes of encoded regions, and estimates the bytes of raw regions from their symbol
+    /// counts and its own code. A raw region reserves the symbols of all regions.
+    fn reserve_regions<'a, I>(&mut self, regions: I)
     where
         Self: 'a,
         I: Iterator<Item = &'a Self> + Clone,
     {
-        todo!()
+        let additional = self.additional(regions);
+        match &mut self.inner {
+            Ok((_huffman, bytes, _bits)) => bytes.reserve(additional),
+            Err(raw) => raw.reserve(additional),
+        }
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let additional = self.additional(regions);
+        match &mut self.inner {
+            Ok((_huffman, bytes, _bits)) => bytes.try_reserve(additional),
+            Err(raw) => raw.try_reserve(additional),
+        }
     }
 
     fn clear(&mut self) {
@@ -223,6 +305,60 @@ where
     }
 }
 
+impl<'a, B> ReserveItems<&'a [B]> for HuffmanContainer<B>
+where
+    B: Ord + Clone + Sized + 'static,
+{
+    /// Reserves space for the symbols of `items`. A raw container reserves the number of
+    /// symbols. An encoded container reserves the bytes that encode the symbols with its code,
+    /// ignoring symbols that the code does not contain.
+    ///
+    /// Records the symbols in the statistics like [`Push`] does, such that a code built from the
+    /// statistics reflects the reserved items. Pushing the reserved items records them again.
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a [B]> + Clone,
+    {
+        for x in items.clone().flat_map(|item| item.iter()) {
+            *self.stats.entry(x.clone()).or_insert(0) += 1;
+        }
+        match &mut self.inner {
+            Ok((huffman, bytes, _bits)) => {
+                let bits: usize = items
+                    .flat_map(|item| item.iter())
+                    .filter_map(|symbol| huffman.bits(symbol))
+                    .sum();
+                bytes.reserve((bits + 7) / 8);
+            }
+            Err(raw) => raw.reserve(items.map(<[B]>::len).sum()),
+        }
+    }
+}
+
+impl<'a, B> ReserveItems<&'a Vec<B>> for HuffmanContainer<B>
+where
+    B: Ord + Clone + Sized + 'static,
+{
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a Vec<B>> + Clone,
+    {
+        self.reserve_items(items.map(Vec::as_slice));
+    }
+}
+
+impl<'a, B, const N: usize> ReserveItems<&'a [B; N]> for HuffmanContainer<B>
+where
+    B: Ord + Clone + Sized + 'static,
+{
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a [B; N]> + Clone,
+    {
+        self.reserve_items(items.map(<[B; N]>::as_slice));
+    }
+}
+
 impl<'a, B> Push<Wrapped<'a, B>> for HuffmanContainer<B>
 where
     B: Ord + Clone + Sized + 'static,
@@ -273,7 +409,7 @@ impl<B: Ord + Clone> Default for HuffmanContainer<B> {
 mod wrapper {
     use std::fmt::Debug;
 
-    use super::Encoded;
+    use super::{DecodeError, Encoded};
 
     pub struct Wrapped<'a, B: Ord> {
         inner: Result<Encoded<'a, B>, &'a [B]>,
@@ -297,6 +433,16 @@ mod wrapper {
                 Err(symbols) => Err(symbols),
             }
         }
+        /// Like [`Wrapped::decode`], but the decoding iterator reports malformed data as an
+        /// error instead of panicking, and ends after the error.
+        pub fn try_decode(
+            &'a self,
+        ) -> Result<impl Iterator<Item = Result<&'a B, DecodeError>> + 'a, &'a [B]> {
+            match &self.inner {
+                Ok(encoded) => Ok(encoded.try_decode()),
+                Err(symbols) => Err(symbols),
+            }
+        }
         /// A wrapper around an encoded sequence.
         pub fn encoded(e: Encoded<'a, B>) -> Self {
             Self { inner: Ok(e) }
@@ -378,7 +524,7 @@ mod wrapper {
 /// Wrapper around a Huffman decoder and byte slices, decodeable to a byte sequence.
 mod encoded {
 
-    use super::Huffman;
+    use super::{DecodeError, Huffman};
 
     /// Welcome to GATs!
     pub struct Encoded<'a, B: Ord> {
@@ -402,6 +548,15 @@ mod encoded {
             };
             self.huffman.decode(iter)
         }
+        /// Like [`Encoded::decode`], but reports malformed data as an error instead of
+        /// panicking.
+        pub fn try_decode(&'a self) -> impl Iterator<Item = Result<&'a B, DecodeError>> + 'a {
+            let iter = BitIterator {
+                bytes: self.bytes,
+                bit_range: self.bit_range,
+            };
+            self.huffman.try_decode(iter)
+        }
         pub fn new(huffman: &'a Huffman<B>, bytes: &'a [u8], bit_range: (usize, usize)) -> Self {
             Self {
                 huffman,
@@ -464,7 +619,7 @@ mod huffman {
     use std::collections::BTreeMap;
     use std::convert::TryInto;
 
-    use self::decoder::Decoder;
+    use self::decoder::{Decoder, TryDecoder};
     use self::encoder::Encoder;
 
     /// Encoding and decoding state for Huffman codes.
@@ -507,6 +662,12 @@ mod huffman {
             Encoder::new(&self.encode, initially, symbols.into_iter())
         }
 
+        /// Returns the number of bits that encode `symbol`, or `None` if the code does not
+        /// contain it.
+        pub fn bits(&self, symbol: &T) -> Option<usize> {
+            self.encode.get(symbol).map(|(bits, _code)| *bits)
+        }
+
         /// Decodes the provided bytes as a sequence of symbols.
         pub fn decode<I>(&self, bytes: I) -> Decoder<'_, T, I::IntoIter>
         where
@@ -515,6 +676,15 @@ mod huffman {
             Decoder::new(&self.decode, bytes.into_iter())
         }
 
+        /// Decodes the provided bytes as a sequence of symbols, reporting malformed data as an
+        /// error.
+        pub fn try_decode<I>(&self, bytes: I) -> TryDecoder<'_, T, I::IntoIter>
+        where
+            I: IntoIterator<Item = (u8, usize)>,
+        {
+            TryDecoder::new(Decoder::new(&self.decode, bytes.into_iter()))
+        }
+
         pub fn create_from(counts: BTreeMap<T, i64>) -> Self
         where
             T: Clone,
@@ -554,7 +724,7 @@ mod huffman {
                     }
                 }
             }
-            levels.sort_by(|x, y| x.0.cmp(&y.0));
+            levels.sort_by_key(|x| x.0);
             let mut code: u64 = 0;
             let mut prev_level = 0;
             let mut encode = BTreeMap::new();
@@ -644,6 +814,7 @@ mod huffman {
     /// A tabled Huffman decoder, written as an iterator.
     mod decoder {
 
+        use super::super::DecodeError;
         use super::Decode;
 
         #[derive(Copy, Clone)]
@@ -670,12 +841,12 @@ mod huffman {
             }
         }
 
-        impl<'a, T, I> Iterator for Decoder<'a, T, I>
+        impl<'a, T, I> Decoder<'a, T, I>
         where
             I: Iterator<Item = (u8, usize)>,
         {
-            type Item = &'a T;
-            fn next(&mut self) -> Option<&'a T> {
+            /// Decodes the next symbol, or returns an error if the data are malformed.
+            pub fn try_next(&mut self) -> Option<Result<&'a T, DecodeError>> {
                 // We must navigate `self.decode`, restocking bits whenever possible.
                 // We stop if ever there are not enough bits remaining.
                 let mut map = self.decode;
@@ -699,20 +870,20 @@ mod huffman {
                         let byte = (self.pending_byte << (8 - self.pending_bits)) as usize;
                         match &map[byte] {
                             Decode::Void => {
-                                panic!("invalid decoding map");
+                                return Some(Err(DecodeError::InvalidMap));
                             }
                             Decode::Further(_) => {
-                                panic!("malformed data: decode incomplete (Further)");
+                                return Some(Err(DecodeError::Incomplete));
                             }
                             Decode::Symbol(s, bits) => {
                                 if bits <= &self.pending_bits {
                                     self.pending_bits -= bits;
                                     self.pending_byte &= (1 << self.pending_bits) - 1;
-                                    return Some(s);
+                                    return Some(Ok(s));
                                 } else if self.pending_bits == 0 {
                                     return None;
                                 } else {
-                                    panic!("malformed data: decode incomplete (Symbol)");
+                                    return Some(Err(DecodeError::Incomplete));
                                 }
                             }
                         }
@@ -720,12 +891,12 @@ mod huffman {
                     let byte = (self.pending_byte >> (self.pending_bits - 8)) as usize;
                     match &map[byte] {
                         Decode::Void => {
-                            panic!("invalid decoding map");
+                            return Some(Err(DecodeError::InvalidMap));
                         }
                         Decode::Symbol(s, bits) => {
                             self.pending_bits -= bits;
                             self.pending_byte &= (1 << self.pending_bits) - 1;
-                            return Some(s);
+                            return Some(Ok(s));
                         }
                         Decode::Further(next_map) => {
                             self.pending_bits -= 8;
@@ -736,6 +907,48 @@ mod huffman {
                 }
             }
         }
+
+        impl<'a, T, I> Iterator for Decoder<'a, T, I>
+        where
+            I: Iterator<Item = (u8, usize)>,
+        {
+            type Item = &'a T;
+            fn next(&mut self) -> Option<&'a T> {
+                self.try_next()
+                    .map(|result| result.unwrap_or_else(|err| panic!("{err}")))
+            }
+        }
+
+        /// A decoder that reports malformed data as an error, and ends after the first error.
+        #[derive(Copy, Clone)]
+        pub struct TryDecoder<'a, T, I> {
+            decoder: Decoder<'a, T, I>,
+            failed: bool,
+        }
+
+        impl<'a, T, I> TryDecoder<'a, T, I> {
+            pub fn new(decoder: Decoder<'a, T, I>) -> Self {
+                Self {
+                    decoder,
+                    failed: false,
+                }
+            }
+        }
+
+        impl<'a, T, I> Iterator for TryDecoder<'a, T, I>
+        where
+            I: Iterator<Item = (u8, usize)>,
+        {
+            type Item = Result<&'a T, DecodeError>;
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.failed {
+                    return None;
+                }
+                let result = self.decoder.try_next();
+                self.failed = matches!(result, Some(Err(_)));
+                result
+            }
+        }
     }
 
     /// A tabled Huffman encoder, written as an iterator.
@@ -840,4 +1053,118 @@ mod tests {
         copy(&mut c3, [2, 3, 4]);
         copy(&mut c3, [2, 3, 4]);
     }
+
+    #[test]
+    fn test_reserve_regions() {
+        let mut raw = HuffmanContainer::<u8>::default();
+        for _ in 0..100 {
+            let _ = raw.push([1, 2, 3]);
+        }
+        let mut encoded = HuffmanContainer::merge_regions([&raw].into_iter());
+        for _ in 0..100 {
+            let _ = encoded.push([1, 2, 3]);
+        }
+        let encoded_bytes = |r: &HuffmanContainer<u8>| match &r.inner {
+            Ok((_huffman, bytes, _bits)) => (bytes.len(), bytes.capacity()),
+            Err(_) => unreachable!(),
+        };
+
+        // Raw regions are estimated from their symbol counts: codes of one and two bits for
+        // 100 occurrences each of three symbols take 500 bits, or 63 bytes.
+        let mut c = HuffmanContainer::merge_regions([&raw].into_iter());
+        c.reserve_regions([&raw, &encoded].into_iter());
+        let (_, capacity) = encoded_bytes(&c);
+        assert!(capacity >= 63 + encoded_bytes(&encoded).0);
+
+        let mut c = HuffmanContainer::<u8>::default();
+        c.reserve_regions([&raw, &encoded].into_iter());
+        match &c.inner {
+            Ok(_) => unreachable!(),
+            Err(raw) => assert!(raw.capacity() >= 600),
+        }
+    }
+
+    #[test]
+    fn test_item_storage_size() {
+        let mut raw = HuffmanContainer::<u8>::default();
+        for _ in 0..100 {
+            let _ = raw.push([b'e'; 10]);
+        }
+        let _ = raw.push(b"qxz");
+        let (frequent, rare) = ([b'e'; 64], [b'q'; 64]);
+        let index = raw.push(frequent);
+        assert_eq!(frequent.len(), raw.item_storage_size(index));
+
+        // The frequent symbol encodes in a single bit, and the rare one in more.
+        let mut encoded = HuffmanContainer::merge_regions([&raw].into_iter());
+        let frequent = encoded.push(frequent);
+        let rare = encoded.push(rare);
+        assert_eq!(8, encoded.item_storage_size(frequent));
+        assert!(encoded.item_storage_size(frequent) < encoded.item_storage_size(rare));
+    }
+
+    #[test]
+    fn test_reserve_items() {
+        let items = vec![vec![1, 2, 3]; 100];
+        let mut c = HuffmanContainer::<u8>::default();
+        c.reserve_items(items.iter());
+        match &c.inner {
+            Ok(_) => unreachable!(),
+            Err(raw) => assert!(raw.capacity() >= 300),
+        }
+        assert_eq!(Some(&100), c.stats.get(&2));
+        let _ = c.push(&items[0]);
+        assert_eq!(Some(&101), c.stats.get(&2));
+
+        // Codes of one and two bits for 100 occurrences each of three symbols take 500 bits.
+        let mut c = HuffmanContainer::merge_regions([&c].into_iter());
+        c.reserve_items(items.iter());
+        // The code does not contain `4`.
+        c.reserve_items([&[4; 10]].into_iter());
+        match &c.inner {
+            Ok((_huffman, bytes, _bits)) => assert!(bytes.capacity() >= 63),
+            Err(_) => unreachable!(),
+        }
+        let stats: Vec<_> = c.stats.into_iter().collect();
+        assert_eq!(vec![(1, 100), (2, 100), (3, 100), (4, 10)], stats);
+    }
+
+    /// Returns an encoded container in which `2` takes two bits.
+    fn two_bit_code() -> HuffmanContainer<u8> {
+        let mut c = HuffmanContainer::<u8>::default();
+        let _ = c.push(vec![1; 100]);
+        let _ = c.push([2, 3]);
+        HuffmanContainer::merge_regions([&c].into_iter())
+    }
+
+    #[test]
+    fn test_try_decode() {
+        let mut c = two_bit_code();
+        let index = c.push([1, 2, 1]);
+        assert!(c
+            .index(index)
+            .try_decode()
+            .unwrap()
+            .eq([Ok(&1), Ok(&2), Ok(&1)]));
+
+        // Ending the range within the code of `2` leaves its second bit undecoded.
+        let (start, _end) = c.push([1, 2]);
+        let truncated = c.index((start, start + 2));
+        assert!(truncated
+            .try_decode()
+            .unwrap()
+            .eq([Ok(&1), Err(DecodeError::Incomplete)]));
+
+        // Raw data are not decoded.
+        let raw = HuffmanContainer::<u8>::default();
+        assert!(raw.index((0, 0)).try_decode().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "malformed data: decode incomplete")]
+    fn test_decode_malformed() {
+        let mut c = two_bit_code();
+        let (start, _end) = c.push([2]);
+        let _ = c.index((start, start + 1)).into_owned();
+    }
 }
@@ -1,9 +1,12 @@
 //! Types to store indexes.
 
+use std::collections::TryReserveError;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 use crate::impls::storage::Storage;
+use crate::IndexOverflow;
 
 /// A container to store indices.
 pub trait IndexContainer<T>: Storage<T> {
@@ -18,6 +21,37 @@ pub trait IndexContainer<T>: Storage<T> {
     /// Accepts a newly pushed element.
     fn push(&mut self, item: T);
 
+    /// Removes the last element and returns it, or `None` if the container is empty.
+    ///
+    /// The default implementation rebuilds the container from its remaining elements, which
+    /// takes time linear in its length. Implementations should override it.
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        let len = self.len().checked_sub(1)?;
+        let item = self.index(len);
+        let remaining: Vec<_> = self.iter().take(len).collect();
+        self.clear();
+        self.extend(remaining);
+        Some(item)
+    }
+
+    /// Shortens the container to `len` elements, dropping the remaining elements. Has no
+    /// effect if `len` is greater or equal to the container's length.
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Accepts a newly pushed element, or returns an error without modifying the container if
+    /// the container cannot represent it.
+    #[inline]
+    fn try_push(&mut self, item: T) -> Result<(), IndexOverflow> {
+        self.push(item);
+        Ok(())
+    }
+
     /// Extend from iterator. Must be [`ExactSizeIterator`] to efficiently
     /// pre-allocate.
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I)
@@ -99,9 +133,14 @@ impl Stride {
     #[must_use]
     #[inline]
     pub fn index(&self, index: usize) -> usize {
+        debug_assert!(
+            index < self.len(),
+            "Stride: index {index} out of bounds for length {}",
+            self.len()
+        );
         match self {
             Stride::Empty => {
-                panic!("Empty Stride")
+                panic!("Stride: index {index} out of bounds for length 0")
             }
             Stride::Zero => 0,
             Stride::Striding(stride, _steps) => *stride * index,
@@ -134,6 +173,47 @@ impl Stride {
         matches!(self, Stride::Empty)
     }
 
+    /// Removes the last element and returns it, or `None` if empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<usize> {
+        let (last, remaining) = match *self {
+            Stride::Empty => return None,
+            Stride::Zero => (0, Stride::Empty),
+            Stride::Striding(stride, 2) => (stride, Stride::Zero),
+            Stride::Striding(stride, steps) => {
+                (stride * (steps - 1), Stride::Striding(stride, steps - 1))
+            }
+            Stride::Saturated(stride, steps, 1) => {
+                (stride * (steps - 1), Stride::Striding(stride, steps))
+            }
+            Stride::Saturated(stride, steps, reps) => (
+                stride * (steps - 1),
+                Stride::Saturated(stride, steps, reps - 1),
+            ),
+        };
+        *self = remaining;
+        Some(last)
+    }
+
+    /// Shortens to `len` elements. Has no effect if `len` is greater or equal to the length.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+        *self = match *self {
+            _ if len == 0 => Stride::Empty,
+            _ if len == 1 => Stride::Zero,
+            Stride::Saturated(stride, steps, _reps) if len > steps => {
+                Stride::Saturated(stride, steps, len - steps)
+            }
+            Stride::Striding(stride, _) | Stride::Saturated(stride, _, _) => {
+                Stride::Striding(stride, len)
+            }
+            Stride::Empty | Stride::Zero => unreachable!(),
+        };
+    }
+
     /// Removes all elements.
     #[inline]
     pub fn clear(&mut self) {
@@ -174,7 +254,7 @@ impl Iterator for StrideIter {
 }
 
 /// A list of unsigned integers that uses `u32` elements as long as they are small enough, and switches to `u64` once they are not.
-#[derive(Eq, PartialEq, Clone, Debug, Default)]
+#[derive(Eq, PartialEq, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IndexList<S, L> {
     /// Indexes that fit within a `u32`.
@@ -183,6 +263,20 @@ pub struct IndexList<S, L> {
     pub chonk: L,
 }
 
+impl<S: Clone, L: Clone> Clone for IndexList<S, L> {
+    fn clone(&self) -> Self {
+        Self {
+            smol: self.smol.clone(),
+            chonk: self.chonk.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.smol.clone_from(&source.smol);
+        self.chonk.clone_from(&source.chonk);
+    }
+}
+
 impl<S, L> IndexList<S, L>
 where
     S: IndexContainer<u32>,
@@ -205,14 +299,46 @@ where
     /// Panics if `usize` does not fit in `u64`.
     #[inline]
     pub fn push(&mut self, index: usize) {
+        if let Err(err) = self.try_push(index) {
+            panic!("IndexList: {err}");
+        }
+    }
+
+    /// Inserts the index, as a `u32` if that is still on the table, or returns an error if
+    /// it does not fit in a `u64`.
+    #[inline]
+    pub fn try_push(&mut self, index: usize) -> Result<(), IndexOverflow> {
         if self.chonk.is_empty() {
             if let Ok(smol) = index.try_into() {
                 self.smol.push(smol);
-            } else {
-                self.chonk.push(index.try_into().unwrap());
+                return Ok(());
             }
+        }
+        let chonk = index.try_into().map_err(|_| IndexOverflow {
+            max: usize::try_from(u64::MAX).unwrap_or(usize::MAX),
+        })?;
+        self.chonk.push(chonk);
+        Ok(())
+    }
+
+    /// Removes the last index and returns it, or `None` if the list is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<usize> {
+        match self.chonk.pop() {
+            Some(chonk) => Some(chonk.try_into().unwrap()),
+            None => self.smol.pop().map(|smol| smol.try_into().unwrap()),
+        }
+    }
+
+    /// Shortens the list to `len` indexes. Has no effect if `len` is greater or equal to the
+    /// length.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        if len <= self.smol.len() {
+            self.smol.truncate(len);
+            self.chonk.clear();
         } else {
-            self.chonk.push(index.try_into().unwrap());
+            self.chonk.truncate(len - self.smol.len());
         }
     }
 
@@ -224,6 +350,11 @@ where
     #[must_use]
     #[inline]
     pub fn index(&self, index: usize) -> usize {
+        debug_assert!(
+            index < self.len(),
+            "IndexList: index {index} out of bounds for length {}",
+            self.len()
+        );
         if index < self.smol.len() {
             self.smol.index(index).try_into().unwrap()
         } else {
@@ -251,6 +382,16 @@ where
         self.smol.reserve(additional);
     }
 
+    /// Reserve space for `additional` elements, or report that the allocation failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the list fails to allocate.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.smol.try_reserve(additional)
+    }
+
     /// Remove all elements.
     #[inline]
     pub fn clear(&mut self) {
@@ -280,6 +421,11 @@ where
         self.reserve(additional)
     }
 
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.try_reserve(additional)
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.clear()
@@ -318,6 +464,21 @@ where
         self.push(item)
     }
 
+    #[inline]
+    fn pop(&mut self) -> Option<usize> {
+        self.pop()
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
+    #[inline]
+    fn try_push(&mut self, item: usize) -> Result<(), IndexOverflow> {
+        self.try_push(item)
+    }
+
     #[inline]
     fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
     where
@@ -362,13 +523,27 @@ where
 
 /// An offset container implementation that first tries to recognize strides, and then spilles into
 /// a regular offset list.
-#[derive(Eq, PartialEq, Default, Debug, Clone)]
+#[derive(Eq, PartialEq, Default, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IndexOptimized<S = Vec<u32>, L = Vec<u64>> {
     strided: Stride,
     spilled: IndexList<S, L>,
 }
 
+impl<S: Clone, L: Clone> Clone for IndexOptimized<S, L> {
+    fn clone(&self) -> Self {
+        Self {
+            strided: self.strided,
+            spilled: self.spilled.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.strided = source.strided;
+        self.spilled.clone_from(&source.spilled);
+    }
+}
+
 impl<S, L> Storage<usize> for IndexOptimized<S, L>
 where
     S: IndexContainer<u32>,
@@ -403,6 +578,15 @@ where
         }
     }
 
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        if self.spilled.is_empty() {
+            Ok(())
+        } else {
+            self.spilled.try_reserve(additional)
+        }
+    }
+
     #[inline]
     fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
         self.spilled.heap_size(callback);
@@ -417,6 +601,11 @@ where
     type Iter<'a> = IndexOptimizedIter<S::Iter<'a>, L::Iter<'a>> where Self: 'a;
 
     fn index(&self, index: usize) -> usize {
+        debug_assert!(
+            index < self.len(),
+            "IndexOptimized: index {index} out of bounds for length {}",
+            self.len()
+        );
         if index < self.strided.len() {
             self.strided.index(index)
         } else {
@@ -435,6 +624,27 @@ where
         }
     }
 
+    fn pop(&mut self) -> Option<usize> {
+        self.spilled.pop().or_else(|| self.strided.pop())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len <= self.strided.len() {
+            self.strided.truncate(len);
+            self.spilled.clear();
+        } else {
+            self.spilled.truncate(len - self.strided.len());
+        }
+    }
+
+    fn try_push(&mut self, item: usize) -> Result<(), IndexOverflow> {
+        if self.spilled.is_empty() && self.strided.push(item) {
+            Ok(())
+        } else {
+            self.spilled.try_push(item)
+        }
+    }
+
     fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
     where
         I::IntoIter: ExactSizeIterator,
@@ -483,6 +693,16 @@ impl<T: Copy> IndexContainer<T> for Vec<T> {
         self.push(item);
     }
 
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        self.pop()
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.truncate(len);
+    }
+
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I)
     where
         I::IntoIter: ExactSizeIterator,
@@ -495,13 +715,186 @@ impl<T: Copy> IndexContainer<T> for Vec<T> {
     }
 }
 
+/// A list of indexes stored in a narrow unsigned integer type `T`, such as `u16`.
+///
+/// Uses less space than wider index containers, but can only represent indexes up to the
+/// maximum of `T`. Pushing a larger index panics, and [`IndexContainer::try_push`] reports it
+/// as an error instead.
+#[derive(Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexNarrow<T>(Vec<T>);
+
+impl<T: Clone> Clone for IndexNarrow<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.0.clone_from(&source.0);
+    }
+}
+
+impl<T> Default for IndexNarrow<T> {
+    fn default() -> Self {
+        Self(Vec::default())
+    }
+}
+
+/// An iterator over the elements of an [`IndexNarrow`].
+#[derive(Clone)]
+pub struct IndexNarrowIter<'a, T>(std::slice::Iter<'a, T>);
+
+macro_rules! implement_narrow {
+    ($index_type:ty) => {
+        impl Storage<usize> for IndexNarrow<$index_type> {
+            #[inline]
+            fn with_capacity(capacity: usize) -> Self {
+                Self(Vec::with_capacity(capacity))
+            }
+
+            #[inline]
+            fn reserve(&mut self, additional: usize) {
+                self.0.reserve(additional);
+            }
+
+            #[inline]
+            fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+                self.0.try_reserve(additional)
+            }
+
+            #[inline]
+            fn clear(&mut self) {
+                self.0.clear();
+            }
+
+            #[inline]
+            fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+                self.0.heap_size(callback);
+            }
+
+            #[inline]
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            #[inline]
+            fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+        }
+
+        impl IndexContainer<usize> for IndexNarrow<$index_type> {
+            type Iter<'a> = IndexNarrowIter<'a, $index_type>;
+
+            #[inline]
+            fn index(&self, index: usize) -> usize {
+                usize::from(self.0[index])
+            }
+
+            #[inline]
+            fn push(&mut self, item: usize) {
+                if let Err(err) = self.try_push(item) {
+                    panic!("IndexNarrow: {err}");
+                }
+            }
+
+            #[inline]
+            fn pop(&mut self) -> Option<usize> {
+                self.0.pop().map(usize::from)
+            }
+
+            #[inline]
+            fn truncate(&mut self, len: usize) {
+                self.0.truncate(len);
+            }
+
+            #[inline]
+            fn try_push(&mut self, item: usize) -> Result<(), IndexOverflow> {
+                let item = item.try_into().map_err(|_| IndexOverflow {
+                    max: usize::from(<$index_type>::MAX),
+                })?;
+                self.0.push(item);
+                Ok(())
+            }
+
+            #[inline]
+            fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
+            where
+                I::IntoIter: ExactSizeIterator,
+            {
+                for item in iter {
+                    self.push(item);
+                }
+            }
+
+            #[inline]
+            fn iter(&self) -> Self::Iter<'_> {
+                IndexNarrowIter(self.0.as_slice().iter())
+            }
+        }
+
+        impl Iterator for IndexNarrowIter<'_, $index_type> {
+            type Item = usize;
+
+            #[inline]
+            fn next(&mut self) -> Option<Self::Item> {
+                self.0.next().map(|x| usize::from(*x))
+            }
+
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.0.size_hint()
+            }
+        }
+    };
+}
+
+implement_narrow!(u8);
+implement_narrow!(u16);
+
 #[cfg(test)]
 mod tests {
-    use crate::impls::deduplicate::ConsecutiveIndexPairs;
-    use crate::{Push, Region, SliceRegion, StringRegion};
+    use crate::impls::deduplicate::{CollapseSequence, ConsecutiveIndexPairs};
+    use crate::impls::shared::SharedRegion;
+    use crate::{
+        FlatStack, MirrorRegion, OwnedRegion, Push, PushIter, Region, RegionLen, SliceRegion,
+        StringRegion, TryPush,
+    };
 
     use super::*;
 
+    #[test]
+    #[should_panic(expected = "Stride: index 0 out of bounds for length 0")]
+    fn test_stride_empty_out_of_bounds() {
+        let _ = Stride::Empty.index(0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Stride: index 3 out of bounds for length 3")]
+    fn test_stride_out_of_bounds() {
+        let _ = Stride::Striding(2, 3).index(3);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "IndexList: index 2 out of bounds for length 2")]
+    fn test_index_list_out_of_bounds() {
+        let mut list = IndexList::<Vec<u32>, Vec<u64>>::default();
+        list.push(1);
+        list.push(usize::MAX);
+        let _ = list.index(2);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "IndexOptimized: index 3 out of bounds for length 3")]
+    fn test_index_optimized_out_of_bounds() {
+        let mut container = <IndexOptimized>::default();
+        container.extend([0, 1, 5]);
+        let _ = container.index(3);
+    }
+
     #[test]
     fn test_index_optimized() {
         fn copy<R: Region + Push<T>, T>(r: &mut R, item: T) -> R::Index {
@@ -576,6 +969,43 @@ mod tests {
         assert!(!os.push(1));
     }
 
+    #[test]
+    fn test_index_optimized_pop() {
+        let items = [0, 3, 6, 6, 6, 7, 9999999999];
+        let mut oo = <IndexOptimized>::default();
+        oo.extend(items);
+        for len in (0..items.len()).rev() {
+            assert_eq!(Some(items[len]), oo.pop());
+            assert!(oo.iter().eq(items[..len].iter().copied()));
+        }
+        assert_eq!(None, oo.pop());
+
+        // Popping restores a state that accepts the same pushes.
+        oo.extend(items);
+        oo.pop();
+        oo.pop();
+        oo.push(7);
+        assert!(oo.spilled.len() == 1);
+        assert!(oo.iter().eq(items[..6].iter().copied()));
+    }
+
+    #[test]
+    fn test_index_optimized_truncate() {
+        let items = [0, 3, 6, 6, 6, 7, 9999999999];
+        for len in (0..=items.len() + 1).rev() {
+            let mut oo = <IndexOptimized>::default();
+            oo.extend(items);
+            oo.truncate(len);
+            let len = len.min(items.len());
+            assert!(oo.iter().eq(items[..len].iter().copied()));
+
+            // Truncating restores a state that accepts the same pushes.
+            oo.extend(items[len..].iter().copied());
+            assert!(oo.iter().eq(items));
+            assert_eq!(1, oo.spilled.chonk.len());
+        }
+    }
+
     #[test]
     fn test_chonk() {
         let mut ol = <IndexList<Vec<_>, Vec<_>>>::default();
@@ -589,4 +1019,169 @@ mod tests {
         let os = Stride::default();
         let _ = os.index(0);
     }
+
+    #[test]
+    fn test_narrow_try_push() {
+        let mut c = IndexNarrow::<u8>::default();
+        assert_eq!(Ok(()), c.try_push(255));
+        assert_eq!(Err(IndexOverflow { max: 255 }), c.try_push(256));
+        assert_eq!(1, c.len());
+        assert!(c.iter().eq([255]));
+    }
+
+    #[test]
+    #[should_panic(expected = "IndexNarrow: index exceeds the maximum of 65535")]
+    fn test_narrow_push_overflow() {
+        let mut c = IndexNarrow::<u16>::default();
+        c.push(1 << 16);
+    }
+
+    #[test]
+    fn test_slice_try_push_overflow() {
+        let mut r =
+            SliceRegion::<ConsecutiveIndexPairs<OwnedRegion<u8>>, IndexNarrow<u16>>::default();
+        let chunk = vec![&[1_u8][..]; 1000];
+
+        // The inner region hands out indexes 0 to 64999, which fit in `u16`.
+        let indices: Vec<_> = (0..65).map(|_| r.try_push(&chunk[..]).unwrap()).collect();
+        // The next chunk contains index 65536.
+        assert_eq!(Err(IndexOverflow { max: 65535 }), r.try_push(&chunk[..]));
+
+        for index in indices {
+            assert_eq!(1000, r.index(index).len());
+            assert!(r.index(index).iter().all(|item| item == [1]));
+        }
+    }
+
+    /// An index container that relies on the default implementations of `pop` and `truncate`.
+    #[derive(Default)]
+    struct Minimal(Vec<usize>);
+
+    impl Storage<usize> for Minimal {
+        fn with_capacity(capacity: usize) -> Self {
+            Self(Vec::with_capacity(capacity))
+        }
+
+        fn reserve(&mut self, additional: usize) {
+            self.0.reserve(additional);
+        }
+
+        fn clear(&mut self) {
+            self.0.clear();
+        }
+
+        fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+            Storage::heap_size(&self.0, callback);
+        }
+
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    impl IndexContainer<usize> for Minimal {
+        type Iter<'a> = std::iter::Copied<std::slice::Iter<'a, usize>>;
+
+        fn index(&self, index: usize) -> usize {
+            self.0[index]
+        }
+
+        fn push(&mut self, item: usize) {
+            self.0.push(item);
+        }
+
+        fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I)
+        where
+            I::IntoIter: ExactSizeIterator,
+        {
+            IndexContainer::extend(&mut self.0, iter);
+        }
+
+        fn iter(&self) -> Self::Iter<'_> {
+            self.0.as_slice().iter().copied()
+        }
+    }
+
+    #[test]
+    fn test_default_pop() {
+        let mut c = Minimal::default();
+        assert_eq!(None, c.pop());
+        IndexContainer::extend(&mut c, [1, 2, 3, 4]);
+        assert_eq!(Some(4), c.pop());
+        assert!(IndexContainer::iter(&c).eq([1, 2, 3]));
+        c.truncate(1);
+        assert!(IndexContainer::iter(&c).eq([1]));
+
+        let mut c = FlatStack::<MirrorRegion<usize>, Minimal>::default();
+        c.copy(5);
+        c.copy(6);
+        assert_eq!(Some(6), c.pop());
+        assert_eq!(1, c.len());
+    }
+
+    #[test]
+    fn test_slice_try_push_rollback() {
+        let mut r = SliceRegion::<MirrorRegion<usize>, IndexNarrow<u8>>::default();
+        let first = r.try_push([1, 2].as_slice()).unwrap();
+
+        // The first two indexes fit in `u8`, the third does not.
+        assert_eq!(
+            Err(IndexOverflow { max: 255 }),
+            r.try_push([3, 4, 256].as_slice())
+        );
+        assert_eq!(2, r.len());
+
+        // The failed push left no indexes behind.
+        let second = r.try_push([5].as_slice()).unwrap();
+        assert_eq!((2, 3), second);
+        assert!(r.index(first).iter().eq([1, 2]));
+        assert!(r.index(second).iter().eq([5]));
+    }
+
+    #[test]
+    fn test_try_reserve() {
+        // Index containers report overflowing reservations.
+        let mut list = IndexList::<Vec<u32>, Vec<u64>>::default();
+        assert!(Storage::try_reserve(&mut list, usize::MAX).is_err());
+        assert!(Storage::try_reserve(&mut list, 10).is_ok());
+        let mut narrow = IndexNarrow::<u8>::default();
+        assert!(narrow.try_reserve(usize::MAX).is_err());
+
+        let mut c = FlatStack::<
+            CollapseSequence<ConsecutiveIndexPairs<StringRegion>>,
+            IndexOptimized,
+        >::default();
+        // Non-consecutive indexes spill from the stride into the list.
+        for item in ["a", "a", "b", "a"] {
+            c.copy(item);
+        }
+        assert!(c.try_reserve(usize::MAX).is_err());
+        assert!(c.try_reserve(1).is_ok());
+        assert!(c.iter().eq(["a", "a", "b", "a"]));
+    }
+
+    #[test]
+    fn test_try_reserve_regions_nested() {
+        type Nested =
+            SliceRegion<CollapseSequence<ConsecutiveIndexPairs<StringRegion>>, IndexOptimized>;
+        // A stride represents many offsets without storing them.
+        let huge = IndexOptimized {
+            strided: Stride::Striding(1, usize::MAX / 4),
+            spilled: IndexList::default(),
+        };
+        let source = SharedRegion::new(Nested::with_index_container(huge));
+
+        let mut r = Nested::default();
+        // Non-consecutive indexes spill from the stride into the list.
+        let index = r.push(PushIter(["a", "a", "b", "a"]));
+        let mut r = SharedRegion::new(r);
+        assert!(r.try_reserve_regions(std::iter::once(&source)).is_err());
+        let copy = SharedRegion::new(r.get().clone());
+        assert!(r.try_reserve_regions(std::iter::once(&copy)).is_ok());
+        assert!(r.index(index).iter().eq(["a", "a", "b", "a"]));
+    }
 }
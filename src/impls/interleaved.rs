@@ -0,0 +1,252 @@
+//! A region that stores rows interleaved and reads them as columns.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// A region that stores rows of fields interleaved in a single buffer, in array-of-structs
+/// layout, but offers columnar access to individual fields.
+///
+/// Pushing a row writes all its fields next to each other, which is cache-friendly when data
+/// arrives row by row. Reading a row returns a reference into the buffer. [`column`] returns an
+/// iterator over one field of all rows, which strides through the buffer by the size of a row,
+/// without transposing the data.
+///
+/// In contrast, [`ColumnsRegion`](crate::ColumnsRegion) stores each column in its own region,
+/// which favors columnar reads over row-wise writes.
+///
+/// [`column`]: InterleavedRegion::column
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::interleaved::InterleavedRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <InterleavedRegion<(u32, u64)>>::default();
+///
+/// let index = r.push((1, 10));
+/// let _ = r.push((2, 20));
+///
+/// assert_eq!(&(1, 10), r.index(index));
+/// assert!(r.column(|row| row.0).eq([1, 2]));
+/// assert!(r.column(|row| row.1).eq([10, 20]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InterleavedRegion<T> {
+    /// The rows, with their fields interleaved.
+    rows: Vec<T>,
+}
+
+impl<T> Default for InterleavedRegion<T> {
+    fn default() -> Self {
+        Self {
+            rows: Vec::default(),
+        }
+    }
+}
+
+impl<T: Clone> Clone for InterleavedRegion<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rows: self.rows.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.rows.clone_from(&source.rows);
+    }
+}
+
+impl<T> InterleavedRegion<T> {
+    /// Returns the number of rows in the region.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns `true` if the region contains no rows.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns an iterator over the field selected by `field` of all rows, in the order the
+    /// rows were pushed.
+    #[inline]
+    pub fn column<'a, U, F>(&'a self, field: F) -> ColumnIter<'a, T, F>
+    where
+        F: FnMut(&'a T) -> U,
+    {
+        ColumnIter {
+            rows: self.rows.iter(),
+            field,
+        }
+    }
+}
+
+/// An iterator over a field of the rows of an [`InterleavedRegion`].
+pub struct ColumnIter<'a, T, F> {
+    /// The remaining rows.
+    rows: std::slice::Iter<'a, T>,
+    /// Projection of a row to the field.
+    field: F,
+}
+
+impl<'a, T, U, F> Iterator for ColumnIter<'a, T, F>
+where
+    F: FnMut(&'a T) -> U,
+{
+    type Item = U;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(&mut self.field)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl<'a, T, U, F> ExactSizeIterator for ColumnIter<'a, T, F> where F: FnMut(&'a T) -> U {}
+
+impl<T: Clone> Region for InterleavedRegion<T> {
+    type Owned = T;
+    type ReadItem<'a> = &'a T where Self: 'a;
+    type Index = usize;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            rows: Vec::with_capacity(regions.map(|r| r.rows.len()).sum()),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        &self.rows[index]
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.rows.reserve(regions.map(|r| r.rows.len()).sum());
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.rows.try_reserve(regions.map(|r| r.rows.len()).sum())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_t = std::mem::size_of::<T>();
+        callback(
+            self.rows.len() * size_of_t,
+            self.rows.capacity() * size_of_t,
+        );
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<T: Clone> Push<T> for InterleavedRegion<T> {
+    #[inline]
+    fn push(&mut self, item: T) -> usize {
+        self.rows.push(item);
+        self.rows.len() - 1
+    }
+}
+
+impl<T: Clone> Push<&T> for InterleavedRegion<T> {
+    #[inline]
+    fn push(&mut self, item: &T) -> usize {
+        self.push(item.clone())
+    }
+}
+
+impl<T: Clone> ReserveItems<T> for InterleavedRegion<T> {
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.rows.reserve(items.count());
+    }
+}
+
+impl<'a, T: Clone + 'a> ReserveItems<&'a T> for InterleavedRegion<T> {
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a T> + Clone,
+    {
+        self.rows.reserve(items.count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, IntoOwned};
+
+    use super::*;
+
+    #[test]
+    fn test_rows_and_columns() {
+        let rows: Vec<(u32, u64)> = (0..10).map(|i| (i, u64::from(i) << 40)).collect();
+
+        let mut r = <InterleavedRegion<(u32, u64)>>::default();
+        r.reserve_items(rows.iter());
+        let indices: Vec<_> = rows.iter().map(|row| r.push(row)).collect();
+        assert_eq!(rows.len(), r.len());
+
+        for (row, index) in rows.iter().zip(indices) {
+            assert_eq!(row, r.index(index));
+            assert_eq!(*row, r.index(index).into_owned());
+        }
+
+        assert_eq!(rows.len(), r.column(|row| row.0).len());
+        assert!(r.column(|row| row.0).eq(0..10));
+        assert!(r.column(|row| &row.1).eq(rows.iter().map(|row| &row.1)));
+        assert_eq!(45, r.column(|row| row.0).sum::<u32>());
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let mut c = FlatStack::<InterleavedRegion<(u32, u64)>>::default();
+        c.copy((1, 2));
+        c.copy(&(3, 4));
+        assert!(c.iter().eq([&(1, 2), &(3, 4)]));
+
+        c.clear();
+        assert!(c.iter().next().is_none());
+    }
+}
@@ -0,0 +1,417 @@
+//! A region that stores slices of strings, interning strings across slices.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::deduplicate::Dedup;
+use crate::impls::index::IndexContainer;
+use crate::impls::slice::{ReadSlice, ReadSliceIter};
+use crate::{
+    IntoOwned, MirrorRegion, Push, PushIter, Region, ReserveItems, SliceRegion, StringRegion,
+};
+
+/// The index of an interned string.
+type StringIndex = <StringRegion as Region>::Index;
+
+/// A region to hold slices of strings, which stores each distinct string once.
+///
+/// Interns strings in a [`Dedup<StringRegion>`] shared by all slices, and stores each slice as
+/// the indexes of its strings. This is effective when the same strings recur across many
+/// slices, for example tags, at the cost of a hash map entry per distinct string. The read item
+/// is a [`ReadInterned`], which presents the strings of a slice as `&str`.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::interned::InternedSliceRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <InternedSliceRegion>::default();
+///
+/// let first = r.push(&["red", "green"]);
+/// let second = r.push(&["green", "blue"]);
+/// assert!(r.index(first).iter().eq(["red", "green"]));
+/// assert_eq!("green", r.index(second).get(0));
+/// assert_eq!(3, r.distinct());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "O: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct InternedSliceRegion<O = Vec<StringIndex>> {
+    /// Distinct strings of all slices.
+    strings: Dedup<StringRegion>,
+    /// Indexes of the strings of each slice.
+    slices: SliceRegion<MirrorRegion<StringIndex>, O>,
+}
+
+impl<O: Clone> Clone for InternedSliceRegion<O> {
+    fn clone(&self) -> Self {
+        Self {
+            strings: self.strings.clone(),
+            slices: self.slices.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.strings.clone_from(&source.strings);
+        self.slices.clone_from(&source.slices);
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> Default for InternedSliceRegion<O> {
+    fn default() -> Self {
+        Self {
+            strings: Dedup::default(),
+            slices: SliceRegion::default(),
+        }
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> InternedSliceRegion<O> {
+    /// Returns the number of distinct strings stored in the region.
+    #[inline]
+    #[must_use]
+    pub fn distinct(&self) -> usize {
+        self.strings.distinct()
+    }
+
+    /// Interns the strings of `items` and stores their indexes as a slice.
+    #[inline]
+    fn push_strs<'a>(&mut self, items: impl Iterator<Item = &'a str>) -> (usize, usize) {
+        let strings = &mut self.strings;
+        self.slices
+            .push(PushIter(items.map(|item| strings.push(item))))
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> Region for InternedSliceRegion<O> {
+    type Owned = Vec<String>;
+    type ReadItem<'a> = ReadInterned<'a, O> where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            strings: Dedup::merge_regions(regions.clone().map(|r| &r.strings)),
+            slices: SliceRegion::merge_regions(regions.map(|r| &r.slices)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadInterned(Ok((&self.strings, self.slices.index(index))))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.strings
+            .reserve_regions(regions.clone().map(|r| &r.strings));
+        self.slices.reserve_regions(regions.map(|r| &r.slices));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.strings
+            .try_reserve_regions(regions.clone().map(|r| &r.strings))?;
+        self.slices.try_reserve_regions(regions.map(|r| &r.slices))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.strings.clear();
+        self.slices.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.strings.heap_size(&mut callback);
+        self.slices.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A slice of strings read from an [`InternedSliceRegion`].
+#[allow(clippy::type_complexity)]
+pub struct ReadInterned<'a, O: IndexContainer<StringIndex> = Vec<StringIndex>>(
+    Result<
+        (
+            &'a Dedup<StringRegion>,
+            ReadSlice<'a, MirrorRegion<StringIndex>, O>,
+        ),
+        &'a [String],
+    >,
+);
+
+impl<'a, O: IndexContainer<StringIndex>> ReadInterned<'a, O> {
+    /// Returns the string at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> &'a str {
+        match &self.0 {
+            Ok((strings, slice)) => strings.index(slice.get(index)),
+            Err(owned) => &owned[index],
+        }
+    }
+
+    /// The number of strings in this slice.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Ok((_, slice)) => slice.len(),
+            Err(owned) => owned.len(),
+        }
+    }
+
+    /// Returns `true` if the slice is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Ok((_, slice)) => slice.is_empty(),
+            Err(owned) => owned.is_empty(),
+        }
+    }
+
+    /// Returns an iterator over the strings.
+    #[must_use]
+    pub fn iter(&self) -> ReadInternedIter<'a, O> {
+        self.into_iter()
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> Clone for ReadInterned<'_, O> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> Copy for ReadInterned<'_, O> {}
+
+impl<O: IndexContainer<StringIndex>> PartialEq for ReadInterned<'_, O> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(other.iter())
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> Eq for ReadInterned<'_, O> {}
+
+impl<O: IndexContainer<StringIndex>> Debug for ReadInterned<'_, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, O: IndexContainer<StringIndex>> IntoOwned<'a> for ReadInterned<'a, O> {
+    type Owned = Vec<String>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().map(str::to_string).collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        let r = std::cmp::min(self.len(), other.len());
+        for (item, target) in self.iter().zip(other.iter_mut()) {
+            item.clone_into(target);
+        }
+        other.truncate(r);
+        other.extend(self.iter().skip(r).map(str::to_string));
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a, O: IndexContainer<StringIndex>> IntoIterator for ReadInterned<'a, O> {
+    type Item = &'a str;
+    type IntoIter = ReadInternedIter<'a, O>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Ok((strings, slice)) => ReadInternedIter(Ok((strings, slice.into_iter()))),
+            Err(owned) => ReadInternedIter(Err(owned.iter())),
+        }
+    }
+}
+
+/// An iterator over the strings of a [`ReadInterned`].
+#[allow(clippy::type_complexity)]
+pub struct ReadInternedIter<'a, O: IndexContainer<StringIndex>>(
+    Result<
+        (
+            &'a Dedup<StringRegion>,
+            ReadSliceIter<'a, MirrorRegion<StringIndex>, O>,
+        ),
+        std::slice::Iter<'a, String>,
+    >,
+);
+
+impl<O: IndexContainer<StringIndex>> Clone for ReadInternedIter<'_, O> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, O: IndexContainer<StringIndex>> Iterator for ReadInternedIter<'a, O> {
+    type Item = &'a str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Ok((strings, iter)) => iter.next().map(|index| strings.index(index)),
+            Err(iter) => iter.next().map(String::as_str),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Ok((_, iter)) => iter.size_hint(),
+            Err(iter) => iter.size_hint(),
+        }
+    }
+}
+
+impl<O: IndexContainer<StringIndex>> ExactSizeIterator for ReadInternedIter<'_, O> {}
+
+impl<O, T> Push<&[T]> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    T: AsRef<str>,
+{
+    #[inline]
+    fn push(&mut self, item: &[T]) -> (usize, usize) {
+        self.push_strs(item.iter().map(AsRef::as_ref))
+    }
+}
+
+impl<O, T, const N: usize> Push<&[T; N]> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    T: AsRef<str>,
+{
+    #[inline]
+    fn push(&mut self, item: &[T; N]) -> (usize, usize) {
+        self.push(item.as_slice())
+    }
+}
+
+impl<O, T> Push<&Vec<T>> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    T: AsRef<str>,
+{
+    #[inline]
+    fn push(&mut self, item: &Vec<T>) -> (usize, usize) {
+        self.push(item.as_slice())
+    }
+}
+
+impl<O, T> Push<Vec<T>> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    T: AsRef<str>,
+{
+    #[inline]
+    fn push(&mut self, item: Vec<T>) -> (usize, usize) {
+        self.push(item.as_slice())
+    }
+}
+
+impl<O, O2> Push<ReadInterned<'_, O2>> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    O2: IndexContainer<StringIndex>,
+{
+    #[inline]
+    fn push(&mut self, item: ReadInterned<'_, O2>) -> (usize, usize) {
+        self.push_strs(item.iter())
+    }
+}
+
+impl<'a, O, T> ReserveItems<&'a [T]> for InternedSliceRegion<O>
+where
+    O: IndexContainer<StringIndex>,
+    T: AsRef<str> + 'a,
+{
+    /// Reserves space for all strings, without accounting for duplicates.
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a [T]> + Clone,
+    {
+        self.strings
+            .reserve_items(items.flat_map(|item| item.iter().map(AsRef::as_ref)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_interning() {
+        let rows = [["a", "b"], ["b", "c"], ["a", "c"]];
+        let mut c = FlatStack::<InternedSliceRegion>::default();
+        c.reserve_items(rows.iter().map(|row| row.as_slice()));
+        for row in &rows {
+            c.copy(row);
+        }
+
+        assert_eq!(3, c.region.distinct());
+        for (row, read) in rows.iter().zip(c.iter()) {
+            assert!(row.iter().copied().eq(read));
+            assert_eq!(row.to_vec(), read.into_owned());
+        }
+        assert_eq!("c", c.get(2).get(1));
+    }
+
+    #[test]
+    fn test_owned() {
+        let mut c = FlatStack::<InternedSliceRegion>::default();
+        let owned = vec!["x".to_string(), String::new(), "x".to_string()];
+        c.copy(&owned);
+        c.copy(c.get(0).into_owned());
+        assert_eq!(2, c.region.distinct());
+        assert_eq!(c.get(0), IntoOwned::borrow_as(&owned));
+        assert_eq!(c.get(0), c.get(1));
+        assert_eq!(r#"["x", "", "x"]"#, format!("{:?}", c.get(1)));
+
+        let mut target = vec!["long".to_string(); 5];
+        c.get(0).clone_onto(&mut target);
+        assert_eq!(owned, target);
+    }
+}
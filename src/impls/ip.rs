@@ -0,0 +1,192 @@
+//! A region that stores IP addresses.
+
+use std::collections::TryReserveError;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, MirrorRegion, Push, Region, RegionPreference, ReserveItems};
+
+impl RegionPreference for IpAddr {
+    type Owned = Self;
+    type Region = IpAddrRegion;
+}
+
+impl<'a> IntoOwned<'a> for IpAddr {
+    type Owned = IpAddr;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self;
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        *owned
+    }
+}
+
+/// A region for IPv4 and IPv6 addresses.
+///
+/// Dispatches on the address family into a mirror region per family, like
+/// [`ResultRegion`](crate::ResultRegion) does for its variants. As the inner regions are
+/// mirror regions, the index is the address itself.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::IpAddr;
+/// use flatcontainer::impls::ip::IpAddrRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = IpAddrRegion::default();
+///
+/// let v4: IpAddr = "10.0.0.1".parse().unwrap();
+/// let v6: IpAddr = "2001:db8::1".parse().unwrap();
+/// let (v4_index, v6_index) = (r.push(v4), r.push(v6));
+/// assert_eq!(v4, r.index(v4_index));
+/// assert_eq!(v6, r.index(v6_index));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IpAddrRegion {
+    v4: MirrorRegion<Ipv4Addr>,
+    v6: MirrorRegion<Ipv6Addr>,
+}
+
+impl Region for IpAddrRegion {
+    type Owned = IpAddr;
+    type ReadItem<'a> = IpAddr;
+    type Index = IpAddr;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            v4: MirrorRegion::merge_regions(regions.clone().map(|r| &r.v4)),
+            v6: MirrorRegion::merge_regions(regions.map(|r| &r.v6)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        match index {
+            IpAddr::V4(index) => IpAddr::V4(self.v4.index(index)),
+            IpAddr::V6(index) => IpAddr::V6(self.v6.index(index)),
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.v4.reserve_regions(regions.clone().map(|r| &r.v4));
+        self.v6.reserve_regions(regions.map(|r| &r.v6));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.v4
+            .try_reserve_regions(regions.clone().map(|r| &r.v4))?;
+        self.v6.try_reserve_regions(regions.map(|r| &r.v6))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.v4.clear();
+        self.v6.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.v4.heap_size(&mut callback);
+        self.v6.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<IpAddr> for IpAddrRegion {
+    #[inline]
+    fn push(&mut self, item: IpAddr) -> IpAddr {
+        match item {
+            IpAddr::V4(address) => IpAddr::V4(self.v4.push(address)),
+            IpAddr::V6(address) => IpAddr::V6(self.v6.push(address)),
+        }
+    }
+}
+
+impl Push<&IpAddr> for IpAddrRegion {
+    #[inline]
+    fn push(&mut self, item: &IpAddr) -> IpAddr {
+        self.push(*item)
+    }
+}
+
+impl<T> ReserveItems<T> for IpAddrRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_mixed() {
+        let addrs: Vec<IpAddr> = [
+            "127.0.0.1",
+            "::1",
+            "::ffff:192.168.1.1",
+            "0.0.0.0",
+            "ff02::1",
+        ]
+        .into_iter()
+        .map(|addr| addr.parse().unwrap())
+        .collect();
+
+        let mut c = FlatStack::default_impl::<IpAddr>();
+        c.extend(&addrs);
+        assert!(c.iter().eq(addrs.iter().copied()));
+        assert!(c.get(2).is_ipv6());
+        assert_eq!(addrs[3], c.get(3).into_owned());
+
+        let merged = FlatStack::merge_capacity(std::iter::once(&c));
+        let mut size = 0;
+        merged.heap_size(|siz, _| size += siz);
+        assert_eq!(0, size);
+    }
+
+    #[test]
+    fn test_families() {
+        let mut c = FlatStack::default_impl::<(Ipv4Addr, Ipv6Addr)>();
+        c.copy((Ipv4Addr::LOCALHOST, Ipv6Addr::LOCALHOST));
+        assert_eq!((Ipv4Addr::LOCALHOST, Ipv6Addr::LOCALHOST), c.get(0));
+    }
+}
@@ -0,0 +1,276 @@
+//! A region that stores length-delimited messages.
+
+use std::collections::TryReserveError;
+use std::io::{ErrorKind, Read};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{OwnedRegion, Push, Region, ReserveItems};
+
+/// The maximum number of bytes in a varint encoding a `u64`.
+const MAX_VARINT_LEN: usize = 10;
+
+/// A region to hold messages read from a stream of length-delimited messages.
+///
+/// Each message in the stream is prefixed by its length as a varint, as written by protobuf's
+/// `writeDelimitedTo`. The region stores the bytes of each message, without the prefix, in an
+/// [`OwnedRegion`], and reads them as `&[u8]`.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use flatcontainer::impls::length_delimited::LengthDelimitedRegion;
+/// use flatcontainer::Region;
+/// let mut r = LengthDelimitedRegion::default();
+///
+/// let mut stream = Cursor::new(b"\x03abc");
+/// let index = r.push_delimited(&mut stream).unwrap().unwrap();
+/// assert_eq!(b"abc", r.index(index));
+/// assert_eq!(None, r.push_delimited(&mut stream).unwrap());
+/// ```
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LengthDelimitedRegion {
+    /// Bytes of all messages.
+    inner: OwnedRegion<u8>,
+    /// Buffer for the message being read.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    buffer: Vec<u8>,
+}
+
+impl Clone for LengthDelimitedRegion {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            buffer: Vec::default(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl LengthDelimitedRegion {
+    /// Reads the next length-delimited message from `reader` and stores it, or returns `None`
+    /// if `reader` is at its end.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, if the stream ends within a message or its length,
+    /// or if the length is not a valid varint.
+    pub fn push_delimited<R: Read>(
+        &mut self,
+        reader: &mut R,
+    ) -> std::io::Result<Option<<Self as Region>::Index>> {
+        let Some(len) = Self::read_varint(reader)? else {
+            return Ok(None);
+        };
+        let len = usize::try_from(len).map_err(|_| {
+            std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!("LengthDelimitedRegion: message length {len} exceeds usize"),
+            )
+        })?;
+
+        // Read through `take` to not allocate the declared length of a truncated message.
+        self.buffer.clear();
+        reader.take(len as u64).read_to_end(&mut self.buffer)?;
+        if self.buffer.len() < len {
+            return Err(std::io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!(
+                    "LengthDelimitedRegion: stream ends after {} of {len} message bytes",
+                    self.buffer.len()
+                ),
+            ));
+        }
+        Ok(Some(self.inner.push(self.buffer.as_slice())))
+    }
+
+    /// Reads a varint, or returns `None` if `reader` is at its end.
+    fn read_varint<R: Read>(reader: &mut R) -> std::io::Result<Option<u64>> {
+        let mut value = 0;
+        for position in 0..MAX_VARINT_LEN {
+            let mut byte = [0];
+            if let Err(err) = reader.read_exact(&mut byte) {
+                return match err.kind() {
+                    ErrorKind::UnexpectedEof if position == 0 => Ok(None),
+                    _ => Err(err),
+                };
+            }
+            value |= u64::from(byte[0] & 0x7f) << (7 * position);
+            if byte[0] & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+        }
+        Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "LengthDelimitedRegion: varint exceeds 10 bytes",
+        ))
+    }
+}
+
+impl Region for LengthDelimitedRegion {
+    type Owned = Vec<u8>;
+    type ReadItem<'a> = &'a [u8] where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: OwnedRegion::merge_regions(regions.map(|r| &r.inner)),
+            buffer: Vec::default(),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        Some(self.inner.index(index))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        callback(self.buffer.len(), self.buffer.capacity());
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<&[u8]> for LengthDelimitedRegion {
+    /// Pushes the bytes of a message, without a length prefix.
+    #[inline]
+    fn push(&mut self, item: &[u8]) -> Self::Index {
+        self.inner.push(item)
+    }
+}
+
+impl Push<&Vec<u8>> for LengthDelimitedRegion {
+    #[inline]
+    fn push(&mut self, item: &Vec<u8>) -> Self::Index {
+        self.inner.push(item.as_slice())
+    }
+}
+
+impl Push<Vec<u8>> for LengthDelimitedRegion {
+    #[inline]
+    fn push(&mut self, item: Vec<u8>) -> Self::Index {
+        self.inner.push(item.as_slice())
+    }
+}
+
+impl<'a> ReserveItems<&'a [u8]> for LengthDelimitedRegion {
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a [u8]> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::FlatStack;
+
+    use super::*;
+
+    /// Encodes messages with varint length prefixes.
+    fn encode(messages: &[&[u8]]) -> Vec<u8> {
+        let mut stream = Vec::new();
+        for message in messages {
+            let mut len = message.len();
+            while len >= 0x80 {
+                stream.push((len as u8) | 0x80);
+                len >>= 7;
+            }
+            stream.push(len as u8);
+            stream.extend_from_slice(message);
+        }
+        stream
+    }
+
+    #[test]
+    fn test_messages() {
+        let long = vec![7; 300];
+        let stream = encode(&[b"hello", &long, b""]);
+        let mut reader = Cursor::new(stream);
+
+        let mut r = LengthDelimitedRegion::default();
+        let mut indices = Vec::new();
+        while let Some(index) = r.push_delimited(&mut reader).unwrap() {
+            indices.push(index);
+        }
+        assert_eq!(3, indices.len());
+        assert_eq!(b"hello", r.index(indices[0]));
+        assert_eq!(long, r.index(indices[1]));
+        assert_eq!(b"", r.index(indices[2]));
+        assert_eq!(None, r.push_delimited(&mut reader).unwrap());
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut r = LengthDelimitedRegion::default();
+
+        let mut stream = encode(&[b"hello"]);
+        stream.pop();
+        let err = r.push_delimited(&mut Cursor::new(stream)).unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+
+        let err = r.push_delimited(&mut Cursor::new([0x80])).unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+
+        let err = r.push_delimited(&mut Cursor::new([0xff; 11])).unwrap_err();
+        assert_eq!(ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let mut c = FlatStack::<LengthDelimitedRegion>::default();
+        c.copy(b"abc".as_slice());
+        c.copy(vec![1, 2]);
+        assert!(c.iter().eq([b"abc".as_slice(), &[1, 2]]));
+    }
+}
@@ -0,0 +1,503 @@
+//! A region that stores maps.
+
+use std::cmp::Ordering;
+use std::collections::TryReserveError;
+use std::collections::{btree_map, BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
+use std::hash::BuildHasher;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::slice::{ReadSlice, ReadSliceIter};
+use crate::impls::tuple::TupleABRegion;
+use crate::{IntoOwned, Push, PushIter, Region, RegionPreference, SliceRegion};
+
+/// The default index container of a [`MapRegion`].
+type Entries<K, V> = Vec<<TupleABRegion<K, V> as Region>::Index>;
+
+impl<K: RegionPreference, V: RegionPreference> RegionPreference for BTreeMap<K, V>
+where
+    K::Owned: Ord,
+{
+    type Owned = BTreeMap<K::Owned, V::Owned>;
+    type Region = MapRegion<K::Region, V::Region>;
+}
+
+/// A region to hold maps from keys to values.
+///
+/// Stores the entries of each map as a slice of key-value pairs in a [`SliceRegion`], sorted
+/// by key and with unique keys. Keys and values live in regions of their own, and the slice
+/// region's index container marks where each map's entries start and end. The read item is a
+/// [`ReadMap`], which looks up keys in logarithmic time and iterates entries in key order.
+///
+/// The region accepts [`BTreeMap`]s, [`HashMap`]s, and vectors of entries. Entries of hash maps
+/// and vectors need to be sorted before they are pushed, which requires a temporary
+/// allocation. Unlike [`AssocRegion`](crate::impls::assoc::AssocRegion), which retains
+/// duplicate keys, the owned representation is a [`BTreeMap`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use flatcontainer::impls::map::MapRegion;
+/// use flatcontainer::{MirrorRegion, Push, Region, StringRegion};
+/// let mut r = <MapRegion<MirrorRegion<u32>, StringRegion>>::default();
+///
+/// let map = BTreeMap::from([(2, "b"), (1, "a")]);
+/// let index = r.push(&map);
+///
+/// assert_eq!(Some("a"), r.index(index).get(&1));
+/// assert_eq!(None, r.index(index).get(&3));
+/// assert!(r.index(index).iter().eq([(1, "a"), (2, "b")]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            K: Serialize + for<'a> Deserialize<'a>,
+            V: Serialize + for<'a> Deserialize<'a>,
+            O: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct MapRegion<K: Region, V: Region, O = Entries<K, V>> {
+    /// Entries of each map, sorted by key.
+    inner: SliceRegion<TupleABRegion<K, V>, O>,
+}
+
+impl<K, V, O> Clone for MapRegion<K, V, O>
+where
+    K: Region + Clone,
+    V: Region + Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<K, V, O> Region for MapRegion<K, V, O>
+where
+    K: Region,
+    K::Owned: Ord,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Owned = BTreeMap<K::Owned, V::Owned>;
+    type ReadItem<'a> = ReadMap<'a, K, V, O> where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: SliceRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadMap(Ok(self.inner.index(index)))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<K, V, O> Default for MapRegion<K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            inner: SliceRegion::default(),
+        }
+    }
+}
+
+/// A map read from a [`MapRegion`].
+#[allow(clippy::type_complexity)]
+pub struct ReadMap<'a, K, V, O = Entries<K, V>>(
+    Result<ReadSlice<'a, TupleABRegion<K, V>, O>, &'a BTreeMap<K::Owned, V::Owned>>,
+)
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>;
+
+impl<'a, K, V, O> ReadMap<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    /// Returns the value associated with `key`, or `None` if the map does not contain the key.
+    #[must_use]
+    pub fn get(&self, key: &K::Owned) -> Option<V::ReadItem<'_>>
+    where
+        K::Owned: Ord,
+        for<'b> K::ReadItem<'b>: Ord,
+    {
+        match &self.0 {
+            Ok(slice) => {
+                let needle = K::reborrow(IntoOwned::borrow_as(key));
+                let (mut lower, mut upper) = (0, slice.len());
+                while lower < upper {
+                    let middle = lower + (upper - lower) / 2;
+                    let (candidate, value) = slice.get(middle);
+                    match K::reborrow(candidate).cmp(&needle) {
+                        Ordering::Less => lower = middle + 1,
+                        Ordering::Equal => return Some(value),
+                        Ordering::Greater => upper = middle,
+                    }
+                }
+                None
+            }
+            Err(map) => map.get(key).map(IntoOwned::borrow_as),
+        }
+    }
+
+    /// Returns `true` if the map contains `key`.
+    #[must_use]
+    pub fn contains_key(&self, key: &K::Owned) -> bool
+    where
+        K::Owned: Ord,
+        for<'b> K::ReadItem<'b>: Ord,
+    {
+        self.get(key).is_some()
+    }
+
+    /// The number of entries in this map.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Ok(slice) => slice.len(),
+            Err(map) => map.len(),
+        }
+    }
+
+    /// Returns `true` if the map has no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Ok(slice) => slice.is_empty(),
+            Err(map) => map.is_empty(),
+        }
+    }
+
+    /// Returns an iterator over the key-value pairs in ascending key order.
+    #[must_use]
+    pub fn iter(&self) -> ReadMapIter<'a, K, V, O> {
+        self.into_iter()
+    }
+
+    /// Returns an iterator over the keys in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = K::ReadItem<'a>> + Clone {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values in ascending order of their keys.
+    pub fn values(&self) -> impl Iterator<Item = V::ReadItem<'a>> + Clone {
+        self.iter().map(|(_, value)| value)
+    }
+}
+
+impl<K, V, O> Clone for ReadMap<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, O> Copy for ReadMap<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+}
+
+impl<K, V, O> Debug for ReadMap<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+    for<'a> K::ReadItem<'a>: Debug,
+    for<'a> V::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, K, V, O> IntoOwned<'a> for ReadMap<'a, K, V, O>
+where
+    K: Region,
+    K::Owned: Ord,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Owned = BTreeMap<K::Owned, V::Owned>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().map(IntoOwned::into_owned).collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend(self.iter().map(IntoOwned::into_owned));
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a, K, V, O> IntoIterator for ReadMap<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Item = (K::ReadItem<'a>, V::ReadItem<'a>);
+    type IntoIter = ReadMapIter<'a, K, V, O>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Ok(slice) => ReadMapIter(Ok(slice.into_iter())),
+            Err(map) => ReadMapIter(Err(map.iter())),
+        }
+    }
+}
+
+/// An iterator over the entries of a [`ReadMap`].
+#[allow(clippy::type_complexity)]
+pub struct ReadMapIter<'a, K, V, O>(
+    Result<ReadSliceIter<'a, TupleABRegion<K, V>, O>, btree_map::Iter<'a, K::Owned, V::Owned>>,
+)
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>;
+
+impl<K, V, O> Clone for ReadMapIter<'_, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, K, V, O> Iterator for ReadMapIter<'a, K, V, O>
+where
+    K: Region,
+    V: Region,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    type Item = (K::ReadItem<'a>, V::ReadItem<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Ok(iter) => iter.next(),
+            Err(iter) => iter
+                .next()
+                .map(|(key, value)| (IntoOwned::borrow_as(key), IntoOwned::borrow_as(value))),
+        }
+    }
+}
+
+impl<K, V, O, KT, VT> Push<BTreeMap<KT, VT>> for MapRegion<K, V, O>
+where
+    K: Region + Push<KT>,
+    K::Owned: Ord,
+    V: Region + Push<VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn push(&mut self, item: BTreeMap<KT, VT>) -> <MapRegion<K, V, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+impl<'a, K, V, O, KT, VT> Push<&'a BTreeMap<KT, VT>> for MapRegion<K, V, O>
+where
+    K: Region + Push<&'a KT>,
+    K::Owned: Ord,
+    V: Region + Push<&'a VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn push(&mut self, item: &'a BTreeMap<KT, VT>) -> <MapRegion<K, V, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+impl<'a, K, V, O, KT, VT, S> Push<&'a HashMap<KT, VT, S>> for MapRegion<K, V, O>
+where
+    K: Region + Push<&'a KT>,
+    K::Owned: Ord,
+    V: Region + Push<&'a VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+    S: BuildHasher,
+{
+    #[inline]
+    fn push(&mut self, item: &'a HashMap<KT, VT, S>) -> <MapRegion<K, V, O> as Region>::Index {
+        let mut sorted: Vec<_> = item.iter().collect();
+        sorted.sort_unstable_by_key(|(key, _)| *key);
+        self.inner.push(PushIter(sorted))
+    }
+}
+
+impl<K, V, O, KT, VT> Push<Vec<(KT, VT)>> for MapRegion<K, V, O>
+where
+    K: Region + Push<KT>,
+    K::Owned: Ord,
+    V: Region + Push<VT>,
+    O: IndexContainer<(K::Index, V::Index)>,
+    KT: Ord,
+{
+    /// Pushes the entries as a map. Like collecting into a [`BTreeMap`], the last entry of a
+    /// key determines its value.
+    #[inline]
+    fn push(&mut self, item: Vec<(KT, VT)>) -> <MapRegion<K, V, O> as Region>::Index {
+        self.push(item.into_iter().collect::<BTreeMap<_, _>>())
+    }
+}
+
+impl<'a, K, V, O> Push<ReadMap<'a, K, V, O>> for MapRegion<K, V, O>
+where
+    K: Region + Push<<K as Region>::ReadItem<'a>>,
+    K::Owned: Ord,
+    V: Region + Push<<V as Region>::ReadItem<'a>>,
+    O: IndexContainer<(K::Index, V::Index)>,
+{
+    #[inline]
+    fn push(&mut self, item: ReadMap<'a, K, V, O>) -> <MapRegion<K, V, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let maps = [
+            BTreeMap::from([(5_u32, "e".to_string()), (1, "a".to_string())]),
+            BTreeMap::new(),
+            BTreeMap::from([(u32::MAX, "max".to_string()), (0, String::new())]),
+        ];
+
+        let mut c = FlatStack::<MapRegion<MirrorRegion<u32>, StringRegion>>::default();
+        for map in &maps {
+            c.copy(map);
+        }
+
+        for (map, read) in maps.iter().zip(c.iter()) {
+            assert_eq!(map.len(), read.len());
+            assert_eq!(*map, read.into_owned());
+            for key in 0..7 {
+                assert_eq!(map.get(&key).map(String::as_str), read.get(&key));
+            }
+            assert!(read.keys().eq(map.keys().copied()));
+        }
+        assert_eq!(r#"{1: "a", 5: "e"}"#, format!("{:?}", c.get(0)));
+
+        let mut size = 0;
+        c.heap_size(|siz, _| size += siz);
+        assert!(size > 0);
+        c.clear();
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_unsorted() {
+        let mut r = <MapRegion<StringRegion, MirrorRegion<u8>>>::default();
+
+        let map: HashMap<_, _> = [("c", 3), ("a", 1), ("b", 2)].into_iter().collect();
+        let index = r.push(&map);
+        assert!(r.index(index).iter().eq([("a", 1), ("b", 2), ("c", 3)]));
+
+        let index = r.push(vec![("b", 2), ("a", 1), ("b", 4)]);
+        assert!(r.index(index).iter().eq([("a", 1), ("b", 4)]));
+        assert_eq!(Some(4), r.index(index).get(&"b".to_string()));
+        assert!(!r.index(index).contains_key(&"c".to_string()));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let map = BTreeMap::from([(2_u32, 20_u64), (4, 40)]);
+        let read = <ReadMap<MirrorRegion<u32>, MirrorRegion<u64>> as IntoOwned>::borrow_as(&map);
+        assert_eq!(Some(40), read.get(&4));
+        assert_eq!(None, read.get(&3));
+
+        let mut c = FlatStack::default_impl::<BTreeMap<u32, u64>>();
+        c.copy(read);
+        assert_eq!(map, c.get(0).into_owned());
+        assert!(c.get(0).values().eq([20, 40]));
+    }
+}
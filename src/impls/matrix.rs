@@ -0,0 +1,390 @@
+//! A region that stores matrices.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, OwnedRegion, Push, Region, ReserveItems};
+
+/// A region to hold two-dimensional matrices of elements.
+///
+/// Stores the elements of each matrix in row-major order in an [`OwnedRegion`], and records
+/// the number of rows and columns in the index. The read item is a [`ReadMatrix`], which
+/// offers access to elements, rows as slices, and columns as strided iterators.
+///
+/// The owned representation is a tuple of the number of rows, the number of columns, and the
+/// elements in row-major order.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::matrix::MatrixRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <MatrixRegion<u32>>::default();
+///
+/// let index = r.push((2, 3, [1, 2, 3, 4, 5, 6].as_slice()));
+/// assert_eq!(6, *r.index(index).get(1, 2));
+/// assert_eq!([4, 5, 6], r.index(index).row(1));
+/// assert!(r.index(index).col(1).eq(&[2, 5]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "T: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct MatrixRegion<T> {
+    /// Elements of all matrices, in row-major order.
+    elements: OwnedRegion<T>,
+}
+
+impl<T: Clone> Clone for MatrixRegion<T> {
+    fn clone(&self) -> Self {
+        Self {
+            elements: self.elements.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.elements.clone_from(&source.elements);
+    }
+}
+
+impl<T> Default for MatrixRegion<T> {
+    fn default() -> Self {
+        Self {
+            elements: OwnedRegion::default(),
+        }
+    }
+}
+
+impl<T: Clone> Region for MatrixRegion<T> {
+    type Owned = (usize, usize, Vec<T>);
+    type ReadItem<'a> = ReadMatrix<'a, T> where Self: 'a;
+    /// The offset of the first element, the number of rows, and the number of columns.
+    type Index = (usize, usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            elements: OwnedRegion::merge_regions(regions.map(|r| &r.elements)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (start, rows, cols): Self::Index) -> Self::ReadItem<'_> {
+        ReadMatrix {
+            elements: self.elements.index((start, start + rows * cols)),
+            rows,
+            cols,
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.elements.reserve_regions(regions.map(|r| &r.elements));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.elements
+            .try_reserve_regions(regions.map(|r| &r.elements))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.elements.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A matrix read from a [`MatrixRegion`].
+pub struct ReadMatrix<'a, T> {
+    /// Elements in row-major order.
+    elements: &'a [T],
+    /// The number of rows.
+    rows: usize,
+    /// The number of columns.
+    cols: usize,
+}
+
+impl<'a, T> ReadMatrix<'a, T> {
+    /// Returns the number of rows.
+    #[inline]
+    #[must_use]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the number of columns.
+    #[inline]
+    #[must_use]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Returns the elements in row-major order.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &'a [T] {
+        self.elements
+    }
+
+    /// Returns the element in row `i` and column `j`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` or `j` is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, i: usize, j: usize) -> &'a T {
+        assert!(
+            i < self.rows && j < self.cols,
+            "ReadMatrix: position ({i}, {j}) out of bounds for shape ({}, {})",
+            self.rows,
+            self.cols
+        );
+        &self.elements[i * self.cols + j]
+    }
+
+    /// Returns the elements of row `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn row(&self, i: usize) -> &'a [T] {
+        assert!(
+            i < self.rows,
+            "ReadMatrix: row {i} out of bounds for {} rows",
+            self.rows
+        );
+        &self.elements[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Returns an iterator over the elements of column `j`, from the first row to the last.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `j` is out of bounds.
+    #[inline]
+    pub fn col(&self, j: usize) -> std::iter::StepBy<std::iter::Skip<std::slice::Iter<'a, T>>> {
+        assert!(
+            j < self.cols,
+            "ReadMatrix: column {j} out of bounds for {} columns",
+            self.cols
+        );
+        self.elements.iter().skip(j).step_by(self.cols)
+    }
+}
+
+impl<T> Clone for ReadMatrix<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadMatrix<'_, T> {}
+
+impl<T: PartialEq> PartialEq for ReadMatrix<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rows == other.rows && self.cols == other.cols && self.elements == other.elements
+    }
+}
+
+impl<T: Eq> Eq for ReadMatrix<'_, T> {}
+
+impl<T: Debug> Debug for ReadMatrix<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries((0..self.rows).map(|i| self.row(i)))
+            .finish()
+    }
+}
+
+impl<'a, T: Clone> IntoOwned<'a> for ReadMatrix<'a, T> {
+    type Owned = (usize, usize, Vec<T>);
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        (self.rows, self.cols, self.elements.to_vec())
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.0 = self.rows;
+        other.1 = self.cols;
+        other.2.clear();
+        other.2.extend_from_slice(self.elements);
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self {
+            elements: &owned.2,
+            rows: owned.0,
+            cols: owned.1,
+        }
+    }
+}
+
+impl<T: Clone> Push<(usize, usize, &[T])> for MatrixRegion<T> {
+    /// Pushes a matrix of `rows` rows and `cols` columns, with elements in row-major order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of elements does not match the shape, or if the shape's number of
+    /// elements overflows `usize`.
+    #[inline]
+    fn push(&mut self, (rows, cols, elements): (usize, usize, &[T])) -> (usize, usize, usize) {
+        let len = rows
+            .checked_mul(cols)
+            .unwrap_or_else(|| panic!("MatrixRegion: shape ({rows}, {cols}) overflows usize"));
+        assert_eq!(
+            len,
+            elements.len(),
+            "MatrixRegion: shape ({rows}, {cols}) does not match {} elements",
+            elements.len()
+        );
+        let (start, _) = self.elements.push(elements);
+        (start, rows, cols)
+    }
+}
+
+impl<T: Clone> Push<&(usize, usize, Vec<T>)> for MatrixRegion<T> {
+    #[inline]
+    fn push(&mut self, item: &(usize, usize, Vec<T>)) -> (usize, usize, usize) {
+        self.push((item.0, item.1, item.2.as_slice()))
+    }
+}
+
+impl<T: Clone> Push<(usize, usize, Vec<T>)> for MatrixRegion<T> {
+    #[inline]
+    fn push(&mut self, item: (usize, usize, Vec<T>)) -> (usize, usize, usize) {
+        self.push(&item)
+    }
+}
+
+impl<T: Clone> Push<ReadMatrix<'_, T>> for MatrixRegion<T> {
+    #[inline]
+    fn push(&mut self, item: ReadMatrix<'_, T>) -> (usize, usize, usize) {
+        self.push((item.rows, item.cols, item.elements))
+    }
+}
+
+impl<'a, T: Clone + 'a> ReserveItems<(usize, usize, &'a [T])> for MatrixRegion<T> {
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = (usize, usize, &'a [T])> + Clone,
+    {
+        self.elements
+            .reserve_items(items.map(|(_, _, elements)| elements));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_access() {
+        let mut r = <MatrixRegion<u32>>::default();
+        let index = r.push((2, 3, [1, 2, 3, 4, 5, 6].as_slice()));
+        let m = r.index(index);
+
+        assert_eq!((2, 3), (m.rows(), m.cols()));
+        assert_eq!(6, *m.get(1, 2));
+        assert_eq!(1, *m.get(0, 0));
+        assert_eq!([1, 2, 3], m.row(0));
+        assert!(m.col(0).eq(&[1, 4]));
+        assert!(m.col(2).eq(&[3, 6]));
+        assert_eq!(2, m.col(2).len());
+        assert_eq!("[[1, 2, 3], [4, 5, 6]]", format!("{m:?}"));
+        assert_eq!((2, 3, vec![1, 2, 3, 4, 5, 6]), m.into_owned());
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let matrices = [
+            (1, 1, vec![7.0_f64]),
+            (3, 2, vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            (0, 4, vec![]),
+        ];
+        let mut c = FlatStack::<MatrixRegion<f64>>::default();
+        c.reserve_items(matrices.iter().map(|(r, c, e)| (*r, *c, e.as_slice())));
+        for matrix in &matrices {
+            c.copy(matrix);
+        }
+        let mut r = <MatrixRegion<f64>>::default();
+        let index = r.push(c.get(1));
+        c.copy(r.index(index));
+
+        for (matrix, read) in matrices.iter().zip(c.iter()) {
+            assert_eq!(*matrix, read.into_owned());
+            assert_eq!(read, IntoOwned::borrow_as(matrix));
+        }
+        assert_eq!(c.get(1), c.get(3));
+        assert!(c.get(3).col(1).eq(&[2.0, 4.0, 6.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "MatrixRegion: shape (2, 2) does not match 3 elements")]
+    fn test_shape_mismatch() {
+        let mut r = <MatrixRegion<u8>>::default();
+        let _ = r.push((2, 2, [1, 2, 3].as_slice()));
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows usize")]
+    fn test_shape_overflow() {
+        let mut r = <MatrixRegion<u8>>::default();
+        let _ = r.push((usize::MAX, 2, [1, 2].as_slice()));
+    }
+
+    #[test]
+    fn test_empty_rows() {
+        let mut r = <MatrixRegion<u8>>::default();
+        let index = r.push((0, 3, [].as_slice()));
+        let m = r.index(index);
+        assert_eq!((0, 3), (m.rows(), m.cols()));
+        assert_eq!(0, m.col(2).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "ReadMatrix: position (0, 3) out of bounds for shape (2, 3)")]
+    fn test_out_of_bounds() {
+        let mut r = <MatrixRegion<u8>>::default();
+        let index = r.push((2, 3, [0; 6].as_slice()));
+        let _ = r.index(index).get(0, 3);
+    }
+}
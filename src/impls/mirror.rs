@@ -6,7 +6,8 @@ use std::marker::PhantomData;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{Index, IntoOwned, Push, Region, RegionPreference, ReserveItems};
+use crate::impls::choose::CanMeasure;
+use crate::{Index, IntoOwned, Push, Region, RegionLen, RegionPreference, ReserveItems};
 
 /// A region for types where the read item type is equal to the index type.
 ///
@@ -61,6 +62,12 @@ where
         index
     }
 
+    /// Returns zero, as the region stores its items in their indices.
+    #[inline(always)]
+    fn item_storage_size(&self, _index: Self::Index) -> usize {
+        0
+    }
+
     #[inline(always)]
     fn reserve_regions<'a, I>(&mut self, _regions: I)
     where
@@ -89,6 +96,17 @@ where
     }
 }
 
+impl<T> RegionLen for MirrorRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T>,
+{
+    /// Returns zero, as the region stores its items in their indices.
+    #[inline(always)]
+    fn len(&self) -> usize {
+        0
+    }
+}
+
 impl<T> Push<T> for MirrorRegion<T>
 where
     for<'a> T: Index + IntoOwned<'a, Owned = T>,
@@ -99,6 +117,17 @@ where
     }
 }
 
+/// Measures zero bytes, as the region stores its items in their indices.
+impl<T> CanMeasure<T> for MirrorRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T>,
+{
+    #[inline(always)]
+    fn measure(&self, _item: &T) -> usize {
+        0
+    }
+}
+
 impl<T> Push<&T> for MirrorRegion<T>
 where
     for<'a> T: Index + IntoOwned<'a, Owned = T>,
@@ -201,8 +230,24 @@ implement_for!(std::num::Wrapping<i64>);
 implement_for!(std::num::Wrapping<i128>);
 implement_for!(std::num::Wrapping<isize>);
 
+implement_for!(std::num::NonZeroU8);
+implement_for!(std::num::NonZeroU16);
+implement_for!(std::num::NonZeroU32);
+implement_for!(std::num::NonZeroU64);
+implement_for!(std::num::NonZeroU128);
+implement_for!(std::num::NonZeroUsize);
+implement_for!(std::num::NonZeroI8);
+implement_for!(std::num::NonZeroI16);
+implement_for!(std::num::NonZeroI32);
+implement_for!(std::num::NonZeroI64);
+implement_for!(std::num::NonZeroI128);
+implement_for!(std::num::NonZeroIsize);
+
 implement_for!(std::time::Duration);
 
+implement_for!(std::net::Ipv4Addr);
+implement_for!(std::net::Ipv6Addr);
+
 #[cfg(test)]
 mod tests {
     use crate::ReserveItems;
@@ -214,4 +259,40 @@ mod tests {
         let mut r = MirrorRegion::<u8>::default();
         ReserveItems::reserve_items(&mut r, std::iter::once(0));
     }
+
+    #[test]
+    fn test_non_zero() {
+        use std::num::{NonZeroI8, NonZeroU64};
+
+        let mut c = crate::FlatStack::default_impl::<NonZeroU64>();
+        let items: Vec<_> = (1..=10).filter_map(NonZeroU64::new).collect();
+        c.extend(&items);
+        assert!(c.iter().eq(items.iter().copied()));
+        assert_eq!(items[3], c.get(3).into_owned());
+
+        let mut r = MirrorRegion::<NonZeroI8>::default();
+        let index = r.push(NonZeroI8::new(i8::MIN).unwrap());
+        assert_eq!(i8::MIN, r.index(index).get());
+    }
+    #[test]
+    fn test_duration() {
+        use std::time::Duration;
+
+        use crate::{FlatStack, SliceRegion};
+
+        let durations = vec![
+            Duration::ZERO,
+            Duration::from_nanos(1),
+            Duration::from_secs(3600),
+            Duration::MAX,
+        ];
+        let mut r = SliceRegion::<MirrorRegion<Duration>>::default();
+        let index = r.push(&durations);
+        assert!(r.index(index).iter().eq(durations.iter().copied()));
+        assert_eq!(durations, r.index(index).into_owned());
+
+        let mut c = FlatStack::default_impl::<(Duration, u8)>();
+        c.copy((Duration::from_millis(5), 1));
+        assert_eq!((Duration::from_millis(5), 1), c.get(0));
+    }
 }
@@ -0,0 +1,237 @@
+//! A region that stores grid coordinates as Morton codes.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::{FlatStack, MirrorRegion, Push, Region, ReserveItems};
+
+/// Spreads the bits of `value` to the even bit positions of the result.
+#[inline]
+fn spread(value: u32) -> u64 {
+    let mut value = u64::from(value);
+    value = (value | (value << 16)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value << 8)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    (value | (value << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Collects the even bits of `value`, inverting [`spread`].
+#[inline]
+fn compact(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0F0F_0F0F_0F0F_0F0F;
+    value = (value | (value >> 4)) & 0x00FF_00FF_00FF_00FF;
+    value = (value | (value >> 8)) & 0x0000_FFFF_0000_FFFF;
+    value = (value | (value >> 16)) & 0x0000_0000_FFFF_FFFF;
+    u32::try_from(value).expect("Compacted value must fit 32 bits")
+}
+
+/// Returns the Morton code of `(x, y)`, which interleaves the bits of `x` at even positions
+/// with the bits of `y` at odd positions.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::morton::{morton_decode, morton_encode};
+/// assert_eq!(0b1001, morton_encode(1, 2));
+/// assert_eq!((1, 2), morton_decode(0b1001));
+/// ```
+#[inline]
+#[must_use]
+pub fn morton_encode(x: u32, y: u32) -> u64 {
+    spread(x) | (spread(y) << 1)
+}
+
+/// Returns the coordinates `(x, y)` of a Morton code, inverting [`morton_encode`].
+#[inline]
+#[must_use]
+pub fn morton_decode(code: u64) -> (u32, u32) {
+    (compact(code), compact(code >> 1))
+}
+
+/// A region for two-dimensional grid coordinates, which stores each coordinate as its
+/// Morton code.
+///
+/// Morton codes order points along a Z-order curve, which places points that are close on
+/// the grid close to each other in most cases. The index is the Morton code, which makes the
+/// region free of storage, and the read item the decoded coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::morton::MortonRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = MortonRegion::default();
+///
+/// let index = r.push((3, 5));
+/// assert_eq!((3, 5), r.index(index));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MortonRegion {
+    /// Storage for the Morton codes.
+    inner: MirrorRegion<u64>,
+}
+
+impl Region for MortonRegion {
+    type Owned = (u32, u32);
+    type ReadItem<'a> = (u32, u32);
+    /// The Morton code of the coordinates.
+    type Index = u64;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: MirrorRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        morton_decode(self.inner.index(index))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<(u32, u32)> for MortonRegion {
+    #[inline]
+    fn push(&mut self, (x, y): (u32, u32)) -> u64 {
+        self.inner.push(morton_encode(x, y))
+    }
+}
+
+impl Push<&(u32, u32)> for MortonRegion {
+    #[inline]
+    fn push(&mut self, item: &(u32, u32)) -> u64 {
+        self.push(*item)
+    }
+}
+
+impl<T> ReserveItems<T> for MortonRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+impl<S: IndexContainer<u64>> FlatStack<MortonRegion, S> {
+    /// Returns the offsets of the coordinates whose Morton codes are in the range from
+    /// `min_code` to `max_code`, including both, in ascending order of offsets.
+    ///
+    /// The range of codes covers all points of the grid cell that the Z-order curve visits
+    /// between the two codes, which can include points outside the bounding box of the two
+    /// corners.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::morton::{morton_encode, MortonRegion};
+    /// use flatcontainer::FlatStack;
+    /// let mut c = FlatStack::<MortonRegion>::default();
+    /// c.copy((0, 0));
+    /// c.copy((100, 100));
+    /// c.copy((1, 1));
+    ///
+    /// let near = c.range_z(morton_encode(0, 0), morton_encode(1, 1));
+    /// assert!(near.eq([0, 2]));
+    /// ```
+    pub fn range_z(
+        &self,
+        min_code: u64,
+        max_code: u64,
+    ) -> impl Iterator<Item = usize> + Clone + '_ {
+        self.indices
+            .iter()
+            .enumerate()
+            .filter(move |(_, code)| (min_code..=max_code).contains(code))
+            .map(|(offset, _)| offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let points = [
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (u32::MAX, 0),
+            (0, u32::MAX),
+            (u32::MAX, u32::MAX),
+            (12_345, 67_890),
+        ];
+        let mut c = FlatStack::<MortonRegion>::default();
+        for point in &points {
+            c.copy(point);
+        }
+        assert!(c.iter().eq(points));
+        assert_eq!(u64::MAX, morton_encode(u32::MAX, u32::MAX));
+    }
+
+    #[test]
+    fn test_locality() {
+        // The four points of a 2x2 cell have consecutive codes.
+        let cell: Vec<_> = [(4, 6), (5, 6), (4, 7), (5, 7)]
+            .into_iter()
+            .map(|(x, y)| morton_encode(x, y))
+            .collect();
+        assert!(cell.windows(2).all(|w| w[1] == w[0] + 1));
+        assert!(morton_encode(5, 7) < morton_encode(1000, 1000));
+
+        let mut c = FlatStack::<MortonRegion>::default();
+        for point in [(4, 6), (1000, 1000), (5, 7), (0, 0), (5, 6)] {
+            c.copy(point);
+        }
+        assert!(c.range_z(cell[0], cell[3]).eq([0, 2, 4]));
+        assert_eq!(0, c.range_z(cell[3], cell[0]).count());
+    }
+}
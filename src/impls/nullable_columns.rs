@@ -0,0 +1,701 @@
+//! A region to contain a variable number of columns, where each cell can be null.
+
+use std::collections::TryReserveError;
+use std::fmt::Debug;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::columns::ColumnsRegion;
+use crate::impls::index::{IndexContainer, IndexOptimized};
+use crate::{IntoOwned, Push, PushIter, Region, RegionLen};
+
+/// A region that can store a variable number of cells per row, where each cell can be null.
+///
+/// The region stores the values of present cells in a [`ColumnsRegion`], and records a bitmap
+/// of null cells for rows that contain any, which distinguishes a null cell from a shorter row.
+/// Null cells do not occupy space in the columns. Reading a row yields an
+/// `Option<R::ReadItem>` per cell, and the owned representation of a row is a
+/// `Vec<Option<R::Owned>>`.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::{IntoOwned, MirrorRegion, NullableColumnsRegion, Push, Region};
+/// let mut r = <NullableColumnsRegion<MirrorRegion<u32>>>::default();
+///
+/// let nulls = r.push(&[Some(1), None, Some(3)]);
+/// let short = r.push(&[Some(1), Some(3)]);
+///
+/// assert!(r.index(nulls).iter().eq([Some(1), None, Some(3)]));
+/// assert_ne!(r.index(nulls), r.index(short));
+/// assert_eq!(vec![Some(1), None, Some(3)], r.index(nulls).into_owned());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            O: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct NullableColumnsRegion<R, O = IndexOptimized>
+where
+    R: Region,
+{
+    /// Values of the present cells.
+    columns: ColumnsRegion<R, O>,
+    /// Rows that contain null cells, and the offset of their null bitmap in `null_bits`,
+    /// ordered by row.
+    nulls: Vec<(usize, usize)>,
+    /// Bitmaps of null cells, with one bit per cell of each row listed in `nulls`.
+    null_bits: Vec<u64>,
+}
+
+impl<R, O> Debug for NullableColumnsRegion<R, O>
+where
+    R: Region,
+    ColumnsRegion<R, O>: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NullableColumnsRegion")
+            .field("columns", &self.columns)
+            .field("nulls", &self.nulls)
+            .field("null_bits", &self.null_bits)
+            .finish()
+    }
+}
+
+impl<R, O> Clone for NullableColumnsRegion<R, O>
+where
+    R: Region + Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            columns: self.columns.clone(),
+            nulls: self.nulls.clone(),
+            null_bits: self.null_bits.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.columns.clone_from(&source.columns);
+        self.nulls.clone_from(&source.nulls);
+        self.null_bits.clone_from(&source.null_bits);
+    }
+}
+
+impl<R, O> Region for NullableColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    type Owned = Vec<Option<R::Owned>>;
+    type ReadItem<'a> = ReadNullableColumns<'a, R> where Self: 'a;
+    type Index = <ColumnsRegion<R, O> as Region>::Index;
+
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            columns: ColumnsRegion::merge_regions(regions.clone().map(|r| &r.columns)),
+            nulls: Vec::with_capacity(regions.clone().map(|r| r.nulls.len()).sum()),
+            null_bits: Vec::with_capacity(regions.map(|r| r.null_bits.len()).sum()),
+        }
+    }
+
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let (columns, values) = self.columns.row(index);
+        ReadNullableColumns(Ok(ReadNullableColumnsInner {
+            columns,
+            index: values,
+            nulls: self.null_bits(index),
+        }))
+    }
+
+    /// Returns the storage size of the row's values and indexes, plus the size of its null
+    /// bitmap.
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.columns.item_storage_size(index) + std::mem::size_of_val(self.null_bits(index))
+    }
+
+    fn last_index(&self) -> Option<Self::Index> {
+        self.columns.last_index()
+    }
+
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.columns
+            .reserve_regions(regions.clone().map(|r| &r.columns));
+        self.nulls
+            .reserve(regions.clone().map(|r| r.nulls.len()).sum());
+        self.null_bits
+            .reserve(regions.map(|r| r.null_bits.len()).sum());
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.columns
+            .try_reserve_regions(regions.clone().map(|r| &r.columns))?;
+        self.nulls
+            .try_reserve(regions.clone().map(|r| r.nulls.len()).sum())?;
+        self.null_bits
+            .try_reserve(regions.map(|r| r.null_bits.len()).sum())
+    }
+
+    fn clear(&mut self) {
+        self.columns.clear();
+        self.nulls.clear();
+        self.null_bits.clear();
+    }
+
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_null = std::mem::size_of::<(usize, usize)>();
+        callback(
+            self.nulls.len() * size_of_null,
+            self.nulls.capacity() * size_of_null,
+        );
+        let size_of_bits = std::mem::size_of::<u64>();
+        callback(
+            self.null_bits.len() * size_of_bits,
+            self.null_bits.capacity() * size_of_bits,
+        );
+        self.columns.heap_size(callback);
+    }
+
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<R, O> RegionLen for NullableColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    /// Returns the number of rows.
+    #[inline]
+    fn len(&self) -> usize {
+        self.columns.len()
+    }
+}
+
+impl<R, O> Default for NullableColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    fn default() -> Self {
+        Self {
+            columns: ColumnsRegion::default(),
+            nulls: Vec::default(),
+            null_bits: Vec::default(),
+        }
+    }
+}
+
+impl<R, O> NullableColumnsRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<usize>,
+{
+    /// Iterate the cells of `column` in row order, yielding `None` for rows that are null at
+    /// `column` or shorter than `column`. Only reads the region of `column`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{MirrorRegion, NullableColumnsRegion, Push};
+    /// let mut r = <NullableColumnsRegion<MirrorRegion<u32>>>::default();
+    ///
+    /// let _ = r.push(&[Some(1), Some(2)]);
+    /// let _ = r.push(&[Some(3)]);
+    /// let _ = r.push(&[Some(4), None]);
+    /// assert!(r.iter_column(1).eq([Some(2), None, None]));
+    /// ```
+    pub fn iter_column(
+        &self,
+        column: usize,
+    ) -> impl Iterator<Item = Option<R::ReadItem<'_>>> + Clone + '_ {
+        (0..self.columns.len()).map(move |row| {
+            let row = self.index(row);
+            if column < row.len() {
+                row.get(column)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Push the cells of a row, recording a null bitmap if any cell is `None`.
+    fn push_cells<T, I>(&mut self, row: I) -> <Self as Region>::Index
+    where
+        R: Push<T>,
+        I: IntoIterator<Item = Option<T>>,
+    {
+        let start = self.null_bits.len();
+        let null_bits = &mut self.null_bits;
+        let cells = row.into_iter().enumerate().filter_map(|(column, cell)| {
+            if column % 64 == 0 {
+                null_bits.push(0);
+            }
+            if cell.is_none() {
+                null_bits[start + column / 64] |= 1 << (column % 64);
+            }
+            cell.map(|value| (column, value))
+        });
+        let row = self.columns.push_at(cells);
+        if self.null_bits[start..].iter().any(|bits| *bits != 0) {
+            self.nulls.push((row, start));
+        } else {
+            self.null_bits.truncate(start);
+        }
+        row
+    }
+
+    /// Returns the null bitmap of `row`, which is empty if the row has no null cells.
+    fn null_bits(&self, row: <Self as Region>::Index) -> &[u64] {
+        match self.nulls.binary_search_by_key(&row, |(row, _)| *row) {
+            Ok(position) => {
+                let start = self.nulls[position].1;
+                let end = self
+                    .nulls
+                    .get(position + 1)
+                    .map_or(self.null_bits.len(), |(_, start)| *start);
+                &self.null_bits[start..end]
+            }
+            Err(_) => &[],
+        }
+    }
+}
+
+/// Returns `true` if bit `offset` is set in `bits`.
+#[inline]
+fn is_set(bits: &[u64], offset: usize) -> bool {
+    bits.get(offset / 64)
+        .map_or(false, |word| word >> (offset % 64) & 1 == 1)
+}
+
+/// Counts the bits set in `bits` before `offset`.
+#[inline]
+fn rank(bits: &[u64], offset: usize) -> usize {
+    let (word, shift) = (offset / 64, offset % 64);
+    let full: u32 = bits.iter().take(word).map(|word| word.count_ones()).sum();
+    let partial = bits
+        .get(word)
+        .map_or(0, |word| (word & ((1 << shift) - 1)).count_ones());
+    (full + partial) as usize
+}
+
+/// Read the cells of a row.
+pub struct ReadNullableColumns<'a, R>(
+    Result<ReadNullableColumnsInner<'a, R>, &'a [Option<R::Owned>]>,
+)
+where
+    R: Region;
+
+struct ReadNullableColumnsInner<'a, R>
+where
+    R: Region,
+{
+    /// Storage for columns.
+    columns: &'a [R],
+    /// Indices to retrieve values from columns, skipping null cells.
+    index: &'a [R::Index],
+    /// Bitmap of null cells, empty if the row has none.
+    nulls: &'a [u64],
+}
+
+impl<'a, R> Clone for ReadNullableColumns<'a, R>
+where
+    R: Region,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R> Clone for ReadNullableColumnsInner<'a, R>
+where
+    R: Region,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, R> Copy for ReadNullableColumns<'a, R> where R: Region {}
+impl<'a, R> Copy for ReadNullableColumnsInner<'a, R> where R: Region {}
+
+impl<'a, R> Debug for ReadNullableColumns<'a, R>
+where
+    R: Region,
+    R::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+impl<R> PartialEq for ReadNullableColumns<'_, R>
+where
+    R: Region,
+    for<'a> R::ReadItem<'a>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.into_iter().eq(other)
+    }
+}
+
+impl<R> Eq for ReadNullableColumns<'_, R>
+where
+    R: Region,
+    for<'a> R::ReadItem<'a>: Eq,
+{
+}
+
+impl<'a, R> ReadNullableColumns<'a, R>
+where
+    R: Region,
+{
+    /// Iterate the cells of a row, yielding `None` for null cells.
+    #[must_use]
+    pub fn iter(&self) -> ReadNullableColumnsIter<'a, R> {
+        self.into_iter()
+    }
+
+    /// Get the cell at `offset`, or `None` if the cell is null.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is out of bounds, i.e., it is larger or equal to the length of this row.
+    #[must_use]
+    pub fn get(&self, offset: usize) -> Option<R::ReadItem<'a>> {
+        assert!(
+            offset < self.len(),
+            "ReadNullableColumns: index {offset} out of bounds for length {}",
+            self.len()
+        );
+        match &self.0 {
+            Ok(inner) => inner.get(offset),
+            Err(slice) => slice[offset].as_ref().map(IntoOwned::borrow_as),
+        }
+    }
+
+    /// Returns the length of this row, including null cells.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Ok(inner) => inner.len(),
+            Err(slice) => slice.len(),
+        }
+    }
+
+    /// Returns `true` if this row is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Ok(inner) => inner.is_empty(),
+            Err(slice) => slice.is_empty(),
+        }
+    }
+}
+
+impl<'a, R> ReadNullableColumnsInner<'a, R>
+where
+    R: Region,
+{
+    /// Get the cell at `offset`, or `None` if the cell is null.
+    #[must_use]
+    fn get(&self, offset: usize) -> Option<R::ReadItem<'a>> {
+        if is_set(self.nulls, offset) {
+            return None;
+        }
+        let index = self.index[offset - rank(self.nulls, offset)];
+        Some(self.columns[offset].index(index))
+    }
+
+    /// Returns the length of this row, including null cells.
+    #[must_use]
+    fn len(&self) -> usize {
+        let nulls: u32 = self.nulls.iter().map(|word| word.count_ones()).sum();
+        self.index.len() + nulls as usize
+    }
+
+    /// Returns `true` if this row is empty.
+    #[must_use]
+    fn is_empty(&self) -> bool {
+        self.index.is_empty() && self.nulls.is_empty()
+    }
+}
+
+impl<'a, R> IntoOwned<'a> for ReadNullableColumns<'a, R>
+where
+    R: Region,
+{
+    type Owned = Vec<Option<R::Owned>>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter()
+            .map(|cell| cell.map(IntoOwned::into_owned))
+            .collect()
+    }
+
+    fn clone_onto(self, other: &mut Self::Owned) {
+        let r = std::cmp::min(self.len(), other.len());
+        for (cell, target) in self.iter().zip(other.iter_mut()) {
+            match (cell, target) {
+                (Some(cell), Some(target)) => cell.clone_onto(target),
+                (cell, target) => *target = cell.map(IntoOwned::into_owned),
+            }
+        }
+        other.extend(
+            self.iter()
+                .skip(r)
+                .map(|cell| cell.map(IntoOwned::into_owned)),
+        );
+        other.truncate(self.len());
+    }
+
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned.as_slice()))
+    }
+}
+
+impl<'a, R> IntoIterator for &ReadNullableColumns<'a, R>
+where
+    R: Region,
+{
+    type Item = Option<R::ReadItem<'a>>;
+    type IntoIter = ReadNullableColumnsIter<'a, R>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        ReadNullableColumnsIter {
+            row: *self,
+            offset: 0,
+            len: self.len(),
+        }
+    }
+}
+
+/// An iterator over the cells of a row.
+pub struct ReadNullableColumnsIter<'a, R: Region> {
+    /// The row to read.
+    row: ReadNullableColumns<'a, R>,
+    /// The offset of the next cell.
+    offset: usize,
+    /// The length of the row.
+    len: usize,
+}
+
+impl<'a, R> Clone for ReadNullableColumnsIter<'a, R>
+where
+    R: Region,
+{
+    fn clone(&self) -> Self {
+        Self {
+            row: self.row,
+            offset: self.offset,
+            len: self.len,
+        }
+    }
+}
+
+impl<'a, R> Iterator for ReadNullableColumnsIter<'a, R>
+where
+    R: Region,
+{
+    type Item = Option<R::ReadItem<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset < self.len {
+            let cell = self.row.get(self.offset);
+            self.offset += 1;
+            Some(cell)
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.offset;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, R> ExactSizeIterator for ReadNullableColumnsIter<'a, R> where R: Region {}
+
+impl<R, O> Push<ReadNullableColumns<'_, R>> for NullableColumnsRegion<R, O>
+where
+    for<'a> R: Region + Push<<R as Region>::ReadItem<'a>>,
+    O: IndexContainer<usize>,
+{
+    fn push(
+        &mut self,
+        item: ReadNullableColumns<'_, R>,
+    ) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push_cells(item.iter())
+    }
+}
+
+impl<'a, R, O, T> Push<&'a [Option<T>]> for NullableColumnsRegion<R, O>
+where
+    R: Region + Push<&'a T>,
+    O: IndexContainer<usize>,
+{
+    fn push(&mut self, item: &'a [Option<T>]) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push_cells(item.iter().map(Option::as_ref))
+    }
+}
+
+impl<'a, R, O, T, const N: usize> Push<&'a [Option<T>; N]> for NullableColumnsRegion<R, O>
+where
+    R: Region + Push<&'a T>,
+    O: IndexContainer<usize>,
+{
+    fn push(&mut self, item: &'a [Option<T>; N]) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl<R, O, T> Push<Vec<Option<T>>> for NullableColumnsRegion<R, O>
+where
+    R: Region + Push<T>,
+    O: IndexContainer<usize>,
+{
+    fn push(&mut self, item: Vec<Option<T>>) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push_cells(item)
+    }
+}
+
+impl<'a, R, O, T> Push<&'a Vec<Option<T>>> for NullableColumnsRegion<R, O>
+where
+    R: Region + Push<&'a T>,
+    O: IndexContainer<usize>,
+{
+    fn push(&mut self, item: &'a Vec<Option<T>>) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl<R, O, T, I> Push<PushIter<I>> for NullableColumnsRegion<R, O>
+where
+    R: Region + Push<T>,
+    O: IndexContainer<usize>,
+    I: IntoIterator<Item = Option<T>>,
+{
+    #[inline]
+    fn push(&mut self, item: PushIter<I>) -> <NullableColumnsRegion<R, O> as Region>::Index {
+        self.push_cells(item.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_push_nulls() {
+        let mut r = NullableColumnsRegion::<MirrorRegion<u32>>::default();
+
+        let nulls = r.push(&[Some(1), None, Some(3)]);
+        let short = r.push(&[Some(1), Some(3)]);
+        let trailing = r.push(&[Some(1), Some(3), None]);
+        let complete = r.push(&[Some(4), Some(5), Some(6)]);
+
+        let row = r.index(nulls);
+        assert_eq!(3, row.len());
+        assert_eq!(Some(1), row.get(0));
+        assert_eq!(None, row.get(1));
+        assert_eq!(Some(3), row.get(2));
+        assert_eq!("[Some(1), None, Some(3)]", format!("{row:?}"));
+
+        // A null cell is distinct from a shorter row.
+        assert!(r.index(short).iter().eq([Some(1), Some(3)]));
+        assert_ne!(r.index(nulls), r.index(short));
+        assert_ne!(r.index(nulls), r.index(trailing));
+        assert!(r.index(trailing).iter().eq([Some(1), Some(3), None]));
+
+        // Rows without nulls do not record a bitmap.
+        assert_eq!(2, r.nulls.len());
+        assert!(r.index(complete).iter().eq([Some(4), Some(5), Some(6)]));
+
+        // Copying a row preserves its nulls.
+        let mut target = NullableColumnsRegion::<MirrorRegion<u32>>::default();
+        let index = target.push(r.index(nulls));
+        assert_eq!(r.index(nulls), target.index(index));
+
+        r.clear();
+        assert!(r.nulls.is_empty() && r.null_bits.is_empty());
+    }
+
+    #[test]
+    fn test_push_nulls_wide() {
+        let row: Vec<Option<u32>> = (0..150)
+            .map(|i| if i % 7 == 0 { None } else { Some(i) })
+            .collect();
+
+        let mut r = NullableColumnsRegion::<MirrorRegion<u32>>::default();
+        let first = r.push(&row);
+        let second = r.push(&row[..70]);
+
+        assert_eq!(row, r.index(first).into_owned());
+        assert_eq!(row[..70].to_vec(), r.index(second).into_owned());
+    }
+
+    #[test]
+    fn test_owned_round_trip() {
+        let rows = [
+            vec![Some("a".to_string()), None, Some("c".to_string())],
+            vec![None],
+            vec![],
+            vec![Some("d".to_string())],
+        ];
+        let mut c = FlatStack::<NullableColumnsRegion<StringRegion>>::default();
+        for row in &rows {
+            c.copy(row);
+        }
+
+        let mut out = vec![vec![Some("x".to_string())]; 5];
+        c.collect_owned_into(&mut out);
+        assert_eq!(rows.to_vec(), out);
+
+        // Owned rows push back to equal rows.
+        let mut copy = FlatStack::<NullableColumnsRegion<StringRegion>>::default();
+        for row in &out {
+            copy.copy(row);
+        }
+        assert!(copy.iter().eq(c.iter()));
+
+        let mut row = vec![None, Some("y".to_string())];
+        c.get(0).clone_onto(&mut row);
+        assert_eq!(rows[0], row);
+
+        assert_eq!(Some(rows[3].clone()), c.pop());
+        assert_eq!(Some(rows[2].clone()), c.pop());
+        assert_eq!(2, c.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "ReadNullableColumns: index 2 out of bounds for length 2")]
+    fn test_get_out_of_bounds() {
+        let mut r = <NullableColumnsRegion<MirrorRegion<u8>>>::default();
+        let index = r.push(&[None, Some(1)]);
+        let _ = r.index(index).get(2);
+    }
+}
@@ -0,0 +1,278 @@
+//! A region that stores optional slices.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::slice::ReadSlice;
+use crate::{Push, Region, ReserveItems, SliceRegion};
+
+/// The index representing `None`. No slice can start at `usize::MAX`, so the sentinel cannot
+/// collide with an index produced by the slice region, including the index of an empty slice.
+const NULL: (usize, usize) = (usize::MAX, usize::MAX);
+
+/// A region to hold optional slices, i.e., nullable lists.
+///
+/// Compared to an [`OptionRegion`](crate::OptionRegion) wrapping a [`SliceRegion`], this region
+/// does not store a discriminant next to the index. Instead, it encodes `None` as a sentinel
+/// index, which does not allocate any storage. The read item is an `Option<ReadSlice>`, which
+/// distinguishes a `None` from an empty slice.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::{MirrorRegion, NullableSliceRegion, Push, Region};
+/// let mut r = <NullableSliceRegion<MirrorRegion<u8>>>::default();
+///
+/// let some_index = r.push(Some(vec![1, 2]));
+/// let none_index = r.push(Option::<Vec<u8>>::None);
+/// let empty_index = r.push(Some(Vec::<u8>::new()));
+///
+/// assert!(r.index(some_index).unwrap().iter().eq([1, 2]));
+/// assert!(r.index(none_index).is_none());
+/// assert!(r.index(empty_index).unwrap().is_empty());
+/// assert_eq!((1, 2), (r.null_count(), r.valid_count()));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NullableSliceRegion<R: Region, O = Vec<<R as Region>::Index>> {
+    /// Region holding the present slices.
+    inner: SliceRegion<R, O>,
+    /// The number of pushed `None`s.
+    null_count: usize,
+    /// The number of pushed slices.
+    valid_count: usize,
+}
+
+impl<R, O> Clone for NullableSliceRegion<R, O>
+where
+    R: Region + Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            null_count: self.null_count,
+            valid_count: self.valid_count,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.null_count = source.null_count;
+        self.valid_count = source.valid_count;
+    }
+}
+
+impl<R: Region, O> NullableSliceRegion<R, O> {
+    /// Returns the number of `None`s pushed since the region was created or last cleared.
+    #[inline]
+    #[must_use]
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Returns the number of slices pushed since the region was created or last cleared.
+    #[inline]
+    #[must_use]
+    pub fn valid_count(&self) -> usize {
+        self.valid_count
+    }
+
+    /// Counts a pushed option.
+    #[inline]
+    fn count(&mut self, is_some: bool) {
+        if is_some {
+            self.valid_count += 1;
+        } else {
+            self.null_count += 1;
+        }
+    }
+}
+
+impl<R: Region, O: IndexContainer<R::Index>> Region for NullableSliceRegion<R, O> {
+    type Owned = Option<Vec<R::Owned>>;
+    type ReadItem<'a> = Option<ReadSlice<'a, R, O>> where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: SliceRegion::merge_regions(regions.map(|r| &r.inner)),
+            null_count: 0,
+            valid_count: 0,
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        (index != NULL).then(|| self.inner.index(index))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.null_count = 0;
+        self.valid_count = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<R: Region, O: IndexContainer<R::Index>> Default for NullableSliceRegion<R, O> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            inner: SliceRegion::default(),
+            null_count: 0,
+            valid_count: 0,
+        }
+    }
+}
+
+impl<T, R, O> Push<Option<T>> for NullableSliceRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    SliceRegion<R, O>: Region<Index = (usize, usize)> + Push<T>,
+{
+    #[inline]
+    fn push(&mut self, item: Option<T>) -> <NullableSliceRegion<R, O> as Region>::Index {
+        self.count(item.is_some());
+        item.map_or(NULL, |t| self.inner.push(t))
+    }
+}
+
+impl<'a, T, R, O> Push<&'a Option<T>> for NullableSliceRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    SliceRegion<R, O>: Region<Index = (usize, usize)> + Push<&'a T>,
+{
+    #[inline]
+    fn push(&mut self, item: &'a Option<T>) -> <NullableSliceRegion<R, O> as Region>::Index {
+        self.count(item.is_some());
+        item.as_ref().map_or(NULL, |t| self.inner.push(t))
+    }
+}
+
+impl<T, R, O> ReserveItems<Option<T>> for NullableSliceRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    SliceRegion<R, O>: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = Option<T>> + Clone,
+    {
+        #[allow(clippy::filter_map_identity)]
+        self.inner.reserve_items(items.filter_map(|r| r));
+    }
+}
+
+impl<'a, T, R, O> ReserveItems<&'a Option<T>> for NullableSliceRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+    SliceRegion<R, O>: ReserveItems<&'a T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a Option<T>> + Clone,
+    {
+        self.inner.reserve_items(items.filter_map(|r| r.as_ref()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoOwned, MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_null_and_empty() {
+        let mut r = <NullableSliceRegion<MirrorRegion<u8>>>::default();
+
+        let some = r.push(Some(vec![1, 2]));
+        let none = r.push(Option::<Vec<u8>>::None);
+        let empty = r.push(Some(Vec::<u8>::new()));
+
+        assert_eq!(Some(vec![1, 2]), r.index(some).into_owned());
+        assert_eq!(None, r.index(none).into_owned());
+        assert_eq!(Some(vec![]), r.index(empty).into_owned());
+
+        assert!(r.index(empty).is_some());
+        assert!(r.index(none).is_none());
+
+        assert_eq!((1, 2), (r.null_count(), r.valid_count()));
+        r.clear();
+        assert_eq!((0, 0), (r.null_count(), r.valid_count()));
+    }
+
+    #[test]
+    fn test_push_read_item() {
+        let mut r = <NullableSliceRegion<StringRegion>>::default();
+        let data = [Some(vec!["a", "b"]), None, Some(vec![])];
+        let indices: Vec<_> = data.iter().map(|item| r.push(item)).collect();
+
+        let mut c = <NullableSliceRegion<StringRegion>>::default();
+        for (index, item) in indices.into_iter().zip(&data) {
+            let read = r.index(index);
+            let index = c.push(read);
+            assert_eq!(
+                item.as_ref().map(|x| x.iter().map(ToString::to_string).collect::<Vec<_>>()),
+                c.index(index).into_owned()
+            );
+        }
+    }
+
+    #[test]
+    fn test_none_does_not_allocate() {
+        let mut r = <NullableSliceRegion<MirrorRegion<u8>>>::default();
+        r.reserve_items([Option::<Vec<u8>>::None, None].iter());
+        for _ in 0..4 {
+            let _ = r.push(Option::<Vec<u8>>::None);
+        }
+        let mut cap = 0;
+        r.heap_size(|_, ca| cap += ca);
+        assert_eq!(0, cap);
+    }
+}
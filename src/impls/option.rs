@@ -1,8 +1,14 @@
 //! A region that stores options.
 
+use std::collections::TryReserveError;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::impls::choose::CanMeasure;
+use crate::impls::discriminant::{DiscriminantIter, DiscriminantStore};
+use crate::impls::index::IndexContainer;
+use crate::impls::storage::Storage;
 use crate::{IntoOwned, Push, Region, RegionPreference, ReserveItems};
 
 impl<T: RegionPreference> RegionPreference for Option<T> {
@@ -25,22 +31,61 @@ impl<T: RegionPreference> RegionPreference for Option<T> {
 ///
 /// assert_eq!(Some(123), r.index(some_index));
 /// assert_eq!(None, r.index(none_index));
+/// assert_eq!((1, 1), (r.null_count(), r.valid_count()));
 /// ```
 #[derive(Default, Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OptionRegion<R> {
     inner: R,
+    /// The number of pushed `None`s.
+    null_count: usize,
+    /// The number of pushed `Some`s.
+    valid_count: usize,
 }
 
 impl<R: Clone> Clone for OptionRegion<R> {
     fn clone(&self) -> Self {
         Self {
             inner: self.inner.clone(),
+            null_count: self.null_count,
+            valid_count: self.valid_count,
         }
     }
 
     fn clone_from(&mut self, source: &Self) {
         self.inner.clone_from(&source.inner);
+        self.null_count = source.null_count;
+        self.valid_count = source.valid_count;
+    }
+}
+
+impl<R> OptionRegion<R> {
+    /// Returns the number of `None`s pushed since the region was created or last cleared.
+    ///
+    /// The region does not know which of its items are still referenced, so the count
+    /// includes items that a [`FlatStack`](crate::FlatStack) removed without clearing the
+    /// region.
+    #[inline]
+    #[must_use]
+    pub fn null_count(&self) -> usize {
+        self.null_count
+    }
+
+    /// Returns the number of `Some`s pushed since the region was created or last cleared.
+    #[inline]
+    #[must_use]
+    pub fn valid_count(&self) -> usize {
+        self.valid_count
+    }
+
+    /// Counts a pushed option.
+    #[inline]
+    fn count(&mut self, is_some: bool) {
+        if is_some {
+            self.valid_count += 1;
+        } else {
+            self.null_count += 1;
+        }
     }
 }
 
@@ -56,6 +101,8 @@ impl<R: Region> Region for OptionRegion<R> {
     {
         Self {
             inner: R::merge_regions(regions.map(|r| &r.inner)),
+            null_count: 0,
+            valid_count: 0,
         }
     }
 
@@ -64,6 +111,12 @@ impl<R: Region> Region for OptionRegion<R> {
         index.map(|t| self.inner.index(t))
     }
 
+    /// Returns the storage size of the inner item, or zero for `None`.
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        index.map_or(0, |index| self.inner.item_storage_size(index))
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -73,9 +126,20 @@ impl<R: Region> Region for OptionRegion<R> {
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.inner.clear();
+        self.null_count = 0;
+        self.valid_count = 0;
     }
 
     #[inline]
@@ -124,16 +188,28 @@ where
 {
     #[inline]
     fn push(&mut self, item: Option<T>) -> <OptionRegion<TR> as Region>::Index {
+        self.count(item.is_some());
         item.map(|t| self.inner.push(t))
     }
 }
 
+impl<T, TR> CanMeasure<Option<T>> for OptionRegion<TR>
+where
+    TR: Region + CanMeasure<T>,
+{
+    #[inline]
+    fn measure(&self, item: &Option<T>) -> usize {
+        item.as_ref().map_or(0, |t| self.inner.measure(t))
+    }
+}
+
 impl<'a, T: 'a, TR> Push<&'a Option<T>> for OptionRegion<TR>
 where
     TR: Region + Push<&'a T>,
 {
     #[inline]
     fn push(&mut self, item: &'a Option<T>) -> <OptionRegion<TR> as Region>::Index {
+        self.count(item.is_some());
         item.as_ref().map(|t| self.inner.push(t))
     }
 }
@@ -167,9 +243,364 @@ where
     }
 }
 
+/// An index container for the indexes of an [`OptionRegion`], which stores discriminants in a
+/// bit each.
+///
+/// A `Vec<Option<I>>` stores the discriminant of each option next to the index, which often
+/// occupies as much space as another index due to alignment. This container stores the
+/// discriminants in a [`DiscriminantStore`], and the indexes in `S`, where it stores
+/// `I::default()` for each `None`.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::option::OptionIndex;
+/// use flatcontainer::{FlatStack, OptionRegion, StringRegion};
+/// let mut c = FlatStack::<OptionRegion<StringRegion>, OptionIndex<Vec<(usize, usize)>>>::default();
+///
+/// c.copy(Some("abc"));
+/// c.copy(None::<&str>);
+/// assert!(c.iter().eq([Some("abc"), None]));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OptionIndex<S> {
+    /// Discriminants, `1` for `Some` and `0` for `None`.
+    discriminants: DiscriminantStore<2>,
+    /// Indexes, with a default index for each `None`.
+    indices: S,
+}
+
+impl<S> OptionIndex<S> {
+    /// Returns the validity bitmap, which has a bit per option that is set for `Some`.
+    ///
+    /// Option `i` is bit `i % 64` of word `i / 64`, counting from the least significant bit.
+    /// Bits past the last option are unset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::index::IndexContainer;
+    /// use flatcontainer::impls::option::OptionIndex;
+    /// let mut d = <OptionIndex<Vec<u32>>>::default();
+    ///
+    /// d.extend([Some(1), None, Some(2)]);
+    /// assert_eq!([0b101], d.validity_bitmap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn validity_bitmap(&self) -> &[u64] {
+        self.discriminants.words()
+    }
+
+    /// Returns the number of `None`s.
+    #[inline]
+    #[must_use]
+    pub fn null_count(&self) -> usize {
+        self.discriminants.len() - self.valid_count()
+    }
+
+    /// Returns the number of `Some`s.
+    #[inline]
+    #[must_use]
+    pub fn valid_count(&self) -> usize {
+        self.validity_bitmap()
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+impl<T, S: Storage<T>> Storage<Option<T>> for OptionIndex<S> {
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            discriminants: DiscriminantStore::with_capacity(capacity),
+            indices: S::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.discriminants.reserve(additional);
+        self.indices.reserve(additional);
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.discriminants.try_reserve(additional)?;
+        self.indices.try_reserve(additional)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.discriminants.clear();
+        self.indices.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.discriminants.heap_size(&mut callback);
+        self.indices.heap_size(callback);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.discriminants.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.discriminants.is_empty()
+    }
+}
+
+impl<T: Default, S: IndexContainer<T>> IndexContainer<Option<T>> for OptionIndex<S> {
+    type Iter<'a> = OptionIndexIter<DiscriminantIter<'a, 2>, S::Iter<'a>> where Self: 'a;
+
+    #[inline]
+    fn index(&self, index: usize) -> Option<T> {
+        (self.discriminants.index(index) == 1).then(|| self.indices.index(index))
+    }
+
+    #[inline]
+    fn push(&mut self, item: Option<T>) {
+        self.discriminants.push(usize::from(item.is_some()));
+        self.indices.push(item.unwrap_or_default());
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Option<T>> {
+        let discriminant = self.discriminants.pop()?;
+        let index = self.indices.pop()?;
+        Some((discriminant == 1).then_some(index))
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.discriminants.truncate(len);
+        self.indices.truncate(len);
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = Option<T>>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        OptionIndexIter {
+            discriminants: self.discriminants.iter(),
+            indices: self.indices.iter(),
+        }
+    }
+}
+
+/// An iterator over the elements of an [`OptionIndex`].
+#[derive(Clone)]
+pub struct OptionIndexIter<D, I> {
+    /// Iterator over discriminants.
+    discriminants: D,
+    /// Iterator over indexes.
+    indices: I,
+}
+
+impl<D, I, T> Iterator for OptionIndexIter<D, I>
+where
+    D: Iterator<Item = usize>,
+    I: Iterator<Item = T>,
+{
+    type Item = Option<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let discriminant = self.discriminants.next()?;
+        let index = self.indices.next()?;
+        Some((discriminant == 1).then_some(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.discriminants.size_hint()
+    }
+}
+
+/// An index type with a value that a region never produces, which can represent `None`.
+///
+/// Only offset-based indexes have a niche: `(usize, usize)` is the `(start, end)` index of
+/// regions like [`OwnedRegion`](crate::OwnedRegion) and [`StringRegion`](crate::StringRegion),
+/// and `(usize::MAX, usize::MAX)` would require an allocation larger than the address space.
+/// Plain integers don't have a niche, because they are valid indexes of a
+/// [`MirrorRegion`](crate::MirrorRegion).
+pub trait Niche: Copy + PartialEq {
+    /// The value representing `None`.
+    const NICHE: Self;
+}
+
+impl Niche for (usize, usize) {
+    const NICHE: Self = (usize::MAX, usize::MAX);
+}
+
+/// An index container for the indexes of an [`OptionRegion`], which stores `None` as the
+/// [niche](Niche) of the index type.
+///
+/// Unlike [`OptionIndex`], this container needs no storage for discriminants, but it cannot
+/// store the niche as `Some`. Index types of inner regions that already have a niche known to
+/// the compiler, such as [`NonZeroU32`](std::num::NonZeroU32) for a
+/// [`MirrorRegion`](crate::MirrorRegion), don't need this container, because a
+/// `Vec<Option<I>>` stores their options without a discriminant.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::option::NicheIndex;
+/// use flatcontainer::{FlatStack, OptionRegion, StringRegion};
+/// let mut c = FlatStack::<OptionRegion<StringRegion>, NicheIndex<Vec<(usize, usize)>>>::default();
+///
+/// c.copy(Some("abc"));
+/// c.copy(None::<&str>);
+/// assert!(c.iter().eq([Some("abc"), None]));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NicheIndex<S> {
+    /// Indexes, with the niche for each `None`.
+    indices: S,
+}
+
+impl<T, S: Storage<T>> Storage<Option<T>> for NicheIndex<S> {
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            indices: S::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.indices.reserve(additional);
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.indices.try_reserve(additional)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.indices.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.indices.heap_size(callback);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+impl<T: Niche, S: IndexContainer<T>> IndexContainer<Option<T>> for NicheIndex<S> {
+    type Iter<'a> = NicheIndexIter<S::Iter<'a>> where Self: 'a;
+
+    #[inline]
+    fn index(&self, index: usize) -> Option<T> {
+        let index = self.indices.index(index);
+        (index != T::NICHE).then_some(index)
+    }
+
+    /// Accepts a newly pushed element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element is the niche as `Some`.
+    #[inline]
+    fn push(&mut self, item: Option<T>) {
+        assert!(
+            item != Some(T::NICHE),
+            "NicheIndex: cannot store the niche as `Some`"
+        );
+        self.indices.push(item.unwrap_or(T::NICHE));
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Option<T>> {
+        self.indices
+            .pop()
+            .map(|index| (index != T::NICHE).then_some(index))
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.indices.truncate(len);
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = Option<T>>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        NicheIndexIter {
+            indices: self.indices.iter(),
+        }
+    }
+}
+
+/// An iterator over the elements of a [`NicheIndex`].
+#[derive(Clone)]
+pub struct NicheIndexIter<I> {
+    /// Iterator over indexes.
+    indices: I,
+}
+
+impl<I, T> Iterator for NicheIndexIter<I>
+where
+    I: Iterator<Item = T>,
+    T: Niche,
+{
+    type Item = Option<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices
+            .next()
+            .map(|index| (index != T::NICHE).then_some(index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{MirrorRegion, OwnedRegion, Region, ReserveItems};
+    use std::num::NonZeroU32;
+
+    use crate::{FlatStack, MirrorRegion, OwnedRegion, Region, ReserveItems, StringRegion};
 
     use super::*;
 
@@ -191,4 +622,99 @@ mod tests {
         });
         assert!(cap > 0);
     }
+
+    #[test]
+    fn test_null_count() {
+        let items: Vec<_> = (0..100u32).map(|i| (i % 3 != 0).then_some(i)).collect();
+        let mut c = FlatStack::<OptionRegion<MirrorRegion<u32>>, OptionIndex<Vec<u32>>>::default();
+        for item in &items {
+            c.copy(item);
+        }
+        let nulls = items.iter().filter(|item| item.is_none()).count();
+        assert_eq!(34, nulls);
+        assert_eq!(
+            (nulls, 100 - nulls),
+            (c.region.null_count(), c.region.valid_count())
+        );
+        assert_eq!(
+            (nulls, 100 - nulls),
+            (c.indices.null_count(), c.indices.valid_count())
+        );
+        assert_eq!(2, c.indices.validity_bitmap().len());
+        assert_eq!(0b110, c.indices.validity_bitmap()[0] & 0b111);
+
+        // The index counts what remains after truncating, the region what it absorbed.
+        c.truncate(3);
+        assert_eq!((1, 2), (c.indices.null_count(), c.indices.valid_count()));
+        assert_eq!(nulls, c.region.null_count());
+
+        c.clear();
+        assert_eq!((0, 0), (c.region.null_count(), c.region.valid_count()));
+    }
+
+    #[test]
+    fn test_option_index() {
+        let mut c =
+            FlatStack::<OptionRegion<StringRegion>, OptionIndex<Vec<(usize, usize)>>>::default();
+        let items: Vec<_> = (0..10_000)
+            .map(|i| (i % 3 != 0).then(|| i.to_string()))
+            .collect();
+        for item in &items {
+            c.copy(item);
+        }
+        assert_eq!(items.len(), c.len());
+        assert!(items.iter().map(|item| item.as_deref()).eq(c.iter()));
+        assert_eq!(Some("10"), c.get(10));
+        assert_eq!(None, c.get(9_999));
+
+        // One bit per discriminant, rounded up to words.
+        let mut size = 0;
+        c.indices.discriminants.heap_size(|siz, _| size += siz);
+        assert_eq!((items.len() + 63) / 64 * 8, size);
+        assert!(size <= items.len() / 8 + 8);
+    }
+
+    #[test]
+    fn test_niche_index() {
+        let mut c =
+            FlatStack::<OptionRegion<StringRegion>, NicheIndex<Vec<(usize, usize)>>>::default();
+        let items: Vec<_> = (0..1_000)
+            .map(|i| (i % 3 != 0).then(|| i.to_string()))
+            .collect();
+        for item in &items {
+            c.copy(item);
+        }
+        assert!(items.iter().map(|item| item.as_deref()).eq(c.iter()));
+        assert_eq!(Some("10"), c.get(10));
+        assert_eq!(None, c.get(999));
+
+        // No storage beyond the indexes themselves.
+        let mut size = 0;
+        c.indices.heap_size(|siz, _| size += siz);
+        assert_eq!(items.len() * std::mem::size_of::<(usize, usize)>(), size);
+    }
+
+    #[test]
+    fn test_non_zero() {
+        let mut c = FlatStack::<OptionRegion<MirrorRegion<NonZeroU32>>>::default();
+        let items: Vec<_> = (0..100).map(NonZeroU32::new).collect();
+        for item in &items {
+            c.copy(item);
+        }
+        assert!(items.clone().into_iter().eq(c.iter()));
+        assert_eq!(None, c.get(0));
+        assert_eq!(NonZeroU32::new(7), c.get(7));
+
+        // The compiler stores `None` in the niche of `NonZeroU32`, without a discriminant.
+        let mut size = 0;
+        Storage::heap_size(&c.indices, |siz, _| size += siz);
+        assert_eq!(items.len() * std::mem::size_of::<NonZeroU32>(), size);
+    }
+
+    #[test]
+    #[should_panic(expected = "NicheIndex: cannot store the niche as `Some`")]
+    fn test_niche_as_some() {
+        let mut c = NicheIndex::<Vec<(usize, usize)>>::default();
+        c.push(Some((usize::MAX, usize::MAX)));
+    }
 }
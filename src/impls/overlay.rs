@@ -0,0 +1,513 @@
+//! A region that stores enums whose variants share fields.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, Push, Region, ReserveItems};
+
+/// Describes how to store an enum in an [`OverlayEnumRegion`].
+///
+/// The descriptor splits each variant into a discriminant, the fields shared by all variants,
+/// and the remaining variant-specific fields. It reassembles the variant from the same parts.
+/// Shared fields are stored in one region independently of the variant, and the discriminant
+/// selects how to interpret them.
+pub trait OverlayDescriptor {
+    /// The enum type.
+    type Owned;
+    /// Region for the fields that all variants have in common.
+    type Shared: Region + 'static;
+    /// Region for the fields specific to a variant.
+    type Rest: Region + 'static;
+
+    /// Splits `item` into its discriminant, shared fields and variant-specific fields.
+    #[allow(clippy::type_complexity)]
+    fn split<'a>(
+        item: &'a Self::Owned,
+    ) -> (
+        u8,
+        <Self::Shared as Region>::ReadItem<'a>,
+        <Self::Rest as Region>::ReadItem<'a>,
+    );
+
+    /// Reassembles the variant with `discriminant` from its shared and variant-specific fields.
+    fn assemble<'a>(
+        discriminant: u8,
+        shared: <Self::Shared as Region>::ReadItem<'a>,
+        rest: <Self::Rest as Region>::ReadItem<'a>,
+    ) -> Self::Owned;
+}
+
+/// A region for enums whose variants share fields, described by an [`OverlayDescriptor`].
+///
+/// Storing each variant in its own region, as [`ResultRegion`](crate::ResultRegion) does,
+/// duplicates the regions for fields that several variants have in common. This region stores
+/// shared fields once, and the variant-specific fields in a second region. The index is the
+/// discriminant and the indexes into both regions.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::overlay::{OverlayDescriptor, OverlayEnumRegion};
+/// use flatcontainer::{IntoOwned, MirrorRegion, OptionRegion, Push, Region, StringRegion};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Msg {
+///     Text(String),
+///     Error(String, u16),
+/// }
+///
+/// struct MsgDescriptor;
+///
+/// impl OverlayDescriptor for MsgDescriptor {
+///     type Owned = Msg;
+///     type Shared = StringRegion;
+///     type Rest = OptionRegion<MirrorRegion<u16>>;
+///
+///     fn split(item: &Msg) -> (u8, &str, Option<u16>) {
+///         match item {
+///             Msg::Text(text) => (0, text, None),
+///             Msg::Error(text, code) => (1, text, Some(*code)),
+///         }
+///     }
+///
+///     fn assemble(discriminant: u8, text: &str, code: Option<u16>) -> Msg {
+///         match (discriminant, code) {
+///             (0, None) => Msg::Text(text.to_string()),
+///             (1, Some(code)) => Msg::Error(text.to_string(), code),
+///             _ => unreachable!("Invalid discriminant"),
+///         }
+///     }
+/// }
+///
+/// let mut r = <OverlayEnumRegion<MsgDescriptor>>::default();
+/// let index = r.push(&Msg::Error("Not found".to_string(), 404));
+///
+/// let item = r.index(index);
+/// assert_eq!(1, item.discriminant());
+/// assert_eq!("Not found", item.shared());
+/// assert_eq!(Msg::Error("Not found".to_string(), 404), item.into_owned());
+/// ```
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound = "
+            D::Shared: Serialize + for<'a> Deserialize<'a>,
+            D::Rest: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct OverlayEnumRegion<D: OverlayDescriptor> {
+    /// Fields shared by all variants.
+    shared: D::Shared,
+    /// Variant-specific fields.
+    rest: D::Rest,
+}
+
+impl<D> Debug for OverlayEnumRegion<D>
+where
+    D: OverlayDescriptor,
+    D::Shared: Debug,
+    D::Rest: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OverlayEnumRegion")
+            .field("shared", &self.shared)
+            .field("rest", &self.rest)
+            .finish()
+    }
+}
+
+impl<D> Clone for OverlayEnumRegion<D>
+where
+    D: OverlayDescriptor,
+    D::Shared: Clone,
+    D::Rest: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+            rest: self.rest.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.shared.clone_from(&source.shared);
+        self.rest.clone_from(&source.rest);
+    }
+}
+
+impl<D: OverlayDescriptor> Default for OverlayEnumRegion<D> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            shared: D::Shared::default(),
+            rest: D::Rest::default(),
+        }
+    }
+}
+
+impl<D: OverlayDescriptor> Region for OverlayEnumRegion<D> {
+    type Owned = D::Owned;
+    type ReadItem<'a> = ReadOverlay<'a, D> where Self: 'a;
+    type Index = (u8, <D::Shared as Region>::Index, <D::Rest as Region>::Index);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            shared: D::Shared::merge_regions(regions.clone().map(|r| &r.shared)),
+            rest: D::Rest::merge_regions(regions.map(|r| &r.rest)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (discriminant, shared, rest): Self::Index) -> Self::ReadItem<'_> {
+        ReadOverlay {
+            discriminant,
+            shared: self.shared.index(shared),
+            rest: self.rest.index(rest),
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.shared
+            .reserve_regions(regions.clone().map(|r| &r.shared));
+        self.rest.reserve_regions(regions.map(|r| &r.rest));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.shared
+            .try_reserve_regions(regions.clone().map(|r| &r.shared))?;
+        self.rest.try_reserve_regions(regions.map(|r| &r.rest))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.shared.clear();
+        self.rest.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.shared.heap_size(&mut callback);
+        self.rest.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        ReadOverlay {
+            discriminant: item.discriminant,
+            shared: D::Shared::reborrow(item.shared),
+            rest: D::Rest::reborrow(item.rest),
+        }
+    }
+}
+
+/// A variant read from an [`OverlayEnumRegion`].
+pub struct ReadOverlay<'a, D: OverlayDescriptor> {
+    discriminant: u8,
+    shared: <D::Shared as Region>::ReadItem<'a>,
+    rest: <D::Rest as Region>::ReadItem<'a>,
+}
+
+impl<'a, D> ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+{
+    /// Returns the discriminant of the variant.
+    #[inline]
+    #[must_use]
+    pub fn discriminant(&self) -> u8 {
+        self.discriminant
+    }
+
+    /// Returns the fields shared by all variants.
+    #[inline]
+    #[must_use]
+    pub fn shared(&self) -> <D::Shared as Region>::ReadItem<'a>
+    where
+        <D::Shared as Region>::ReadItem<'a>: Copy,
+    {
+        self.shared
+    }
+
+    /// Returns the variant-specific fields.
+    #[inline]
+    #[must_use]
+    pub fn rest(&self) -> <D::Rest as Region>::ReadItem<'a>
+    where
+        <D::Rest as Region>::ReadItem<'a>: Copy,
+    {
+        self.rest
+    }
+}
+
+impl<'a, D> Clone for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+    <D::Shared as Region>::ReadItem<'a>: Clone,
+    <D::Rest as Region>::ReadItem<'a>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            discriminant: self.discriminant,
+            shared: self.shared.clone(),
+            rest: self.rest.clone(),
+        }
+    }
+}
+
+impl<'a, D> Copy for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+    <D::Shared as Region>::ReadItem<'a>: Copy,
+    <D::Rest as Region>::ReadItem<'a>: Copy,
+{
+}
+
+impl<'a, D> Debug for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+    <D::Shared as Region>::ReadItem<'a>: Debug,
+    <D::Rest as Region>::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadOverlay")
+            .field("discriminant", &self.discriminant)
+            .field("shared", &self.shared)
+            .field("rest", &self.rest)
+            .finish()
+    }
+}
+
+impl<'a, D> PartialEq for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+    <D::Shared as Region>::ReadItem<'a>: PartialEq,
+    <D::Rest as Region>::ReadItem<'a>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.discriminant == other.discriminant
+            && self.shared == other.shared
+            && self.rest == other.rest
+    }
+}
+
+impl<'a, D> Eq for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+    <D::Shared as Region>::ReadItem<'a>: Eq,
+    <D::Rest as Region>::ReadItem<'a>: Eq,
+{
+}
+
+impl<'a, D> IntoOwned<'a> for ReadOverlay<'a, D>
+where
+    D: OverlayDescriptor,
+{
+    type Owned = D::Owned;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        D::assemble(self.discriminant, self.shared, self.rest)
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self.into_owned();
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        let (discriminant, shared, rest) = D::split(owned);
+        Self {
+            discriminant,
+            shared,
+            rest,
+        }
+    }
+}
+
+impl<'a, D> Push<&'a D::Owned> for OverlayEnumRegion<D>
+where
+    D: OverlayDescriptor,
+    D::Shared: Push<<D::Shared as Region>::ReadItem<'a>>,
+    D::Rest: Push<<D::Rest as Region>::ReadItem<'a>>,
+{
+    #[inline]
+    fn push(&mut self, item: &'a D::Owned) -> <OverlayEnumRegion<D> as Region>::Index {
+        self.push(ReadOverlay::<D>::borrow_as(item))
+    }
+}
+
+impl<'a, D> Push<ReadOverlay<'a, D>> for OverlayEnumRegion<D>
+where
+    D: OverlayDescriptor,
+    D::Shared: Push<<D::Shared as Region>::ReadItem<'a>>,
+    D::Rest: Push<<D::Rest as Region>::ReadItem<'a>>,
+{
+    #[inline]
+    fn push(&mut self, item: ReadOverlay<'a, D>) -> <OverlayEnumRegion<D> as Region>::Index {
+        (
+            item.discriminant,
+            self.shared.push(item.shared),
+            self.rest.push(item.rest),
+        )
+    }
+}
+
+impl<'a, D> ReserveItems<&'a D::Owned> for OverlayEnumRegion<D>
+where
+    D: OverlayDescriptor,
+    D::Shared: ReserveItems<<D::Shared as Region>::ReadItem<'a>>,
+    D::Rest: ReserveItems<<D::Rest as Region>::ReadItem<'a>>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a D::Owned> + Clone,
+    {
+        self.shared
+            .reserve_items(items.clone().map(|item| D::split(item).1));
+        self.rest.reserve_items(items.map(|item| D::split(item).2));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::tuple::TupleABRegion;
+    use crate::{FlatStack, MirrorRegion, OptionRegion, ResultRegion, StringRegion};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Msg {
+        Text(String),
+        Error(String, u16),
+    }
+
+    struct MsgDescriptor;
+
+    impl OverlayDescriptor for MsgDescriptor {
+        type Owned = Msg;
+        type Shared = StringRegion;
+        type Rest = OptionRegion<MirrorRegion<u16>>;
+
+        fn split(item: &Msg) -> (u8, &str, Option<u16>) {
+            match item {
+                Msg::Text(text) => (0, text, None),
+                Msg::Error(text, code) => (1, text, Some(*code)),
+            }
+        }
+
+        fn assemble(discriminant: u8, text: &str, code: Option<u16>) -> Msg {
+            match (discriminant, code) {
+                (0, None) => Msg::Text(text.to_string()),
+                (1, Some(code)) => Msg::Error(text.to_string(), code),
+                _ => unreachable!("Invalid discriminant {discriminant}"),
+            }
+        }
+    }
+
+    fn messages() -> Vec<Msg> {
+        (0..100)
+            .map(|i| match i % 3 {
+                0 => Msg::Error(format!("error {i}"), 500 + i),
+                _ => Msg::Text(format!("text {i}")),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let messages = messages();
+        let mut r = <OverlayEnumRegion<MsgDescriptor>>::default();
+        let indices: Vec<_> = messages.iter().map(|msg| r.push(msg)).collect();
+
+        for (msg, index) in messages.iter().zip(indices) {
+            let item = r.index(index);
+            assert_eq!(*msg, item.into_owned());
+            assert_eq!(ReadOverlay::borrow_as(msg), item);
+            match msg {
+                Msg::Text(text) => {
+                    assert_eq!(0, item.discriminant());
+                    assert_eq!(text, item.shared());
+                    assert_eq!(None, item.rest());
+                }
+                Msg::Error(text, code) => {
+                    assert_eq!(1, item.discriminant());
+                    assert_eq!(text, item.shared());
+                    assert_eq!(Some(*code), item.rest());
+                }
+            }
+
+            let mut owned = Msg::Text(String::new());
+            item.clone_onto(&mut owned);
+            assert_eq!(*msg, owned);
+        }
+    }
+
+    #[test]
+    fn test_push_read_item() {
+        let mut r = <OverlayEnumRegion<MsgDescriptor>>::default();
+        let index = r.push(&Msg::Error("Teapot".to_string(), 418));
+
+        let mut merged = OverlayEnumRegion::merge_regions(std::iter::once(&r));
+        let index = merged.push(r.index(index));
+        assert_eq!(
+            Msg::Error("Teapot".to_string(), 418),
+            merged.index(index).into_owned()
+        );
+    }
+
+    #[test]
+    fn test_storage() {
+        let messages = messages();
+
+        let mut overlay = FlatStack::<OverlayEnumRegion<MsgDescriptor>>::default();
+        overlay.reserve_items(messages.iter());
+        for msg in &messages {
+            overlay.copy(msg);
+        }
+        assert!(messages
+            .iter()
+            .cloned()
+            .eq(overlay.iter().map(IntoOwned::into_owned)));
+
+        // A region per variant, where both variants store their string separately.
+        type Separate = ResultRegion<StringRegion, TupleABRegion<StringRegion, MirrorRegion<u16>>>;
+        let mut separate = FlatStack::<Separate>::default();
+        for msg in &messages {
+            match msg {
+                Msg::Text(text) => separate.copy(Ok::<_, (&str, u16)>(text.as_str())),
+                Msg::Error(text, code) => separate.copy(Err::<&str, _>((text.as_str(), *code))),
+            }
+        }
+
+        let (mut overlay_size, mut separate_size) = (0, 0);
+        overlay.heap_size(|siz, _| overlay_size += siz);
+        separate.heap_size(|siz, _| separate_size += siz);
+        assert!(
+            overlay_size < separate_size,
+            "{overlay_size} not less than {separate_size}"
+        );
+    }
+}
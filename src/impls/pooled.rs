@@ -0,0 +1,450 @@
+//! Index containers that allocate from a shared pool.
+
+use std::cell::RefCell;
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+use std::rc::Rc;
+
+use crate::impls::index::IndexContainer;
+use crate::impls::storage::Storage;
+
+/// The number of elements in a block of a pool.
+const BLOCK_LEN: usize = 16;
+
+/// A pool of storage for [`PooledIndex`] containers.
+///
+/// The pool hands out blocks of 16 elements from a single allocation, and recycles
+/// the blocks of dropped containers. Many small containers drawing from one pool allocate
+/// only when the pool grows, instead of once per container. Cloning the pool clones a handle
+/// to the same storage.
+///
+/// The pool is not thread-safe, which makes its containers neither [`Send`] nor [`Sync`].
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::index::IndexContainer;
+/// use flatcontainer::impls::pooled::{IndexPool, PooledIndex};
+/// let pool = IndexPool::default();
+///
+/// let mut a = PooledIndex::new_in(&pool);
+/// let mut b = PooledIndex::new_in(&pool);
+/// a.push(1);
+/// b.push(2);
+/// assert_eq!((1, 2), (a.index(0), b.index(0)));
+/// ```
+pub struct IndexPool<T> {
+    inner: Rc<RefCell<PoolInner<T>>>,
+}
+
+/// The storage of an [`IndexPool`].
+struct PoolInner<T> {
+    /// Elements of all blocks.
+    values: Vec<T>,
+    /// Offsets of blocks returned by dropped containers.
+    free: Vec<usize>,
+}
+
+impl<T> IndexPool<T> {
+    /// Returns `true` if `other` is a handle to the same pool.
+    #[inline]
+    #[must_use]
+    pub fn same_pool(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Returns the number of blocks the pool has handed out or recycled.
+    #[must_use]
+    pub fn blocks(&self) -> usize {
+        self.inner.borrow().values.len() / BLOCK_LEN
+    }
+
+    /// Calls `callback` with the size and capacity of the pool's storage in bytes.
+    pub fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let inner = self.inner.borrow();
+        let size_of_t = std::mem::size_of::<T>();
+        callback(
+            inner.values.len() * size_of_t,
+            inner.values.capacity() * size_of_t,
+        );
+        let size_of_offset = std::mem::size_of::<usize>();
+        callback(
+            inner.free.len() * size_of_offset,
+            inner.free.capacity() * size_of_offset,
+        );
+    }
+
+    /// Reserves space for `additional` elements in the pool's storage.
+    fn try_reserve(&self, additional: usize) -> Result<(), TryReserveError> {
+        self.inner.borrow_mut().values.try_reserve(additional)
+    }
+
+    /// Returns the offset of an unused block, growing the pool if no block is free.
+    fn allocate(&self) -> usize
+    where
+        T: Copy + Default,
+    {
+        let mut inner = self.inner.borrow_mut();
+        inner.free.pop().unwrap_or_else(|| {
+            let offset = inner.values.len();
+            inner.values.resize(offset + BLOCK_LEN, T::default());
+            offset
+        })
+    }
+}
+
+impl<T> Default for IndexPool<T> {
+    fn default() -> Self {
+        Self {
+            inner: Rc::new(RefCell::new(PoolInner {
+                values: Vec::new(),
+                free: Vec::new(),
+            })),
+        }
+    }
+}
+
+impl<T> Clone for IndexPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Rc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> Debug for IndexPool<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IndexPool")
+            .field("blocks", &self.blocks())
+            .finish_non_exhaustive()
+    }
+}
+
+/// An index container that stores its elements in blocks of an [`IndexPool`].
+///
+/// Use it as the offset container of regions that are small and numerous, such as a
+/// [`SliceRegion`](crate::SliceRegion) per group, and construct them with
+/// [`SliceRegion::with_index_container`](crate::SliceRegion::with_index_container). A
+/// container that fits in one block allocates nothing of its own.
+///
+/// A default container uses a pool of its own, as do containers created by
+/// [`Storage::with_capacity`]. Merging containers uses the pool of the first container. The
+/// heap size of a container reports its blocks, but not the pool's spare capacity.
+pub struct PooledIndex<T: Copy + Default> {
+    /// The pool the blocks belong to.
+    pool: IndexPool<T>,
+    /// The offset of the first block, if any.
+    first: Option<usize>,
+    /// The offsets of further blocks.
+    rest: Vec<usize>,
+    /// The number of elements.
+    len: usize,
+}
+
+impl<T: Copy + Default> PooledIndex<T> {
+    /// Constructs an empty container that allocates from `pool`.
+    #[must_use]
+    pub fn new_in(pool: &IndexPool<T>) -> Self {
+        Self {
+            pool: pool.clone(),
+            first: None,
+            rest: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the pool of this container.
+    #[inline]
+    #[must_use]
+    pub fn pool(&self) -> &IndexPool<T> {
+        &self.pool
+    }
+
+    /// Returns the number of blocks of this container.
+    #[inline]
+    fn blocks(&self) -> usize {
+        usize::from(self.first.is_some()) + self.rest.len()
+    }
+
+    /// Returns the offset in the pool of the element at `index`.
+    #[inline]
+    fn offset(&self, index: usize) -> usize {
+        let block = match index / BLOCK_LEN {
+            0 => self.first.unwrap(),
+            block => self.rest[block - 1],
+        };
+        block + index % BLOCK_LEN
+    }
+
+    /// Allocates blocks until the container can hold `capacity` elements.
+    fn grow(&mut self, capacity: usize) {
+        while self.blocks() * BLOCK_LEN < capacity {
+            let block = self.pool.allocate();
+            if self.first.is_none() {
+                self.first = Some(block);
+            } else {
+                self.rest.push(block);
+            }
+        }
+    }
+}
+
+impl<T: Copy + Default> Default for PooledIndex<T> {
+    fn default() -> Self {
+        Self::new_in(&IndexPool::default())
+    }
+}
+
+impl<T: Copy + Default> Clone for PooledIndex<T> {
+    /// Clones the elements into new blocks of the same pool.
+    fn clone(&self) -> Self {
+        let mut clone = Self::new_in(&self.pool);
+        clone.extend(self.iter());
+        clone
+    }
+}
+
+impl<T: Copy + Default + Debug> Debug for PooledIndex<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Copy + Default> Drop for PooledIndex<T> {
+    /// Returns the blocks to the pool.
+    fn drop(&mut self) {
+        let mut inner = self.pool.inner.borrow_mut();
+        if let Some(first) = self.first.take() {
+            inner.free.push(first);
+        }
+        inner.free.append(&mut self.rest);
+    }
+}
+
+impl<T: Copy + Default> Storage<T> for PooledIndex<T> {
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        let mut container = Self::default();
+        container.grow(capacity);
+        container
+    }
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let mut container = regions
+            .clone()
+            .next()
+            .map_or_else(Self::default, |first| Self::new_in(&first.pool));
+        container.grow(regions.map(Self::len).sum());
+        container
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.grow(self.len + additional);
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let capacity = self.len.saturating_add(additional);
+        let blocks = (capacity / BLOCK_LEN + usize::from(capacity % BLOCK_LEN != 0))
+            .saturating_sub(self.blocks());
+        self.rest.try_reserve(blocks)?;
+        self.pool.try_reserve(blocks.saturating_mul(BLOCK_LEN))?;
+        self.grow(capacity);
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_t = std::mem::size_of::<T>();
+        callback(self.len * size_of_t, self.blocks() * BLOCK_LEN * size_of_t);
+        let size_of_offset = std::mem::size_of::<usize>();
+        callback(
+            self.rest.len() * size_of_offset,
+            self.rest.capacity() * size_of_offset,
+        );
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<T: Copy + Default> IndexContainer<T> for PooledIndex<T> {
+    type Iter<'a> = PooledIndexIter<'a, T> where Self: 'a;
+
+    /// Returns the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    fn index(&self, index: usize) -> T {
+        assert!(
+            index < self.len,
+            "PooledIndex: index {index} out of bounds for length {}",
+            self.len
+        );
+        self.pool.inner.borrow().values[self.offset(index)]
+    }
+
+    #[inline]
+    fn push(&mut self, item: T) {
+        self.grow(self.len + 1);
+        let offset = self.offset(self.len);
+        self.pool.inner.borrow_mut().values[offset] = item;
+        self.len += 1;
+    }
+
+    /// Removes the last element and returns it, or `None` if the container is empty. Retains
+    /// the blocks of the container.
+    #[inline]
+    fn pop(&mut self) -> Option<T> {
+        let index = self.len.checked_sub(1)?;
+        let item = self.index(index);
+        self.len = index;
+        Some(item)
+    }
+
+    /// Shortens the container to `len` elements. Retains the blocks of the container.
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        PooledIndexIter {
+            container: self,
+            position: 0,
+        }
+    }
+}
+
+/// An iterator over the elements of a [`PooledIndex`].
+#[derive(Clone)]
+pub struct PooledIndexIter<'a, T: Copy + Default> {
+    /// The container to iterate.
+    container: &'a PooledIndex<T>,
+    /// The position of the next element.
+    position: usize,
+}
+
+impl<'a, T: Copy + Default> Iterator for PooledIndexIter<'a, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        (self.position < self.container.len).then(|| {
+            self.position += 1;
+            self.container.index(self.position - 1)
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.container.len - self.position;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a, T: Copy + Default> ExactSizeIterator for PooledIndexIter<'a, T> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MirrorRegion, Push, Region, SliceRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_blocks() {
+        let pool = IndexPool::default();
+        let mut a = PooledIndex::new_in(&pool);
+        let mut b = PooledIndex::new_in(&pool);
+        for i in 0..40 {
+            a.push(i);
+            b.push(i * 2);
+        }
+        assert_eq!(6, pool.blocks());
+        assert!((0..40).eq(a.iter()));
+        assert!((0..40).map(|i| i * 2).eq(b.iter()));
+        assert_eq!(40, b.iter().len());
+
+        // Clearing retains blocks, dropping returns them to the pool.
+        a.clear();
+        a.extend(0..3);
+        drop(b);
+        let mut c = PooledIndex::new_in(&pool);
+        c.extend(0..40);
+        assert_eq!(6, pool.blocks());
+        assert!((0..3).eq(a.iter()));
+        assert!(c.clone().iter().eq(0..40));
+    }
+
+    #[test]
+    fn test_slice_regions() {
+        let pool = IndexPool::default();
+        let mut regions: Vec<_> = (0..100)
+            .map(|_| {
+                <SliceRegion<MirrorRegion<u32>, _>>::with_index_container(PooledIndex::new_in(
+                    &pool,
+                ))
+            })
+            .collect();
+        let indices: Vec<_> = regions
+            .iter_mut()
+            .enumerate()
+            .map(|(i, r)| r.push([i as u32, 1, 2].as_slice()))
+            .collect();
+        for (i, (r, index)) in regions.iter().zip(indices).enumerate() {
+            assert!(r.index(index).iter().eq([i as u32, 1, 2]));
+        }
+        assert_eq!(100, pool.blocks());
+    }
+
+    #[test]
+    fn test_merge() {
+        let pool = IndexPool::default();
+        let mut a = PooledIndex::new_in(&pool);
+        a.extend([1, 2, 3]);
+        let merged = PooledIndex::merge_regions(std::iter::once(&a));
+        assert!(merged.pool().same_pool(&pool));
+        assert!(merged.is_empty());
+        assert_eq!(2, pool.blocks());
+    }
+
+    #[test]
+    #[should_panic(expected = "PooledIndex: index 1 out of bounds for length 1")]
+    fn test_index_out_of_bounds() {
+        let mut c = PooledIndex::default();
+        c.push(1);
+        let _ = c.index(1);
+    }
+}
@@ -0,0 +1,317 @@
+//! A region that stores floats quantized to a few bits.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// A region for `f32` values in a known range, which stores each value lossily in `BITS` bits.
+///
+/// The region maps the range from `min` to `max` onto the integers from zero to
+/// `2^BITS - 1`, stores the integer closest to each pushed value, and reads back the value
+/// the integer represents. Reading a value returns it with an error of at most
+/// [`max_error`](QuantizedFloatRegion::max_error), which is half the distance between
+/// adjacent representable values, up to the rounding of `f32`. The region clamps values
+/// outside the range to the range, and stores `NaN` as `min`.
+///
+/// The region packs the integers into `u64` words. An integer does not span two words, so
+/// widths that do not divide 64 leave the high bits of each word unused. `BITS` must be
+/// between 1 and 32. The index is the position of the value in the region.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::quantized::QuantizedFloatRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = QuantizedFloatRegion::<8>::new(-1.0, 1.0);
+///
+/// let index = r.push(0.3);
+/// assert!((r.index(index) - 0.3).abs() <= r.max_error());
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct QuantizedFloatRegion<const BITS: usize> {
+    /// The smallest representable value.
+    min: f32,
+    /// The largest representable value.
+    max: f32,
+    /// Packed integers.
+    words: Vec<u64>,
+    /// The number of values.
+    len: usize,
+}
+
+impl<const BITS: usize> QuantizedFloatRegion<BITS> {
+    /// The number of integers per word.
+    const PER_WORD: usize = 64 / BITS;
+
+    /// The largest integer.
+    const LEVELS: u32 = u32::MAX >> (32 - BITS);
+
+    /// Constructs an empty region for values from `min` to `max`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `BITS` is not between 1 and 32, or if `min` is not smaller than `max`.
+    #[must_use]
+    pub fn new(min: f32, max: f32) -> Self {
+        assert!(
+            (1..=32).contains(&BITS),
+            "QuantizedFloatRegion: {BITS} bits not between 1 and 32"
+        );
+        assert!(
+            min < max,
+            "QuantizedFloatRegion: empty range from {min} to {max}"
+        );
+        Self {
+            min,
+            max,
+            words: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns the smallest representable value.
+    #[inline]
+    #[must_use]
+    pub fn min(&self) -> f32 {
+        self.min
+    }
+
+    /// Returns the largest representable value.
+    #[inline]
+    #[must_use]
+    pub fn max(&self) -> f32 {
+        self.max
+    }
+
+    /// Returns the largest difference between a value in the range and the value the region
+    /// reads back.
+    #[inline]
+    #[must_use]
+    pub fn max_error(&self) -> f32 {
+        (self.step() / 2.0) as f32
+    }
+
+    /// Returns the number of values in the region.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the region contains no values.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The distance between adjacent representable values.
+    #[inline]
+    fn step(&self) -> f64 {
+        (f64::from(self.max) - f64::from(self.min)) / f64::from(Self::LEVELS)
+    }
+
+    /// Returns the integer that represents `value`.
+    #[inline]
+    fn quantize(&self, value: f32) -> u64 {
+        if value.is_nan() {
+            return 0;
+        }
+        let level = ((f64::from(value) - f64::from(self.min)) / self.step()).round();
+        level.clamp(0.0, f64::from(Self::LEVELS)) as u64
+    }
+}
+
+impl<const BITS: usize> Default for QuantizedFloatRegion<BITS> {
+    /// Constructs an empty region for values from zero to one.
+    fn default() -> Self {
+        Self::new(0.0, 1.0)
+    }
+}
+
+impl<const BITS: usize> Region for QuantizedFloatRegion<BITS> {
+    type Owned = f32;
+    type ReadItem<'a> = f32;
+    /// The position of the value.
+    type Index = usize;
+
+    /// Constructs a region for the union of the ranges of `regions`. Pushing the values of
+    /// regions with a smaller range can add to their error.
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let mut merged = regions
+            .clone()
+            .map(|r| Self::new(r.min, r.max))
+            .reduce(|a, b| Self::new(a.min.min(b.min), a.max.max(b.max)))
+            .unwrap_or_default();
+        let len = regions.map(|r| r.len).sum::<usize>();
+        merged
+            .words
+            .reserve((len + Self::PER_WORD - 1) / Self::PER_WORD);
+        merged
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let word = self.words[index / Self::PER_WORD];
+        let shift = (index % Self::PER_WORD) * BITS;
+        let level = (word >> shift) & u64::from(Self::LEVELS);
+        (f64::from(self.min) + level as f64 * self.step()) as f32
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = self.len + regions.map(|r| r.len).sum::<usize>();
+        let words = (len + Self::PER_WORD - 1) / Self::PER_WORD;
+        self.words.reserve(words.saturating_sub(self.words.len()));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = self.len + regions.map(|r| r.len).sum::<usize>();
+        let words = (len + Self::PER_WORD - 1) / Self::PER_WORD;
+        self.words
+            .try_reserve(words.saturating_sub(self.words.len()))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.words.clear();
+        self.len = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_word = std::mem::size_of::<u64>();
+        callback(
+            self.words.len() * size_of_word,
+            self.words.capacity() * size_of_word,
+        );
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<const BITS: usize> Push<f32> for QuantizedFloatRegion<BITS> {
+    #[inline]
+    fn push(&mut self, item: f32) -> usize {
+        let index = self.len;
+        let shift = (index % Self::PER_WORD) * BITS;
+        if shift == 0 {
+            self.words.push(0);
+        }
+        *self.words.last_mut().unwrap() |= self.quantize(item) << shift;
+        self.len += 1;
+        index
+    }
+}
+
+impl<const BITS: usize> Push<&f32> for QuantizedFloatRegion<BITS> {
+    #[inline]
+    fn push(&mut self, item: &f32) -> usize {
+        self.push(*item)
+    }
+}
+
+impl<const BITS: usize, T> ReserveItems<T> for QuantizedFloatRegion<BITS> {
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        let len = self.len + items.count();
+        let words = (len + Self::PER_WORD - 1) / Self::PER_WORD;
+        self.words.reserve(words.saturating_sub(self.words.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_error_bound() {
+        let values: Vec<f32> = (0..=1000).map(|i| i as f32 / 1000.0).collect();
+        let mut c = FlatStack::<QuantizedFloatRegion<8>>::default();
+        for value in &values {
+            c.copy(value);
+        }
+        let max_error = c.region.max_error();
+        assert!((max_error - 0.5 / 255.0).abs() < 1e-6);
+        for (value, read) in values.iter().zip(c.iter()) {
+            // Values halfway between two levels can exceed the bound by a rounding error.
+            assert!(
+                (value - read).abs() <= max_error + f32::EPSILON,
+                "{value} read as {read}"
+            );
+        }
+        assert_eq!(0.0, c.get(0));
+        assert_eq!(1.0, c.get(1000));
+
+        // Eight values per word.
+        let mut size = 0;
+        c.region.heap_size(|siz, _| size += siz);
+        assert_eq!((values.len() + 7) / 8 * 8, size);
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut r = QuantizedFloatRegion::<3>::new(-2.0, 5.0);
+        let indices: Vec<_> = [-10.0, f32::NAN, 100.0, 0.0, 1.4]
+            .into_iter()
+            .map(|value| r.push(value))
+            .collect();
+        let read: Vec<_> = indices.into_iter().map(|index| r.index(index)).collect();
+        assert_eq!(vec![-2.0, -2.0, 5.0, 0.0, 1.0], read);
+
+        let merged = QuantizedFloatRegion::merge_regions(
+            [&r, &QuantizedFloatRegion::new(-3.0, 1.0)].into_iter(),
+        );
+        assert_eq!((-3.0, 5.0), (merged.min(), merged.max()));
+        assert!(merged.is_empty());
+    }
+
+    #[test]
+    fn test_widths() {
+        let mut r = QuantizedFloatRegion::<32>::default();
+        let index = r.push(0.123_456);
+        assert!((r.index(index) - 0.123_456).abs() <= r.max_error().max(f32::EPSILON));
+
+        let mut r = QuantizedFloatRegion::<1>::default();
+        let indices: Vec<_> = (0..100).map(|i| r.push((i % 2) as f32)).collect();
+        assert!(indices
+            .into_iter()
+            .map(|index| r.index(index))
+            .eq((0..100).map(|i| (i % 2) as f32)));
+        assert_eq!(100, r.len());
+    }
+
+    #[test]
+    #[should_panic(expected = "QuantizedFloatRegion: 0 bits not between 1 and 32")]
+    fn test_zero_bits() {
+        let _ = QuantizedFloatRegion::<0>::default();
+    }
+}
@@ -1,8 +1,14 @@
 //! A region that stores results.
 
+use std::collections::TryReserveError;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::impls::choose::CanMeasure;
+use crate::impls::discriminant::{DiscriminantIter, DiscriminantStore};
+use crate::impls::index::IndexContainer;
+use crate::impls::storage::Storage;
 use crate::{IntoOwned, Push, Region, RegionPreference, ReserveItems};
 
 impl<T: RegionPreference, E: RegionPreference> RegionPreference for Result<T, E> {
@@ -75,6 +81,14 @@ where
         }
     }
 
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        match index {
+            Ok(index) => self.oks.item_storage_size(index),
+            Err(index) => self.errs.item_storage_size(index),
+        }
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -85,6 +99,17 @@ where
         self.errs.reserve_regions(regions.map(|r| &r.errs));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.oks
+            .try_reserve_regions(regions.clone().map(|r| &r.oks))?;
+        self.errs.try_reserve_regions(regions.map(|r| &r.errs))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.oks.clear();
@@ -148,6 +173,20 @@ where
     }
 }
 
+impl<T, TC, E, EC> CanMeasure<Result<T, E>> for ResultRegion<TC, EC>
+where
+    TC: Region + CanMeasure<T>,
+    EC: Region + CanMeasure<E>,
+{
+    #[inline]
+    fn measure(&self, item: &Result<T, E>) -> usize {
+        match item {
+            Ok(t) => self.oks.measure(t),
+            Err(e) => self.errs.measure(e),
+        }
+    }
+}
+
 impl<'a, T: 'a, TC, E: 'a, EC> Push<&'a Result<T, E>> for ResultRegion<TC, EC>
 where
     TC: Region + Push<&'a T>,
@@ -194,9 +233,188 @@ where
     }
 }
 
+/// An index container for the indexes of a [`ResultRegion`] whose regions share an index
+/// type, which stores discriminants in a bit each.
+///
+/// A `Vec<Result<I, I>>` stores the discriminant of each result next to the index, which often
+/// occupies as much space as another index due to alignment. This container stores the
+/// discriminants in a [`DiscriminantStore`], and the indexes of both variants in `S`.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::result::ResultIndex;
+/// use flatcontainer::{FlatStack, ResultRegion, StringRegion};
+/// type R = ResultRegion<StringRegion, StringRegion>;
+/// let mut c = FlatStack::<R, ResultIndex<Vec<(usize, usize)>>>::default();
+///
+/// c.copy(Ok::<_, &str>("abc"));
+/// c.copy(Err::<&str, _>("error"));
+/// assert!(c.iter().eq([Ok("abc"), Err("error")]));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResultIndex<S> {
+    /// Discriminants, `1` for `Ok` and `0` for `Err`.
+    discriminants: DiscriminantStore<2>,
+    /// Indexes of both variants.
+    indices: S,
+}
+
+impl<S> ResultIndex<S> {
+    /// Returns the number of `Ok`s.
+    #[inline]
+    #[must_use]
+    pub fn ok_count(&self) -> usize {
+        self.discriminants
+            .words()
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of `Err`s.
+    #[inline]
+    #[must_use]
+    pub fn err_count(&self) -> usize {
+        self.discriminants.len() - self.ok_count()
+    }
+}
+
+impl<T, S: Storage<T>> Storage<Result<T, T>> for ResultIndex<S> {
+    #[inline]
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            discriminants: DiscriminantStore::with_capacity(capacity),
+            indices: S::with_capacity(capacity),
+        }
+    }
+
+    #[inline]
+    fn reserve(&mut self, additional: usize) {
+        self.discriminants.reserve(additional);
+        self.indices.reserve(additional);
+    }
+
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.discriminants.try_reserve(additional)?;
+        self.indices.try_reserve(additional)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.discriminants.clear();
+        self.indices.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.discriminants.heap_size(&mut callback);
+        self.indices.heap_size(callback);
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.discriminants.len()
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.discriminants.is_empty()
+    }
+}
+
+impl<T, S: IndexContainer<T>> IndexContainer<Result<T, T>> for ResultIndex<S> {
+    type Iter<'a> = ResultIndexIter<DiscriminantIter<'a, 2>, S::Iter<'a>> where Self: 'a;
+
+    #[inline]
+    fn index(&self, index: usize) -> Result<T, T> {
+        to_result(self.discriminants.index(index), self.indices.index(index))
+    }
+
+    #[inline]
+    fn push(&mut self, item: Result<T, T>) {
+        self.discriminants.push(usize::from(item.is_ok()));
+        self.indices.push(item.unwrap_or_else(|index| index));
+    }
+
+    #[inline]
+    fn pop(&mut self) -> Option<Result<T, T>> {
+        let discriminant = self.discriminants.pop()?;
+        let index = self.indices.pop()?;
+        Some(to_result(discriminant, index))
+    }
+
+    #[inline]
+    fn truncate(&mut self, len: usize) {
+        self.discriminants.truncate(len);
+        self.indices.truncate(len);
+    }
+
+    #[inline]
+    fn extend<I: IntoIterator<Item = Result<T, T>>>(&mut self, iter: I)
+    where
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        self.reserve(iter.len());
+        for item in iter {
+            self.push(item);
+        }
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        ResultIndexIter {
+            discriminants: self.discriminants.iter(),
+            indices: self.indices.iter(),
+        }
+    }
+}
+
+/// Combines a discriminant and an index into a result.
+#[inline]
+fn to_result<T>(discriminant: usize, index: T) -> Result<T, T> {
+    if discriminant == 1 {
+        Ok(index)
+    } else {
+        Err(index)
+    }
+}
+
+/// An iterator over the elements of a [`ResultIndex`].
+#[derive(Clone)]
+pub struct ResultIndexIter<D, I> {
+    /// Iterator over discriminants.
+    discriminants: D,
+    /// Iterator over indexes.
+    indices: I,
+}
+
+impl<D, I, T> Iterator for ResultIndexIter<D, I>
+where
+    D: Iterator<Item = usize>,
+    I: Iterator<Item = T>,
+{
+    type Item = Result<T, T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let discriminant = self.discriminants.next()?;
+        let index = self.indices.next()?;
+        Some(to_result(discriminant, index))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.discriminants.size_hint()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{MirrorRegion, OwnedRegion, Region, ReserveItems};
+    use crate::{FlatStack, MirrorRegion, OwnedRegion, Region, ReserveItems, StringRegion};
 
     use super::*;
 
@@ -218,4 +436,44 @@ mod tests {
         });
         assert!(cap > 0);
     }
+
+    #[test]
+    fn test_result_index() {
+        type R = ResultRegion<StringRegion, StringRegion>;
+        let mut c = FlatStack::<R, ResultIndex<Vec<(usize, usize)>>>::default();
+        let items: Vec<Result<String, String>> = (0..1_000)
+            .map(|i| {
+                if i % 3 == 0 {
+                    Err(format!("error {i}"))
+                } else {
+                    Ok(i.to_string())
+                }
+            })
+            .collect();
+        for item in &items {
+            c.copy(item);
+        }
+        assert!(items
+            .iter()
+            .map(|item| item.as_deref().map_err(String::as_str))
+            .eq(c.iter()));
+        assert_eq!(Ok("10"), c.get(10));
+        assert_eq!(Err("error 999"), c.get(999));
+        assert_eq!((666, 334), (c.indices.ok_count(), c.indices.err_count()));
+
+        // One index per result, and one bit per discriminant rounded up to words.
+        let mut size = 0;
+        c.indices.heap_size(|siz, _| size += siz);
+        let index_size = std::mem::size_of::<(usize, usize)>();
+        assert_eq!(items.len() * index_size + (items.len() + 63) / 64 * 8, size);
+        assert!(size < items.len() * std::mem::size_of::<Result<(usize, usize), (usize, usize)>>());
+
+        assert!(matches!(c.indices.pop(), Some(Err(_))));
+        c.indices.truncate(10);
+        assert!(c
+            .indices
+            .iter()
+            .map(|index| index.is_ok())
+            .eq(items[..10].iter().map(Result::is_ok)));
+    }
 }
@@ -0,0 +1,269 @@
+//! A region that stores runs of equal items once per run.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::tuple::TupleABRegion;
+use crate::{MirrorRegion, Push, Region};
+
+/// A region to run-length encode a column of items.
+///
+/// Compares each pushed item to the previous item, and either extends the current run or
+/// stores the item in a new run. Each run is a pair of the item in `R` and the length of the
+/// run. The index of an item is its position in the order of pushing, and reading a position
+/// binary-searches the ends of the runs.
+///
+/// Consecutive positions compress well in a [`Stride`](crate::impls::index::Stride)-based
+/// index container, such as [`IndexOptimized`](crate::impls::index::IndexOptimized).
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::rle::RleRegion;
+/// use flatcontainer::{MirrorRegion, Push, Region};
+/// let mut r = <RleRegion<MirrorRegion<u8>>>::default();
+///
+/// let a = r.push(1);
+/// let b = r.push(1);
+/// let c = r.push(2);
+/// assert_eq!((1, 1, 2), (r.index(a), r.index(b), r.index(c)));
+/// assert_eq!(2, r.runs());
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct RleRegion<R: Region> {
+    /// The item and length of each run.
+    runs: TupleABRegion<R, MirrorRegion<u64>>,
+    /// The index of each run in `runs`.
+    indices: Vec<(R::Index, u64)>,
+    /// The position after the last item of each run.
+    ends: Vec<usize>,
+}
+
+impl<R: Region + Clone> Clone for RleRegion<R> {
+    fn clone(&self) -> Self {
+        Self {
+            runs: self.runs.clone(),
+            indices: self.indices.clone(),
+            ends: self.ends.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.runs.clone_from(&source.runs);
+        self.indices.clone_from(&source.indices);
+        self.ends.clone_from(&source.ends);
+    }
+}
+
+impl<R: Region> Default for RleRegion<R> {
+    fn default() -> Self {
+        Self {
+            runs: TupleABRegion::default(),
+            indices: Vec::default(),
+            ends: Vec::default(),
+        }
+    }
+}
+
+impl<R: Region> RleRegion<R> {
+    /// Returns the number of items in the region.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    /// Returns `true` if the region contains no items.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the number of runs in the region.
+    #[inline]
+    #[must_use]
+    pub fn runs(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Iterates the runs as pairs of the item and the length of the run.
+    pub fn iter_runs(&self) -> impl Iterator<Item = (R::ReadItem<'_>, u64)> + Clone {
+        self.indices
+            .as_slice()
+            .iter()
+            .map(move |index| self.runs.index(*index))
+    }
+}
+
+impl<R: Region> Region for RleRegion<R> {
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    /// The position of the item.
+    type Index = usize;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let runs = regions.clone().map(Self::runs).sum();
+        Self {
+            runs: TupleABRegion::merge_regions(regions.map(|r| &r.runs)),
+            indices: Vec::with_capacity(runs),
+            ends: Vec::with_capacity(runs),
+        }
+    }
+
+    /// Returns the item at `position`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `position` is out of bounds.
+    #[inline]
+    fn index(&self, position: Self::Index) -> Self::ReadItem<'_> {
+        assert!(
+            position < self.len(),
+            "RleRegion: position {position} out of bounds for length {}",
+            self.len()
+        );
+        let run = self.ends.partition_point(|end| *end <= position);
+        self.runs.index(self.indices[run]).0
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let runs = regions.clone().map(Self::runs).sum();
+        self.runs.reserve_regions(regions.map(|r| &r.runs));
+        self.indices.reserve(runs);
+        self.ends.reserve(runs);
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let runs = regions.clone().map(Self::runs).sum();
+        self.runs.try_reserve_regions(regions.map(|r| &r.runs))?;
+        self.indices.try_reserve(runs)?;
+        self.ends.try_reserve(runs)
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.runs.clear();
+        self.indices.clear();
+        self.ends.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_index = std::mem::size_of::<(R::Index, u64)>();
+        callback(
+            self.indices.len() * size_of_index,
+            self.indices.capacity() * size_of_index,
+        );
+        let size_of_end = std::mem::size_of::<usize>();
+        callback(
+            self.ends.len() * size_of_end,
+            self.ends.capacity() * size_of_end,
+        );
+        self.runs.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+impl<R, T> Push<T> for RleRegion<R>
+where
+    R: Region + Push<T>,
+    for<'a> T: PartialEq<R::ReadItem<'a>>,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> <RleRegion<R> as Region>::Index {
+        let position = self.len();
+        match (self.indices.last_mut(), self.ends.last_mut()) {
+            (Some((index, length)), Some(end)) if item == self.runs.index((*index, *length)).0 => {
+                *length += 1;
+                *end += 1;
+            }
+            _ => {
+                self.indices.push(self.runs.push((item, 1)));
+                self.ends.push(position + 1);
+            }
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::impls::index::IndexOptimized;
+    use crate::{FlatStack, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_runs() {
+        let mut r = <RleRegion<MirrorRegion<u32>>>::default();
+        let items = [1, 1, 1, 2, 2, 3];
+        let positions: Vec<_> = items.into_iter().map(|item| r.push(item)).collect();
+        assert_eq!(vec![0, 1, 2, 3, 4, 5], positions);
+        for (position, item) in items.into_iter().enumerate() {
+            assert_eq!(item, r.index(position));
+        }
+        assert!(r.iter_runs().eq([(1, 3), (2, 2), (3, 1)]));
+        assert_eq!((6, 3), (r.len(), r.runs()));
+
+        // Storage per run, not per item.
+        let mut size = 0;
+        r.heap_size(|siz, _| size += siz);
+        let size_of_run = std::mem::size_of::<(u32, u64)>() + std::mem::size_of::<usize>();
+        assert_eq!(3 * size_of_run, size);
+
+        r.clear();
+        assert!(r.is_empty());
+        assert_eq!(0, r.push(3));
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let mut c = FlatStack::<RleRegion<StringRegion>, IndexOptimized>::default();
+        let items = ["a", "a", "bb", "a", "a", "a"];
+        for item in items {
+            c.copy(item);
+        }
+        assert!(c.iter().eq(items));
+        assert_eq!(3, c.region.runs());
+    }
+
+    #[test]
+    #[should_panic(expected = "RleRegion: position 1 out of bounds for length 1")]
+    fn test_out_of_bounds() {
+        let mut r = <RleRegion<MirrorRegion<u32>>>::default();
+        let _ = r.push(1);
+        let _ = r.index(1);
+    }
+}
@@ -0,0 +1,384 @@
+//! A region that stores sparse sets of positions, similar to roaring bitmaps.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, OwnedRegion, Push, Region};
+
+/// A container of a set, which is the high 16 bits of its positions, and the end offset of
+/// its low bits relative to the set's first low bits.
+type Container = (u16, u32);
+
+/// A region to hold sparse sets of `u32` positions, for example the set bits of a large
+/// bitmap that has few of them.
+///
+/// Similarly to a roaring bitmap, the region splits positions into containers by their high
+/// 16 bits, and stores the low 16 bits of each position in its container. A set with few
+/// positions takes two bytes per position plus six bytes per container, independently of
+/// the size of its domain. The read item is a [`ReadRoaring`], which tests membership in
+/// logarithmic time.
+///
+/// The owned representation is a vector of positions in ascending order. The region accepts
+/// unordered positions and duplicates, at the cost of sorting a copy.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::roaring::RoaringRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = RoaringRegion::default();
+///
+/// let index = r.push(&[7, 1_000_000, 3][..]);
+/// assert!(r.index(index).contains(1_000_000));
+/// assert!(!r.index(index).contains(4));
+/// assert!(r.index(index).iter().eq([3, 7, 1_000_000]));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RoaringRegion {
+    /// Containers of all sets.
+    containers: OwnedRegion<Container>,
+    /// Low 16 bits of all positions.
+    values: OwnedRegion<u16>,
+}
+
+impl RoaringRegion {
+    /// Pushes positions that are strictly ascending.
+    fn push_sorted(&mut self, positions: &[u32]) -> <Self as Region>::Index {
+        let mut containers: Vec<Container> = Vec::new();
+        for (offset, position) in positions.iter().enumerate() {
+            let high = (position >> 16) as u16;
+            let end = u32::try_from(offset + 1).expect("Set must have at most 2^32 positions");
+            match containers.last_mut() {
+                Some((last, last_end)) if *last == high => *last_end = end,
+                _ => containers.push((high, end)),
+            }
+        }
+        let containers = self.containers.push(containers);
+        let values = self
+            .values
+            .push(positions.iter().map(|p| *p as u16).collect::<Vec<_>>());
+        (containers, values)
+    }
+}
+
+impl Region for RoaringRegion {
+    type Owned = Vec<u32>;
+    type ReadItem<'a> = ReadRoaring<'a>;
+    /// The ranges of the set's containers and low bits.
+    type Index = ((usize, usize), (usize, usize));
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            containers: OwnedRegion::merge_regions(regions.clone().map(|r| &r.containers)),
+            values: OwnedRegion::merge_regions(regions.map(|r| &r.values)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (containers, values): Self::Index) -> Self::ReadItem<'_> {
+        ReadRoaring(Ok((
+            self.containers.index(containers),
+            self.values.index(values),
+        )))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.containers
+            .reserve_regions(regions.clone().map(|r| &r.containers));
+        self.values.reserve_regions(regions.map(|r| &r.values));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.containers
+            .try_reserve_regions(regions.clone().map(|r| &r.containers))?;
+        self.values.try_reserve_regions(regions.map(|r| &r.values))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.containers.clear();
+        self.values.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.containers.heap_size(&mut callback);
+        self.values.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A set of positions read from a [`RoaringRegion`].
+#[derive(Clone, Copy)]
+#[allow(clippy::type_complexity)]
+pub struct ReadRoaring<'a>(Result<(&'a [Container], &'a [u16]), &'a [u32]>);
+
+impl<'a> ReadRoaring<'a> {
+    /// Returns `true` if the set contains `position`.
+    #[must_use]
+    pub fn contains(&self, position: u32) -> bool {
+        match self.0 {
+            Ok((containers, values)) => {
+                let high = (position >> 16) as u16;
+                let Ok(container) = containers.binary_search_by_key(&high, |(high, _)| *high)
+                else {
+                    return false;
+                };
+                let start = container
+                    .checked_sub(1)
+                    .map_or(0, |previous| containers[previous].1 as usize);
+                let end = containers[container].1 as usize;
+                values[start..end].binary_search(&(position as u16)).is_ok()
+            }
+            Err(positions) => positions.binary_search(&position).is_ok(),
+        }
+    }
+
+    /// The number of positions in the set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self.0 {
+            Ok((_, values)) => values.len(),
+            Err(positions) => positions.len(),
+        }
+    }
+
+    /// Returns `true` if the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the positions in ascending order.
+    #[must_use]
+    pub fn iter(&self) -> ReadRoaringIter<'a> {
+        self.into_iter()
+    }
+}
+
+impl PartialEq for ReadRoaring<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.iter().eq(*other)
+    }
+}
+
+impl Eq for ReadRoaring<'_> {}
+
+impl Debug for ReadRoaring<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoOwned<'a> for ReadRoaring<'a> {
+    type Owned = Vec<u32>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend(self.iter());
+    }
+
+    /// Borrows a set of positions, which must be in ascending order.
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a> IntoIterator for ReadRoaring<'a> {
+    type Item = u32;
+    type IntoIter = ReadRoaringIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        ReadRoaringIter {
+            set: self,
+            container: 0,
+            offset: 0,
+        }
+    }
+}
+
+/// An iterator over the positions of a [`ReadRoaring`].
+#[derive(Clone)]
+pub struct ReadRoaringIter<'a> {
+    set: ReadRoaring<'a>,
+    /// The container of the next position.
+    container: usize,
+    /// The offset of the next position.
+    offset: usize,
+}
+
+impl Iterator for ReadRoaringIter<'_> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        let position = match self.set.0 {
+            Ok((containers, values)) => {
+                let low = *values.get(self.offset)?;
+                while containers[self.container].1 as usize <= self.offset {
+                    self.container += 1;
+                }
+                u32::from(containers[self.container].0) << 16 | u32::from(low)
+            }
+            Err(positions) => *positions.get(self.offset)?,
+        };
+        self.offset += 1;
+        Some(position)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.set.len() - self.offset;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ReadRoaringIter<'_> {}
+
+impl Push<&[u32]> for RoaringRegion {
+    #[inline]
+    fn push(&mut self, item: &[u32]) -> <RoaringRegion as Region>::Index {
+        if item.windows(2).all(|pair| pair[0] < pair[1]) {
+            self.push_sorted(item)
+        } else {
+            let mut sorted = item.to_vec();
+            sorted.sort_unstable();
+            sorted.dedup();
+            self.push_sorted(&sorted)
+        }
+    }
+}
+
+impl Push<Vec<u32>> for RoaringRegion {
+    #[inline]
+    fn push(&mut self, item: Vec<u32>) -> <RoaringRegion as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl Push<&Vec<u32>> for RoaringRegion {
+    #[inline]
+    fn push(&mut self, item: &Vec<u32>) -> <RoaringRegion as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+/// Pushes the positions of the `true` elements of a bitmap.
+impl Push<&[bool]> for RoaringRegion {
+    #[inline]
+    fn push(&mut self, item: &[bool]) -> <RoaringRegion as Region>::Index {
+        let positions: Vec<_> = item
+            .iter()
+            .enumerate()
+            .filter(|(_, bit)| **bit)
+            .map(|(position, _)| u32::try_from(position).expect("Bitmap must fit u32 positions"))
+            .collect();
+        self.push_sorted(&positions)
+    }
+}
+
+impl Push<ReadRoaring<'_>> for RoaringRegion {
+    #[inline]
+    fn push(&mut self, item: ReadRoaring<'_>) -> <RoaringRegion as Region>::Index {
+        match item.0 {
+            Ok((containers, values)) => {
+                let values = self.values.push(values);
+                let containers = self.containers.push(containers);
+                (containers, values)
+            }
+            Err(positions) => self.push(positions),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_sparse() {
+        let positions = [3, 65_535, 65_536, 1 << 20, (1 << 20) + 1, u32::MAX];
+        let mut c = FlatStack::<RoaringRegion>::default();
+        c.copy(&positions[..]);
+        c.copy(Vec::new());
+        c.copy(vec![9, 2, 9]);
+
+        let set = c.get(0);
+        assert_eq!(positions.len(), set.len());
+        assert!(set.iter().eq(positions));
+        for position in positions {
+            assert!(set.contains(position));
+        }
+        for position in [0, 4, 65_534, 65_537, 1 << 19, u32::MAX - 1] {
+            assert!(!set.contains(position));
+        }
+        assert!(c.get(1).is_empty());
+        assert!(!c.get(1).contains(0));
+        assert_eq!(vec![2, 9], c.get(2).into_owned());
+
+        let owned = positions.to_vec();
+        assert_eq!(c.get(0), ReadRoaring::borrow_as(&owned));
+        c.copy(ReadRoaring::borrow_as(&owned));
+        let copy = c.clone();
+        c.copy(copy.get(0));
+        assert_eq!(c.get(0), c.get(3));
+        assert_eq!(c.get(0), c.get(4));
+
+        // A borrowed vector need not be sorted.
+        let unsorted = vec![9, 2, 9];
+        c.copy(ReadRoaring::borrow_as(&unsorted));
+        assert_eq!(vec![2, 9], c.get(5).into_owned());
+    }
+
+    #[test]
+    fn test_heap_size() {
+        const DOMAIN: usize = 10_000_000;
+        let mut bitmap = vec![false; DOMAIN];
+        for position in [17, 4_000_000, 4_000_001, 9_999_999] {
+            bitmap[position] = true;
+        }
+        let mut r = RoaringRegion::default();
+        let index = r.push(bitmap.as_slice());
+        let set = r.index(index);
+        assert!(set.iter().eq([17, 4_000_000, 4_000_001, 9_999_999]));
+
+        let mut size = 0;
+        r.heap_size(|siz, _| size += siz);
+        assert!(size * 1000 < DOMAIN / 8);
+    }
+}
@@ -0,0 +1,396 @@
+//! A region that stores sets.
+
+use std::cmp::Ordering;
+use std::collections::TryReserveError;
+use std::collections::{btree_set, BTreeSet, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::hash::BuildHasher;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::impls::slice::{ReadSlice, ReadSliceIter};
+use crate::{IntoOwned, Push, PushIter, Region, RegionPreference, SliceRegion};
+
+impl<T: RegionPreference> RegionPreference for BTreeSet<T>
+where
+    T::Owned: Ord,
+{
+    type Owned = BTreeSet<T::Owned>;
+    type Region = SetRegion<T::Region>;
+}
+
+/// A region to hold sets of elements.
+///
+/// Stores each set as a sorted slice of unique elements in a [`SliceRegion`]. The read item
+/// is a [`ReadSet`], which supports iterating the elements in order and membership queries
+/// in logarithmic time.
+///
+/// The region accepts [`BTreeSet`]s and [`HashSet`]s. Elements of hash sets need to be sorted
+/// before they are pushed, which requires a temporary allocation.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use flatcontainer::impls::set::SetRegion;
+/// use flatcontainer::{MirrorRegion, Push, Region};
+/// let mut r = <SetRegion<MirrorRegion<u32>>>::default();
+///
+/// let set = BTreeSet::from([3, 1, 2]);
+/// let index = r.push(&set);
+///
+/// assert!(r.index(index).contains(&2));
+/// assert!(!r.index(index).contains(&4));
+/// assert!(r.index(index).iter().eq([1, 2, 3]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SetRegion<R: Region, O = Vec<<R as Region>::Index>> {
+    /// Sorted elements of each set.
+    inner: SliceRegion<R, O>,
+}
+
+impl<R, O> Clone for SetRegion<R, O>
+where
+    R: Region + Clone,
+    O: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<R, O> Region for SetRegion<R, O>
+where
+    R: Region,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+{
+    type Owned = BTreeSet<R::Owned>;
+    type ReadItem<'a> = ReadSet<'a, R, O> where Self: 'a;
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: SliceRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadSet(Ok(self.inner.index(index)))
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<R, O> Default for SetRegion<R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self {
+            inner: SliceRegion::default(),
+        }
+    }
+}
+
+/// A set read from a [`SetRegion`].
+pub struct ReadSet<'a, R: Region, O: IndexContainer<R::Index> = Vec<<R as Region>::Index>>(
+    Result<ReadSlice<'a, R, O>, &'a BTreeSet<R::Owned>>,
+);
+
+impl<'a, R, O> ReadSet<'a, R, O>
+where
+    R: Region,
+    O: IndexContainer<R::Index>,
+{
+    /// Returns `true` if the set contains `item`.
+    #[must_use]
+    pub fn contains(&self, item: &R::Owned) -> bool
+    where
+        R::Owned: Ord,
+        for<'b> R::ReadItem<'b>: Ord,
+    {
+        match &self.0 {
+            Ok(slice) => {
+                let needle = R::reborrow(IntoOwned::borrow_as(item));
+                let (mut lower, mut upper) = (0, slice.len());
+                while lower < upper {
+                    let middle = lower + (upper - lower) / 2;
+                    match R::reborrow(slice.get(middle)).cmp(&needle) {
+                        Ordering::Less => lower = middle + 1,
+                        Ordering::Equal => return true,
+                        Ordering::Greater => upper = middle,
+                    }
+                }
+                false
+            }
+            Err(set) => set.contains(item),
+        }
+    }
+
+    /// The number of elements in this set.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            Ok(slice) => slice.len(),
+            Err(set) => set.len(),
+        }
+    }
+
+    /// Returns `true` if the set is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        match &self.0 {
+            Ok(slice) => slice.is_empty(),
+            Err(set) => set.is_empty(),
+        }
+    }
+
+    /// Returns an iterator over the elements in ascending order.
+    #[must_use]
+    pub fn iter(&self) -> ReadSetIter<'a, R, O> {
+        self.into_iter()
+    }
+}
+
+impl<R: Region, O: IndexContainer<R::Index>> Clone for ReadSet<'_, R, O> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: Region, O: IndexContainer<R::Index>> Copy for ReadSet<'_, R, O> {}
+
+impl<R: Region, O: IndexContainer<R::Index>> Debug for ReadSet<'_, R, O>
+where
+    for<'a> R::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, R, O> IntoOwned<'a> for ReadSet<'a, R, O>
+where
+    R: Region,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+{
+    type Owned = BTreeSet<R::Owned>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self.iter().map(IntoOwned::into_owned).collect()
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend(self.iter().map(IntoOwned::into_owned));
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self(Err(owned))
+    }
+}
+
+impl<'a, R: Region, O: IndexContainer<R::Index>> IntoIterator for ReadSet<'a, R, O> {
+    type Item = R::ReadItem<'a>;
+    type IntoIter = ReadSetIter<'a, R, O>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        match self.0 {
+            Ok(slice) => ReadSetIter(Ok(slice.into_iter())),
+            Err(set) => ReadSetIter(Err(set.iter())),
+        }
+    }
+}
+
+/// An iterator over the elements of a [`ReadSet`].
+pub struct ReadSetIter<'a, R: Region, O: IndexContainer<R::Index>>(
+    Result<ReadSliceIter<'a, R, O>, btree_set::Iter<'a, R::Owned>>,
+);
+
+impl<R: Region, O: IndexContainer<R::Index>> Clone for ReadSetIter<'_, R, O> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<'a, R: Region, O: IndexContainer<R::Index>> Iterator for ReadSetIter<'a, R, O> {
+    type Item = R::ReadItem<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Ok(iter) => iter.next(),
+            Err(iter) => iter.next().map(IntoOwned::borrow_as),
+        }
+    }
+}
+
+impl<R, O, T> Push<BTreeSet<T>> for SetRegion<R, O>
+where
+    R: Region + Push<T>,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+{
+    #[inline]
+    fn push(&mut self, item: BTreeSet<T>) -> <SetRegion<R, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+impl<'a, R, O, T> Push<&'a BTreeSet<T>> for SetRegion<R, O>
+where
+    R: Region + Push<&'a T>,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+{
+    #[inline]
+    fn push(&mut self, item: &'a BTreeSet<T>) -> <SetRegion<R, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+impl<'a, R, O, T, S> Push<&'a HashSet<T, S>> for SetRegion<R, O>
+where
+    R: Region + Push<&'a T>,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+    T: Ord,
+    S: BuildHasher,
+{
+    #[inline]
+    fn push(&mut self, item: &'a HashSet<T, S>) -> <SetRegion<R, O> as Region>::Index {
+        let mut sorted: Vec<_> = item.iter().collect();
+        sorted.sort_unstable();
+        self.inner.push(PushIter(sorted))
+    }
+}
+
+impl<'a, R, O> Push<ReadSet<'a, R, O>> for SetRegion<R, O>
+where
+    R: Region + Push<<R as Region>::ReadItem<'a>>,
+    R::Owned: Ord,
+    O: IndexContainer<R::Index>,
+{
+    #[inline]
+    fn push(&mut self, item: ReadSet<'a, R, O>) -> <SetRegion<R, O> as Region>::Index {
+        self.inner.push(PushIter(item))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let sets = [
+            BTreeSet::from([5_u32, 1, 3]),
+            BTreeSet::new(),
+            BTreeSet::from([u32::MAX, 0]),
+        ];
+
+        let mut r = <SetRegion<MirrorRegion<u32>>>::default();
+        let indices: Vec<_> = sets.iter().map(|set| r.push(set)).collect();
+
+        for (set, index) in sets.iter().zip(indices) {
+            let read = r.index(index);
+            assert_eq!(set.len(), read.len());
+            assert_eq!(*set, read.into_owned());
+            for item in 0..7 {
+                assert_eq!(set.contains(&item), read.contains(&item));
+            }
+            assert!(read.contains(&u32::MAX) == set.contains(&u32::MAX));
+        }
+    }
+
+    #[test]
+    fn test_hash_set() {
+        let set: HashSet<_> = ["c", "a", "b"].into_iter().collect();
+
+        let mut r = <SetRegion<StringRegion>>::default();
+        let index = r.push(&set);
+        assert!(r.index(index).iter().eq(["a", "b", "c"]));
+        assert!(r.index(index).contains(&"b".to_string()));
+        assert!(!r.index(index).contains(&"d".to_string()));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let set = BTreeSet::from([2_u32, 4]);
+        let read = <ReadSet<MirrorRegion<u32>> as IntoOwned>::borrow_as(&set);
+        assert!(read.contains(&2));
+        assert!(!read.contains(&3));
+
+        let mut r = <SetRegion<MirrorRegion<u32>>>::default();
+        let index = r.push(read);
+        assert_eq!(set, r.index(index).into_owned());
+        assert_eq!("{2, 4}", format!("{:?}", r.index(index)));
+    }
+
+    #[test]
+    fn test_preference() {
+        let mut c = crate::FlatStack::default_impl::<BTreeSet<u32>>();
+        c.copy(BTreeSet::from([1, 2]));
+        assert!(c.get(0).contains(&1));
+    }
+}
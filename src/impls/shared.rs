@@ -0,0 +1,189 @@
+//! A region that shares a built region between readers.
+
+use std::collections::TryReserveError;
+use std::sync::Arc;
+
+use crate::impls::index::IndexContainer;
+use crate::{FlatStack, Region};
+
+/// A region that shares a region `R` behind an [`Arc`], which makes cloning the region cheap,
+/// and allows threads to read it concurrently if `R` is [`Sync`].
+///
+/// The region is read-only and does not accept new items. It is meant for regions that are
+/// fully built, see [`FlatStack::into_shared`]. Reserving space only has an effect if the
+/// region is not shared, and clearing a shared region replaces it by an empty one, leaving
+/// other readers unaffected.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::{FlatStack, StringRegion};
+/// let c: FlatStack<StringRegion> = ["a", "b"].into_iter().collect();
+/// let shared = c.into_shared();
+///
+/// let reader = shared.clone();
+/// std::thread::spawn(move || assert_eq!("b", reader.get(1)))
+///     .join()
+///     .unwrap();
+/// assert_eq!("a", shared.get(0));
+/// ```
+#[derive(Default, Debug)]
+pub struct SharedRegion<R> {
+    inner: Arc<R>,
+}
+
+impl<R> Clone for SharedRegion<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<R> SharedRegion<R> {
+    /// Shares `region`.
+    #[inline]
+    #[must_use]
+    pub fn new(region: R) -> Self {
+        Self {
+            inner: Arc::new(region),
+        }
+    }
+
+    /// Returns the shared region.
+    #[inline]
+    #[must_use]
+    pub fn get(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Region> Region for SharedRegion<R> {
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    type Index = R::Index;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self::new(R::merge_regions(regions.map(|r| r.get())))
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    /// Reserves space in the inner region if it is not shared, and does nothing otherwise.
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.reserve_regions(regions.map(|r| r.get()));
+        }
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.try_reserve_regions(regions.map(|r| r.get()))?;
+        }
+        Ok(())
+    }
+
+    /// Clears the inner region if it is not shared, and replaces it by an empty region
+    /// otherwise.
+    #[inline]
+    fn clear(&mut self) {
+        match Arc::get_mut(&mut self.inner) {
+            Some(inner) => inner.clear(),
+            None => self.inner = Arc::default(),
+        }
+    }
+
+    /// Reports the heap size of the inner region, which every clone of a shared region
+    /// reports again.
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+impl<R: Region, S: IndexContainer<R::Index>> FlatStack<R, S> {
+    /// Converts the stack into a read-only stack whose region is shared between its clones.
+    ///
+    /// Cloning the result copies the indices, but not the region. See [`SharedRegion`].
+    #[must_use]
+    pub fn into_shared(self) -> FlatStack<SharedRegion<R>, S> {
+        FlatStack {
+            indices: self.indices,
+            region: SharedRegion::new(self.region),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{MirrorRegion, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_threads() {
+        let c: FlatStack<StringRegion> = (0..100).map(|i| i.to_string()).collect();
+        let shared = c.into_shared();
+
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let reader = shared.clone();
+                scope.spawn(move || {
+                    for i in (thread..100).step_by(4) {
+                        assert_eq!(i.to_string(), reader.get(i));
+                    }
+                });
+            }
+        });
+        assert_eq!(100, shared.len());
+    }
+
+    #[test]
+    fn test_clear() {
+        let c: FlatStack<MirrorRegion<u8>> = [1, 2, 3].into_iter().collect();
+        let mut shared = c.into_shared();
+        let other = shared.clone();
+        shared.clear();
+        assert!(shared.is_empty());
+        assert!(other.iter().eq([1, 2, 3]));
+
+        let merged = FlatStack::merge_capacity([&shared, &other].into_iter());
+        assert!(merged.is_empty());
+    }
+}
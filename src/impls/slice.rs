@@ -1,14 +1,21 @@
 //! A region that stores slices.
 
+use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::TryReserveError;
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, Range};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::impls::choose::CanMeasure;
 use crate::impls::index::IndexContainer;
-use crate::{IntoOwned, Push, Region, RegionPreference, ReserveItems};
+use crate::{
+    IndexOverflow, IntoOwned, Push, PushIter, Region, RegionLen, RegionPreference, ReserveItems,
+    TryPush,
+};
 
 impl<T: RegionPreference> RegionPreference for Vec<T> {
     type Owned = Vec<T::Owned>;
@@ -81,6 +88,20 @@ where
     }
 }
 
+impl<R: Region, O> SliceRegion<R, O> {
+    /// Constructs an empty region that stores the indexes of its slices in `slices`.
+    ///
+    /// Use this to construct a region whose index container needs state, such as a
+    /// [`PooledIndex`](crate::impls::pooled::PooledIndex) sharing a pool with other regions.
+    #[must_use]
+    pub fn with_index_container(slices: O) -> Self {
+        Self {
+            slices,
+            inner: R::default(),
+        }
+    }
+}
+
 impl<R: Region, O: IndexContainer<R::Index>> Region for SliceRegion<R, O> {
     type Owned = Vec<R::Owned>;
     type ReadItem<'a> = ReadSlice<'a, R, O> where Self: 'a;
@@ -99,6 +120,11 @@ impl<R: Region, O: IndexContainer<R::Index>> Region for SliceRegion<R, O> {
 
     #[inline]
     fn index(&self, (start, end): Self::Index) -> Self::ReadItem<'_> {
+        debug_assert!(
+            start <= end && end <= self.slices.len(),
+            "SliceRegion: index {start}..{end} out of bounds for length {}",
+            self.slices.len()
+        );
         ReadSlice(Ok(ReadSliceInner {
             region: self,
             start,
@@ -106,6 +132,15 @@ impl<R: Region, O: IndexContainer<R::Index>> Region for SliceRegion<R, O> {
         }))
     }
 
+    /// Returns the storage size of the slice's elements, plus the size of their indexes.
+    #[inline]
+    fn item_storage_size(&self, (start, end): Self::Index) -> usize {
+        let elements: usize = (start..end)
+            .map(|offset| self.inner.item_storage_size(self.slices.index(offset)))
+            .sum();
+        elements + (end - start) * std::mem::size_of::<R::Index>()
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -117,6 +152,17 @@ impl<R: Region, O: IndexContainer<R::Index>> Region for SliceRegion<R, O> {
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.slices
+            .try_reserve(regions.clone().map(|r| r.slices.len()).sum())?;
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.slices.clear();
@@ -138,6 +184,14 @@ impl<R: Region, O: IndexContainer<R::Index>> Region for SliceRegion<R, O> {
     }
 }
 
+impl<R: Region, O: IndexContainer<R::Index>> RegionLen for SliceRegion<R, O> {
+    /// Returns the number of elements of all slices.
+    #[inline]
+    fn len(&self) -> usize {
+        self.slices.len()
+    }
+}
+
 impl<R: Region, O: IndexContainer<R::Index>> Default for SliceRegion<R, O> {
     #[inline]
     fn default() -> Self {
@@ -163,6 +217,11 @@ impl<R: Region, O: IndexContainer<R::Index>> ReadSlice<'_, R, O> {
     #[inline]
     #[must_use]
     pub fn get(&self, index: usize) -> R::ReadItem<'_> {
+        assert!(
+            index < self.len(),
+            "ReadSlice: index {index} out of bounds for length {}",
+            self.len()
+        );
         match &self.0 {
             Ok(inner) => inner.get(index),
             Err(slice) => IntoOwned::borrow_as(&slice[index]),
@@ -226,6 +285,20 @@ where
     }
 }
 
+impl<R: Region, O: IndexContainer<R::Index>> Hash for ReadSlice<'_, R, O>
+where
+    for<'a> R::ReadItem<'a>: Hash,
+{
+    /// Hashes the length followed by the elements, so that equal slices hash equally
+    /// independent of whether they are borrowed from an owned representation.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(state);
+        }
+    }
+}
+
 struct ReadSliceInner<'a, R: Region, O: IndexContainer<R::Index> = Vec<<R as Region>::Index>> {
     region: &'a SliceRegion<R, O>,
     start: usize,
@@ -233,22 +306,10 @@ struct ReadSliceInner<'a, R: Region, O: IndexContainer<R::Index> = Vec<<R as Reg
 }
 
 impl<R: Region, O: IndexContainer<R::Index>> ReadSliceInner<'_, R, O> {
-    /// Read the n-th item from the underlying region.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the index is out of bounds, i.e., it is larger than the
-    /// length of this slice representation.
+    /// Read the n-th item from the underlying region. The caller checks the bounds.
     #[inline]
     #[must_use]
     pub fn get(&self, index: usize) -> R::ReadItem<'_> {
-        assert!(
-            index <= self.end - self.start,
-            "Index {index} out of bounds {} ({}..{})",
-            self.end - self.start,
-            self.start,
-            self.end
-        );
         self.region
             .inner
             .index(self.region.slices.index(self.start + index))
@@ -416,6 +477,36 @@ where
     }
 }
 
+/// Measures the elements in the inner region, and an index per element.
+impl<'a, C, T, O> CanMeasure<&'a [T]> for SliceRegion<C, O>
+where
+    C: Region + CanMeasure<&'a T>,
+    O: IndexContainer<C::Index>,
+{
+    #[inline]
+    fn measure(&self, item: &&'a [T]) -> usize {
+        let elements: usize = item.iter().map(|t| self.inner.measure(&t)).sum();
+        elements + item.len() * std::mem::size_of::<C::Index>()
+    }
+}
+
+/// Pushes a slice, or fails if the index container cannot represent an index of the inner region.
+///
+/// On error, the region drops the indexes pushed so far. The inner region retains the elements
+/// pushed so far, but no index refers to them.
+impl<'a, C, T, O> TryPush<&'a [T]> for SliceRegion<C, O>
+where
+    C: Region + Push<&'a T>,
+    O: IndexContainer<C::Index>,
+{
+    type Error = IndexOverflow;
+
+    #[inline]
+    fn try_push(&mut self, item: &'a [T]) -> Result<Self::Index, Self::Error> {
+        self.try_push(PushIter(item))
+    }
+}
+
 impl<'a, T, R, O> ReserveItems<&'a [T]> for SliceRegion<R, O>
 where
     R: Region + ReserveItems<&'a T>,
@@ -445,6 +536,22 @@ where
     }
 }
 
+/// Pushes the elements of a borrowed slice by reference, and of an owned slice by value.
+impl<'a, C, T, O> Push<Cow<'a, [T]>> for SliceRegion<C, O>
+where
+    T: Clone,
+    C: Region + Push<&'a T> + Push<T>,
+    O: IndexContainer<C::Index>,
+{
+    #[inline]
+    fn push(&mut self, item: Cow<'a, [T]>) -> <SliceRegion<C, O> as Region>::Index {
+        match item {
+            Cow::Borrowed(item) => self.push(item),
+            Cow::Owned(item) => self.push(item),
+        }
+    }
+}
+
 impl<C, T, O> Push<&Vec<T>> for SliceRegion<C, O>
 where
     for<'a> C: Region + Push<&'a T>,
@@ -520,6 +627,62 @@ where
     }
 }
 
+impl<C, O, T, I> Push<PushIter<I>> for SliceRegion<C, O>
+where
+    C: Region + Push<T>,
+    O: IndexContainer<C::Index>,
+    I: IntoIterator<Item = T>,
+{
+    #[inline]
+    fn push(&mut self, item: PushIter<I>) -> <SliceRegion<C, O> as Region>::Index {
+        let start = self.slices.len();
+        for item in item.0 {
+            let index = self.inner.push(item);
+            self.slices.push(index);
+        }
+        (start, self.slices.len())
+    }
+}
+
+/// Pushes the elements of an iterator whose type is erased.
+impl<C, O, T> Push<&mut dyn Iterator<Item = T>> for SliceRegion<C, O>
+where
+    C: Region + Push<T>,
+    O: IndexContainer<C::Index>,
+{
+    #[inline]
+    fn push(&mut self, item: &mut dyn Iterator<Item = T>) -> <SliceRegion<C, O> as Region>::Index {
+        self.push(PushIter(item))
+    }
+}
+
+/// Pushes the elements of an iterator, or fails if the index container cannot represent an
+/// index of the inner region.
+///
+/// On error, the region drops the indexes pushed so far. The inner region retains the elements
+/// pushed so far, but no index refers to them.
+impl<C, O, T, I> TryPush<PushIter<I>> for SliceRegion<C, O>
+where
+    C: Region + Push<T>,
+    O: IndexContainer<C::Index>,
+    I: IntoIterator<Item = T>,
+{
+    type Error = IndexOverflow;
+
+    #[inline]
+    fn try_push(&mut self, item: PushIter<I>) -> Result<Self::Index, Self::Error> {
+        let start = self.slices.len();
+        for item in item.0 {
+            let index = self.inner.push(item);
+            if let Err(err) = self.slices.try_push(index) {
+                self.slices.truncate(start);
+                return Err(err);
+            }
+        }
+        Ok((start, self.slices.len()))
+    }
+}
+
 impl<T, R, O, const N: usize> Push<[T; N]> for SliceRegion<R, O>
 where
     for<'a> R: Region + Push<&'a T>,
@@ -599,7 +762,7 @@ mod tests {
         let slice = r.index(index);
         assert_eq!(s.len(), slice.len());
         assert!(!slice.is_empty());
-        assert_eq!(s.get(0), Some(&1));
+        assert_eq!(s.first(), Some(&1));
         assert_eq!(s.get(1), Some(&2));
         assert_eq!(s.get(2), Some(&3));
         assert_eq!(s.get(3), Some(&4));
@@ -611,15 +774,32 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "ReadSlice: index 4 out of bounds for length 4")]
     fn test_get_out_of_bounds() {
         let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
         let index = r.push([1; 4]);
+        let _ = r.push([2; 4]);
 
         // Index 4 is out of bounds and expected to panic.
         let _ = r.index(index).get(4);
     }
 
+    #[test]
+    #[should_panic(expected = "ReadSlice: index 1 out of bounds for length 1")]
+    fn test_get_out_of_bounds_owned() {
+        let owned = vec![1_u8];
+        let _ = <ReadSlice<MirrorRegion<u8>> as IntoOwned>::borrow_as(&owned).get(1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "SliceRegion: index 2..5 out of bounds for length 4")]
+    fn test_index_out_of_bounds() {
+        let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
+        let _ = r.push([1; 4]);
+        let _ = r.index((2, 5));
+    }
+
     #[test]
     fn test_read_slice_debug() {
         let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
@@ -687,6 +867,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_push_iter() {
+        let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
+        let index = r.push(PushIter((1..4).rev()));
+        assert!(r.index(index).iter().eq([3, 2, 1]));
+        let index = r.push(PushIter(std::iter::empty::<u8>()));
+        assert!(r.index(index).is_empty());
+    }
+
+    #[test]
+    fn test_push_dyn_iter() {
+        let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
+        let iter: &mut dyn Iterator<Item = u8> = &mut (1..4).rev();
+        let index = r.push(iter);
+        assert!(r.index(index).iter().eq([3, 2, 1]));
+    }
+
     #[test]
     fn test_reserve_ref_slice() {
         let mut r = <SliceRegion<MirrorRegion<u8>>>::default();
@@ -719,4 +916,16 @@ mod tests {
         });
         assert!(cap > 0);
     }
+
+    #[test]
+    fn test_push_cow() {
+        use crate::StringRegion;
+
+        let items = ["a".to_string(), "bc".to_string()];
+        let mut r = <SliceRegion<StringRegion>>::default();
+        let borrowed = r.push(Cow::Borrowed(items.as_slice()));
+        let owned = r.push(Cow::<[_]>::Owned(items.to_vec()));
+        assert!(r.index(borrowed).iter().eq(["a", "bc"]));
+        assert_eq!(r.index(borrowed), r.index(owned));
+    }
 }
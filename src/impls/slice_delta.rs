@@ -0,0 +1,464 @@
+//! A region that stores slices as differences to their predecessor.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, Push, Region, ReserveItems};
+
+/// A region for sequences of similar slices, such as frames of sensor readings.
+///
+/// The region stores a slice as the positions and values of the elements that differ from
+/// the previously pushed slice. Reading a slice starts at the most recent slice stored in full,
+/// its checkpoint, and applies the differences of the slices that follow it. The region stores a
+/// slice in full if it is the first slice, if its length differs from its predecessor's, if
+/// the difference is not smaller than the slice, or every `INTERVAL` slices to bound the cost
+/// of reading.
+///
+/// The region retains a copy of the most recently pushed slice to compute differences.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::slice_delta::SliceDeltaRegion;
+/// use flatcontainer::{IntoOwned, Push, Region};
+/// let mut r = <SliceDeltaRegion<u8>>::default();
+///
+/// let first = r.push([1, 2, 3, 4].as_slice());
+/// let second = r.push([1, 2, 5, 4].as_slice());
+/// assert_eq!(vec![1, 2, 3, 4], r.index(first).into_owned());
+/// assert_eq!(Some(&5), r.index(second).get(2));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "T: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct SliceDeltaRegion<T, const INTERVAL: usize = 16> {
+    /// Elements of full slices and changed elements of differences.
+    values: Vec<T>,
+    /// Positions of changed elements of differences, ascending per slice.
+    positions: Vec<u32>,
+    /// Per slice, the end of its values and positions.
+    ends: Vec<(usize, usize)>,
+    /// Slices that are stored in full, ascending.
+    checkpoints: Vec<usize>,
+    /// The most recently pushed slice.
+    last: Vec<T>,
+}
+
+impl<T: Clone, const INTERVAL: usize> Clone for SliceDeltaRegion<T, INTERVAL> {
+    fn clone(&self) -> Self {
+        Self {
+            values: self.values.clone(),
+            positions: self.positions.clone(),
+            ends: self.ends.clone(),
+            checkpoints: self.checkpoints.clone(),
+            last: self.last.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.values.clone_from(&source.values);
+        self.positions.clone_from(&source.positions);
+        self.ends.clone_from(&source.ends);
+        self.checkpoints.clone_from(&source.checkpoints);
+        self.last.clone_from(&source.last);
+    }
+}
+
+impl<T, const INTERVAL: usize> Default for SliceDeltaRegion<T, INTERVAL> {
+    fn default() -> Self {
+        Self {
+            values: Vec::default(),
+            positions: Vec::default(),
+            ends: Vec::default(),
+            checkpoints: Vec::default(),
+            last: Vec::default(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> SliceDeltaRegion<T, INTERVAL> {
+    /// Pushes the slice presented by `items`, which we might need to traverse twice.
+    fn push_iter<'a, I>(&mut self, items: I) -> usize
+    where
+        T: 'a,
+        I: ExactSizeIterator<Item = &'a T> + Clone,
+    {
+        let slice = self.ends.len();
+        let len = items.len();
+        let diff = self.checkpoints.last().map_or(false, |checkpoint| {
+            slice - checkpoint < INTERVAL.max(1)
+                && len == self.last.len()
+                && u32::try_from(len).is_ok()
+        });
+
+        if diff {
+            let start = self.positions.len();
+            for (position, (item, last)) in items.clone().zip(&mut self.last).enumerate() {
+                if item != last {
+                    self.positions.push(position as u32);
+                    self.values.push(item.clone());
+                    last.clone_from(item);
+                }
+            }
+            let changed = self.positions.len() - start;
+            let size_of_changed = std::mem::size_of::<T>() + std::mem::size_of::<u32>();
+            if changed * size_of_changed < len * std::mem::size_of::<T>() {
+                self.ends.push((self.values.len(), self.positions.len()));
+                return slice;
+            }
+            // The difference is too large, store the slice in full instead.
+            self.positions.truncate(start);
+            self.values.truncate(self.values.len() - changed);
+        } else {
+            self.last.clear();
+            self.last.extend(items.clone().cloned());
+        }
+
+        self.values.extend(items.cloned());
+        self.ends.push((self.values.len(), self.positions.len()));
+        self.checkpoints.push(slice);
+        slice
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> Region for SliceDeltaRegion<T, INTERVAL> {
+    type Owned = Vec<T>;
+    type ReadItem<'a> = ReadSliceDelta<'a, T> where Self: 'a;
+    /// The position of the slice.
+    type Index = usize;
+
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            values: Vec::with_capacity(regions.clone().map(|r| r.values.len()).sum()),
+            positions: Vec::with_capacity(regions.clone().map(|r| r.positions.len()).sum()),
+            ends: Vec::with_capacity(regions.clone().map(|r| r.ends.len()).sum()),
+            checkpoints: Vec::with_capacity(regions.map(|r| r.checkpoints.len()).sum()),
+            last: Vec::default(),
+        }
+    }
+
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let checkpoint = self.checkpoints[self.checkpoints.partition_point(|c| *c <= index) - 1];
+        let (values_start, _) = checkpoint
+            .checked_sub(1)
+            .map_or((0, 0), |previous| self.ends[previous]);
+        let (values_end, positions_end) = self.ends[checkpoint];
+        ReadSliceDelta {
+            base: &self.values[values_start..values_end],
+            start: (values_end, positions_end),
+            ends: &self.ends[checkpoint + 1..=index],
+            values: &self.values,
+            positions: &self.positions,
+        }
+    }
+
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.values
+            .reserve(regions.clone().map(|r| r.values.len()).sum());
+        self.positions
+            .reserve(regions.clone().map(|r| r.positions.len()).sum());
+        self.ends
+            .reserve(regions.clone().map(|r| r.ends.len()).sum());
+        self.checkpoints
+            .reserve(regions.map(|r| r.checkpoints.len()).sum());
+    }
+
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.values
+            .try_reserve(regions.clone().map(|r| r.values.len()).sum())?;
+        self.positions
+            .try_reserve(regions.clone().map(|r| r.positions.len()).sum())?;
+        self.ends
+            .try_reserve(regions.clone().map(|r| r.ends.len()).sum())?;
+        self.checkpoints
+            .try_reserve(regions.map(|r| r.checkpoints.len()).sum())
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.positions.clear();
+        self.ends.clear();
+        self.checkpoints.clear();
+        self.last.clear();
+    }
+
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        fn vec_size<T, F: FnMut(usize, usize)>(vec: &Vec<T>, callback: &mut F) {
+            let size_of_t = std::mem::size_of::<T>();
+            callback(vec.len() * size_of_t, vec.capacity() * size_of_t);
+        }
+        vec_size(&self.values, &mut callback);
+        vec_size(&self.positions, &mut callback);
+        vec_size(&self.ends, &mut callback);
+        vec_size(&self.checkpoints, &mut callback);
+        vec_size(&self.last, &mut callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A slice read from a [`SliceDeltaRegion`], which reconstructs elements on access.
+pub struct ReadSliceDelta<'a, T> {
+    /// The checkpoint slice.
+    base: &'a [T],
+    /// The start of the first difference's values and positions.
+    start: (usize, usize),
+    /// The ends of the differences to apply to `base`, in push order.
+    ends: &'a [(usize, usize)],
+    /// The values of the region.
+    values: &'a [T],
+    /// The positions of the region.
+    positions: &'a [u32],
+}
+
+impl<'a, T> ReadSliceDelta<'a, T> {
+    /// Returns the number of elements in the slice.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.base.len()
+    }
+
+    /// Returns `true` if the slice is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.base.is_empty()
+    }
+
+    /// Returns the element at `index`, or `None` if out of bounds.
+    ///
+    /// Searches the differences from the most recent to the checkpoint.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        let position = u32::try_from(index).ok()?;
+        for delta in (0..self.ends.len()).rev() {
+            let (positions, values) = self.delta(delta);
+            if let Ok(offset) = positions.binary_search(&position) {
+                return Some(&values[offset]);
+            }
+        }
+        self.base.get(index)
+    }
+
+    /// Iterates the elements of the slice.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &'a T> + Clone + 'a {
+        let this = *self;
+        (0..self.len()).map(move |index| this.get(index).unwrap())
+    }
+
+    /// Returns the positions and values of the difference at `delta`.
+    #[inline]
+    fn delta(&self, delta: usize) -> (&'a [u32], &'a [T]) {
+        let (values_start, positions_start) = delta
+            .checked_sub(1)
+            .map_or(self.start, |previous| self.ends[previous]);
+        let (values_end, positions_end) = self.ends[delta];
+        (
+            &self.positions[positions_start..positions_end],
+            &self.values[values_start..values_end],
+        )
+    }
+}
+
+impl<T> Clone for ReadSliceDelta<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadSliceDelta<'_, T> {}
+
+impl<T: PartialEq> PartialEq for ReadSliceDelta<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ReadSliceDelta<'_, T> {}
+
+impl<T: Debug> Debug for ReadSliceDelta<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T: Clone> IntoOwned<'a> for ReadSliceDelta<'a, T> {
+    type Owned = Vec<T>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        let mut owned = Vec::new();
+        self.clone_onto(&mut owned);
+        owned
+    }
+
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend_from_slice(self.base);
+        for delta in 0..self.ends.len() {
+            let (positions, values) = self.delta(delta);
+            for (position, value) in positions.iter().zip(values) {
+                other[*position as usize].clone_from(value);
+            }
+        }
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self {
+            base: owned,
+            start: (0, 0),
+            ends: &[],
+            values: &[],
+            positions: &[],
+        }
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> Push<&[T]> for SliceDeltaRegion<T, INTERVAL> {
+    #[inline]
+    fn push(&mut self, item: &[T]) -> usize {
+        self.push_iter(item.iter())
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize, const N: usize> Push<&[T; N]>
+    for SliceDeltaRegion<T, INTERVAL>
+{
+    #[inline]
+    fn push(&mut self, item: &[T; N]) -> usize {
+        self.push(item.as_slice())
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> Push<&Vec<T>> for SliceDeltaRegion<T, INTERVAL> {
+    #[inline]
+    fn push(&mut self, item: &Vec<T>) -> usize {
+        self.push(item.as_slice())
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> Push<Vec<T>> for SliceDeltaRegion<T, INTERVAL> {
+    #[inline]
+    fn push(&mut self, item: Vec<T>) -> usize {
+        self.push(item.as_slice())
+    }
+}
+
+impl<T: Clone + PartialEq, const INTERVAL: usize> Push<ReadSliceDelta<'_, T>>
+    for SliceDeltaRegion<T, INTERVAL>
+{
+    #[inline]
+    fn push(&mut self, item: ReadSliceDelta<'_, T>) -> usize {
+        self.push_iter(item.iter())
+    }
+}
+
+impl<'a, T: Clone + PartialEq + 'a, const INTERVAL: usize> ReserveItems<&'a [T]>
+    for SliceDeltaRegion<T, INTERVAL>
+{
+    /// Reserves space to store all items in full.
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'a [T]> + Clone,
+    {
+        self.values.reserve(items.clone().map(<[T]>::len).sum());
+        self.ends.reserve(items.count());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, OwnedRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_frames() {
+        let mut frames = vec![vec![7_u8; 1024]; 3];
+        frames[1][10] = 8;
+        frames[2][10] = 9;
+        frames[2][1000] = 0;
+
+        let mut c = FlatStack::<SliceDeltaRegion<u8>>::default();
+        c.reserve_items(frames.iter().map(Vec::as_slice));
+        for frame in &frames {
+            c.copy(frame);
+        }
+        for (frame, read) in frames.iter().zip(c.iter()) {
+            assert_eq!(*frame, read.into_owned());
+            assert!(frame.iter().eq(read.iter()));
+            assert_eq!(read, IntoOwned::borrow_as(frame));
+        }
+        assert_eq!(Some(&8), c.get(1).get(10));
+        assert_eq!(Some(&7), c.get(1).get(1000));
+        assert_eq!(Some(&0), c.get(2).get(1000));
+        assert_eq!(None, c.get(2).get(1024));
+
+        let mut owned = FlatStack::<OwnedRegion<u8>>::default();
+        for frame in &frames {
+            owned.copy(frame);
+        }
+        let (mut delta_size, mut owned_size) = (0, 0);
+        c.heap_size(|siz, _| delta_size += siz);
+        owned.heap_size(|siz, _| owned_size += siz);
+        assert!(
+            delta_size < owned_size,
+            "delta: {delta_size}, owned: {owned_size}"
+        );
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let mut r = <SliceDeltaRegion<u32, 4>>::default();
+        let mut frame = vec![0_u32; 8];
+        let mut indices = Vec::new();
+        let mut frames = Vec::new();
+        for i in 0..10 {
+            frame[i % 8] += 1;
+            indices.push(r.push(&frame));
+            frames.push(frame.clone());
+        }
+        // A different length, and a frame that differs in every element.
+        indices.push(r.push([1, 2, 3].as_slice()));
+        frames.push(vec![1, 2, 3]);
+        indices.push(r.push([4, 5, 6].as_slice()));
+        frames.push(vec![4, 5, 6]);
+        indices.push(r.push(Vec::new()));
+        frames.push(Vec::new());
+
+        assert_eq!(vec![0, 4, 8, 10, 11, 12], r.checkpoints);
+        for (frame, index) in frames.iter().zip(indices) {
+            assert_eq!(*frame, r.index(index).into_owned());
+        }
+
+        let mut c = FlatStack::<SliceDeltaRegion<u32, 4>>::default();
+        c.copy(r.index(5));
+        assert_eq!(frames[5], c.get(0).into_owned());
+    }
+}
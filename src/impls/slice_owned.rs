@@ -1,12 +1,17 @@
 //! A region that stores slices of copy types.
 
+use std::borrow::Cow;
+use std::collections::TryReserveError;
+use std::io::{self, Write};
 use std::marker::PhantomData;
+use std::mem::MaybeUninit;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::impls::choose::CanMeasure;
 use crate::impls::storage::{PushStorage, Storage};
-use crate::{Push, PushIter, Region, ReserveItems};
+use crate::{FlatStack, Push, PushIter, Region, RegionLen, ReserveItems, TryPush};
 
 /// A container for owned types.
 ///
@@ -75,6 +80,12 @@ where
         &self.slices[start..end]
     }
 
+    /// Returns the number of bytes of the slice's elements.
+    #[inline]
+    fn item_storage_size(&self, (start, end): Self::Index) -> usize {
+        (end - start) * std::mem::size_of::<T>()
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -84,6 +95,15 @@ where
         self.slices.reserve_regions(regions.map(|r| &r.slices));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.slices.try_reserve_regions(regions.map(|r| &r.slices))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.slices.clear();
@@ -103,6 +123,130 @@ where
     }
 }
 
+impl<T, S> RegionLen for OwnedRegion<T, S>
+where
+    [T]: ToOwned,
+    S: Storage<T> + std::ops::Index<std::ops::Range<usize>, Output = [T]>,
+{
+    /// Returns the number of elements of all slices.
+    #[inline]
+    fn len(&self) -> usize {
+        self.slices.len()
+    }
+}
+
+impl<T, S> OwnedRegion<T, S>
+where
+    S: std::ops::IndexMut<std::ops::Range<usize>, Output = [T]>,
+{
+    /// Returns the slice at `index` for updating its elements in place.
+    ///
+    /// The returned slice has the length of the pushed slice and cannot grow or shrink, so
+    /// all indices remain valid. The updated elements are visible through every copy of
+    /// `index`, and it is up to the caller to ensure that this is intended.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{OwnedRegion, Push, Region};
+    /// let mut r = <OwnedRegion<u32>>::default();
+    ///
+    /// let index = r.push([1, 2, 3].as_slice());
+    /// r.index_mut(index)[1] += 40;
+    /// assert_eq!([1, 42, 3], r.index(index));
+    /// ```
+    #[inline]
+    #[allow(clippy::should_implement_trait)]
+    pub fn index_mut(&mut self, (start, end): (usize, usize)) -> &mut [T] {
+        &mut self.slices[start..end]
+    }
+}
+
+impl<S> OwnedRegion<u8, S>
+where
+    S: std::ops::Index<std::ops::Range<usize>, Output = [u8]>,
+{
+    /// Writes the bytes at `index` to `out`, and returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `out` if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{OwnedRegion, Push};
+    /// let mut r = <OwnedRegion<u8>>::default();
+    ///
+    /// let index = r.push(b"abc");
+    /// let mut out = Vec::new();
+    /// assert_eq!(3, r.write_to(index, &mut out).unwrap());
+    /// assert_eq!(b"abc", out.as_slice());
+    /// ```
+    #[inline]
+    pub fn write_to(
+        &self,
+        (start, end): (usize, usize),
+        out: &mut impl Write,
+    ) -> io::Result<usize> {
+        out.write_all(&self.slices[start..end])?;
+        Ok(end - start)
+    }
+}
+
+impl<T> OwnedRegion<T, Vec<T>> {
+    /// Pushes a slice of up to `len` elements that `fill` writes in place, without copying
+    /// them from a temporary buffer.
+    ///
+    /// Reserves `len` uninitialized slots at the end of the backing storage and passes them
+    /// to `fill`, which initializes a prefix of the slots and returns its length. Returns the
+    /// index of the initialized prefix. If `fill` panics, the region remains unchanged.
+    ///
+    /// # Safety
+    ///
+    /// `fill` must initialize the first `written` slots it receives, where `written` is the
+    /// value it returns. Reporting a slot that was not written exposes uninitialized memory.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fill` reports more than `len` written slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{OwnedRegion, Region};
+    /// let mut r = <OwnedRegion<u32>>::default();
+    ///
+    /// // SAFETY: The closure initializes the three slots it reports.
+    /// let index = unsafe {
+    ///     r.push_with(4, |slots| {
+    ///         for (slot, value) in slots.iter_mut().zip([1, 2, 3]) {
+    ///             slot.write(value);
+    ///         }
+    ///         3
+    ///     })
+    /// };
+    /// assert_eq!([1, 2, 3], r.index(index));
+    /// ```
+    #[inline]
+    pub unsafe fn push_with<F>(&mut self, len: usize, fill: F) -> (usize, usize)
+    where
+        F: FnOnce(&mut [MaybeUninit<T>]) -> usize,
+    {
+        let start = self.slices.len();
+        self.slices.reserve(len);
+        let written = fill(&mut self.slices.spare_capacity_mut()[..len]);
+        assert!(
+            written <= len,
+            "OwnedRegion: push_with wrote {written} elements into {len} slots"
+        );
+        // SAFETY: The capacity covers `len` slots, of which the caller guarantees that `fill`
+        // initialized the first `written`.
+        unsafe { self.slices.set_len(start + written) };
+        (start, start + written)
+    }
+}
+
 impl<T, S: Storage<T>> Default for OwnedRegion<T, S> {
     #[inline]
     fn default() -> Self {
@@ -183,6 +327,39 @@ where
     }
 }
 
+/// Pushes a slice, or fails without modifying the region if the storage cannot grow to hold it,
+/// for example because its end offset would exceed `usize`.
+impl<T, S> TryPush<&[T]> for OwnedRegion<T, S>
+where
+    T: Clone,
+    S: Storage<T>
+        + for<'a> PushStorage<&'a [T]>
+        + std::ops::Index<std::ops::Range<usize>, Output = [T]>,
+{
+    type Error = TryReserveError;
+
+    #[inline]
+    fn try_push(&mut self, item: &[T]) -> Result<Self::Index, Self::Error> {
+        self.slices.try_reserve(item.len())?;
+        let start = self.slices.len();
+        self.slices.push_storage(item);
+        Ok((start, self.slices.len()))
+    }
+}
+
+impl<T, S> CanMeasure<&[T]> for OwnedRegion<T, S>
+where
+    T: Clone,
+    S: Storage<T>
+        + for<'a> PushStorage<&'a [T]>
+        + std::ops::Index<std::ops::Range<usize>, Output = [T]>,
+{
+    #[inline]
+    fn measure(&self, item: &&[T]) -> usize {
+        std::mem::size_of_val(*item)
+    }
+}
+
 impl<T: Clone, S: Storage<T>> Push<&&[T]> for OwnedRegion<T, S>
 where
     for<'a> Self: Push<&'a [T]>,
@@ -222,6 +399,21 @@ where
     }
 }
 
+/// Pushes a borrowed slice by copying it, and an owned slice by moving its elements.
+impl<'b, T, S> Push<Cow<'b, [T]>> for OwnedRegion<T, S>
+where
+    T: Clone,
+    Self: Push<&'b [T]> + Push<Vec<T>>,
+{
+    #[inline]
+    fn push(&mut self, item: Cow<'b, [T]>) -> <OwnedRegion<T, S> as Region>::Index {
+        match item {
+            Cow::Borrowed(item) => self.push(item),
+            Cow::Owned(item) => self.push(item),
+        }
+    }
+}
+
 impl<T, S> Push<&Vec<T>> for OwnedRegion<T, S>
 where
     T: Clone,
@@ -266,6 +458,23 @@ where
     }
 }
 
+/// Pushes the elements of an iterator whose type is erased. Unlike [`PushIter`], the iterator
+/// does not need to know its length, at the cost of not pre-sizing the storage.
+impl<T, S> Push<&mut dyn Iterator<Item = T>> for OwnedRegion<T, S>
+where
+    T: Clone,
+    S: Storage<T>
+        + for<'a, 'b> PushStorage<PushIter<&'a mut (dyn Iterator<Item = T> + 'b)>>
+        + std::ops::Index<std::ops::Range<usize>, Output = [T]>,
+{
+    #[inline]
+    fn push(&mut self, item: &mut dyn Iterator<Item = T>) -> <OwnedRegion<T, S> as Region>::Index {
+        let start = self.slices.len();
+        self.slices.push_storage(PushIter(item));
+        (start, self.slices.len())
+    }
+}
+
 impl<T, S, J> ReserveItems<PushIter<J>> for OwnedRegion<T, S>
 where
     [T]: ToOwned,
@@ -282,6 +491,85 @@ where
     }
 }
 
+impl<T: Clone> FlatStack<OwnedRegion<T>, Vec<(usize, usize)>> {
+    /// Iterate the slices in this stack.
+    ///
+    /// Produces the same items as [`FlatStack::iter`], but walks the region's backing
+    /// storage directly: slices pushed one after the other are adjacent in the storage, which
+    /// allows the iterator to split each slice off the remaining storage instead of looking up
+    /// its range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, OwnedRegion};
+    /// let mut c = FlatStack::<OwnedRegion<u8>>::default();
+    /// c.copy(b"abc");
+    /// c.copy(b"de");
+    ///
+    /// assert!(c.iter_slices().eq(c.iter()));
+    /// assert!(c.iter_slices().eq([&b"abc"[..], &b"de"[..]]));
+    /// ```
+    #[inline]
+    pub fn iter_slices(&self) -> OwnedSliceIter<'_, T> {
+        OwnedSliceIter {
+            indices: self.indices.iter(),
+            storage: &self.region.slices,
+            remaining: &self.region.slices,
+            position: 0,
+        }
+    }
+}
+
+/// An iterator over the slices of a [`FlatStack`] of [`OwnedRegion`]s, see
+/// [`FlatStack::iter_slices`].
+pub struct OwnedSliceIter<'a, T> {
+    /// Iterator over the indices.
+    indices: std::slice::Iter<'a, (usize, usize)>,
+    /// The region's backing storage.
+    storage: &'a [T],
+    /// The storage following the last slice.
+    remaining: &'a [T],
+    /// The offset of `remaining` in `storage`.
+    position: usize,
+}
+
+impl<'a, T> Iterator for OwnedSliceIter<'a, T> {
+    type Item = &'a [T];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let &(start, end) = self.indices.next()?;
+        let (item, remaining) = if start == self.position {
+            self.remaining.split_at(end - start)
+        } else {
+            // The slice does not follow the previous one.
+            self.storage[start..].split_at(end - start)
+        };
+        self.remaining = remaining;
+        self.position = end;
+        Some(item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for OwnedSliceIter<'_, T> {}
+
+impl<T> Clone for OwnedSliceIter<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            indices: self.indices.clone(),
+            storage: self.storage,
+            remaining: self.remaining,
+            position: self.position,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{Push, PushIter, Region, ReserveItems};
@@ -322,4 +610,125 @@ mod tests {
         let index = r.push(PushIter(iter));
         assert_eq!([1, 1, 1, 1], r.index(index));
     }
+
+    #[test]
+    fn test_copy_dyn_iter() {
+        let mut r = <OwnedRegion<u8>>::default();
+        let mut iter = (1..10u8).filter(|i| i % 3 == 0);
+        let index = r.push(&mut iter as &mut dyn Iterator<Item = u8>);
+        assert_eq!([3, 6, 9], r.index(index));
+        let index = r.push(&mut std::iter::empty() as &mut dyn Iterator<Item = u8>);
+        assert!(r.index(index).is_empty());
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut r = <OwnedRegion<u32>>::default();
+        let counters = r.push([0, 0, 0].as_slice());
+        let other = r.push([7].as_slice());
+
+        for position in [0, 2, 2] {
+            r.index_mut(counters)[position] += 1;
+        }
+        assert_eq!([1, 0, 2], r.index(counters));
+        assert_eq!([7], r.index(other));
+
+        r.index_mut(other).copy_from_slice(&[8]);
+        assert_eq!([8], r.index(other));
+    }
+
+    #[test]
+    fn test_iter_slices() {
+        let mut c = FlatStack::<OwnedRegion<u8>>::default();
+        for i in 0..100_u8 {
+            c.copy(vec![i; usize::from(i % 7)]);
+        }
+        assert_eq!(c.len(), c.iter_slices().len());
+        assert!(c.iter_slices().eq(c.iter()));
+
+        c.clear();
+        assert_eq!(None, c.iter_slices().next());
+        c.copy([1, 2, 3]);
+        c.copy([]);
+        c.copy([4]);
+        assert!(c.iter_slices().eq(c.iter()));
+        assert!(c.iter_slices().eq([&[1, 2, 3][..], &[], &[4]]));
+    }
+
+    #[test]
+    fn test_try_push() {
+        let mut r = <OwnedRegion<u8>>::default();
+        let index = r.try_push(&[1, 2, 3][..]).unwrap();
+        assert_eq!((0, 3), index);
+        assert_eq!(Ok((3, 5)), r.try_push(&[4, 5][..]));
+        assert_eq!([1, 2, 3], r.index(index));
+    }
+
+    #[test]
+    fn test_try_push_overflow() {
+        let mut r = <OwnedRegion<()>>::default();
+        let index = r.push([()].as_slice());
+
+        // The end offset of the second slice would exceed `usize`.
+        assert!(r.try_push([(); usize::MAX].as_slice()).is_err());
+        assert_eq!(1, r.len());
+        assert_eq!(1, r.index(index).len());
+        assert_eq!(Ok((1, 2)), r.try_push([()].as_slice()));
+    }
+
+    #[test]
+    fn test_push_with() {
+        let mut r = <OwnedRegion<u64>>::default();
+        let before = r.push([7].as_slice());
+
+        // SAFETY: The closure initializes all slots.
+        let index = unsafe {
+            r.push_with(100, |slots| {
+                for (value, slot) in (0..).zip(slots.iter_mut()) {
+                    slot.write(value * value);
+                }
+                slots.len()
+            })
+        };
+        // SAFETY: The closure reports no initialized slots.
+        let empty = unsafe { r.push_with(10, |_| 0) };
+        let after = r.push([8].as_slice());
+
+        assert_eq!(100, r.index(index).len());
+        assert!(r.index(index).iter().copied().eq((0..100).map(|v| v * v)));
+        assert!(r.index(empty).is_empty());
+        assert_eq!([7], r.index(before));
+        assert_eq!([8], r.index(after));
+    }
+
+    #[test]
+    fn test_push_with_panic() {
+        let mut r = <OwnedRegion<String>>::default();
+        let index = r.push(["a".to_string()].as_slice());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            // SAFETY: The closure reports no initialized slots, as it panics.
+            let _ = unsafe { r.push_with(2, |_| panic!("fill")) };
+        }));
+        assert!(result.is_err());
+        assert_eq!(1, r.len());
+        assert_eq!("a", r.index(index)[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "OwnedRegion: push_with wrote 2 elements into 1 slots")]
+    fn test_push_with_overreport() {
+        let mut r = <OwnedRegion<u8>>::default();
+        // SAFETY: The region panics before reading the reported slots.
+        let _ = unsafe { r.push_with(1, |_| 2) };
+    }
+
+    #[test]
+    fn test_push_cow() {
+        let mut r = <OwnedRegion<String>>::default();
+        let items = ["a".to_string(), "b".to_string()];
+        let borrowed = r.push(Cow::Borrowed(items.as_slice()));
+        let owned = r.push(Cow::<[_]>::Owned(items.to_vec()));
+        assert_eq!(items, r.index(borrowed));
+        assert_eq!(r.index(borrowed), r.index(owned));
+    }
 }
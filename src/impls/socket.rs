@@ -0,0 +1,201 @@
+//! A region that stores socket addresses.
+
+use std::collections::TryReserveError;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::tuple::TupleABCRegion;
+use crate::{IntoOwned, MirrorRegion, Push, Region, RegionPreference, ReserveItems};
+
+/// A region for IPv4 and IPv6 socket addresses.
+///
+/// The region does not allocate: an index holds the IP address widened to a `u128`, a flag
+/// that is set for IPv6 addresses, and the port, and reading an index rebuilds the socket
+/// address from them. An IPv4-mapped IPv6 address stays an IPv6 address.
+///
+/// The region drops the flow info and scope ID of IPv6 socket addresses, which read as zero.
+/// Applications that rely on link-local scopes need to store the scope ID separately.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::SocketAddr;
+/// use flatcontainer::impls::socket::SocketAddrRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = SocketAddrRegion::default();
+///
+/// let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+/// let index = r.push(addr);
+/// assert_eq!(addr, r.index(index));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SocketAddrRegion {
+    /// Storage for the addresses, address families, and ports.
+    inner: TupleABCRegion<MirrorRegion<u128>, MirrorRegion<bool>, MirrorRegion<u16>>,
+}
+
+impl RegionPreference for SocketAddr {
+    type Owned = Self;
+    type Region = SocketAddrRegion;
+}
+
+impl<'a> IntoOwned<'a> for SocketAddr {
+    type Owned = SocketAddr;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        self
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self;
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        *owned
+    }
+}
+
+impl Region for SocketAddrRegion {
+    type Owned = SocketAddr;
+    type ReadItem<'a> = SocketAddr;
+    /// The address, whether it is an IPv6 address, and the port.
+    type Index = (u128, bool, u16);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: TupleABCRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        match self.inner.index(index) {
+            (address, false, port) => {
+                let address = u32::try_from(address).expect("Index must originate from IPv4");
+                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(address), port))
+            }
+            (address, true, port) => {
+                SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::from(address), port, 0, 0))
+            }
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<SocketAddr> for SocketAddrRegion {
+    #[inline]
+    fn push(&mut self, item: SocketAddr) -> (u128, bool, u16) {
+        match item {
+            SocketAddr::V4(addr) => {
+                self.inner
+                    .push((u128::from(u32::from(*addr.ip())), false, addr.port()))
+            }
+            SocketAddr::V6(addr) => self.inner.push((u128::from(*addr.ip()), true, addr.port())),
+        }
+    }
+}
+
+impl Push<&SocketAddr> for SocketAddrRegion {
+    #[inline]
+    fn push(&mut self, item: &SocketAddr) -> (u128, bool, u16) {
+        self.push(*item)
+    }
+}
+
+impl<T> ReserveItems<T> for SocketAddrRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_ipv4() {
+        let addr: SocketAddr = "192.168.1.1:443".parse().unwrap();
+        let mut r = SocketAddrRegion::default();
+        let index = r.push(&addr);
+        assert_eq!(addr, r.index(index));
+        assert!(r.index(index).is_ipv4());
+        assert_eq!(443, r.index(index).port());
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let addrs: [SocketAddr; 3] = [
+            "[2001:db8::1]:8080".parse().unwrap(),
+            // An IPv4-mapped address stays an IPv6 address.
+            "[::ffff:192.168.1.1]:443".parse().unwrap(),
+            "0.0.0.0:0".parse().unwrap(),
+        ];
+        let mut c = FlatStack::<<SocketAddr as RegionPreference>::Region>::default();
+        for addr in addrs {
+            c.copy(addr);
+        }
+        assert!(c.iter().eq(addrs));
+        assert!(c.get(1).is_ipv6());
+        assert_eq!(addrs[0], c.get(0).into_owned());
+    }
+
+    #[test]
+    fn test_ipv6_drops_flow_info_and_scope_id() {
+        let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 7, 3);
+        let mut r = SocketAddrRegion::default();
+        let index = r.push(SocketAddr::V6(addr));
+        let expected = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 8080, 0, 0);
+        assert_eq!(SocketAddr::V6(expected), r.index(index));
+    }
+}
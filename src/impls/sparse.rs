@@ -0,0 +1,436 @@
+//! A region that stores sparse vectors.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::slice::ReadSlice;
+use crate::{Index, IntoOwned, MirrorRegion, Push, Region, ReserveItems, SliceRegion};
+
+/// A region to hold sparse vectors, which are mostly zero.
+///
+/// Stores the positions and values of the non-zero entries of each vector in two parallel
+/// slice regions, and records the logical length of the vector in the index. The read item
+/// is a [`ReadSparseVec`], which returns the zero value, [`T::default()`](Default), for
+/// absent positions.
+///
+/// The owned representation is a tuple of the length, the strictly increasing positions of
+/// the entries, and their values.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::sparse::SparseVecRegion;
+/// use flatcontainer::{Push, Region};
+/// let mut r = <SparseVecRegion<f32>>::default();
+///
+/// let index = r.push((100, [3, 42].as_slice(), [1.5, -2.0].as_slice()));
+/// assert_eq!(100, r.index(index).len());
+/// assert_eq!(-2.0, r.index(index).get(42));
+/// assert_eq!(0.0, r.index(index).get(41));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "T: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T>,
+{
+    /// Positions of entries.
+    positions: SliceRegion<MirrorRegion<u32>>,
+    /// Values of entries.
+    values: SliceRegion<MirrorRegion<T>>,
+}
+
+impl<T> Clone for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            positions: self.positions.clone(),
+            values: self.values.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.positions.clone_from(&source.positions);
+        self.values.clone_from(&source.values);
+    }
+}
+
+impl<T> Default for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T>,
+{
+    fn default() -> Self {
+        Self {
+            positions: SliceRegion::default(),
+            values: SliceRegion::default(),
+        }
+    }
+}
+
+impl<T> Region for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T> + Default,
+{
+    type Owned = (usize, Vec<u32>, Vec<T>);
+    type ReadItem<'a> = ReadSparseVec<'a, T> where Self: 'a;
+    /// The length of the vector, and the index of its entries in both slice regions.
+    type Index = (usize, (usize, usize));
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            positions: SliceRegion::merge_regions(regions.clone().map(|r| &r.positions)),
+            values: SliceRegion::merge_regions(regions.map(|r| &r.values)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (len, entries): Self::Index) -> Self::ReadItem<'_> {
+        ReadSparseVec {
+            len,
+            positions: self.positions.index(entries),
+            values: self.values.index(entries),
+        }
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.positions
+            .reserve_regions(regions.clone().map(|r| &r.positions));
+        self.values.reserve_regions(regions.map(|r| &r.values));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.positions
+            .try_reserve_regions(regions.clone().map(|r| &r.positions))?;
+        self.values.try_reserve_regions(regions.map(|r| &r.values))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.positions.clear();
+        self.values.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.positions.heap_size(&mut callback);
+        self.values.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+/// A sparse vector read from a [`SparseVecRegion`].
+pub struct ReadSparseVec<'a, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T>,
+{
+    /// The logical length.
+    len: usize,
+    /// Positions of entries, strictly increasing.
+    positions: ReadSlice<'a, MirrorRegion<u32>>,
+    /// Values of entries.
+    values: ReadSlice<'a, MirrorRegion<T>>,
+}
+
+impl<'a, T> ReadSparseVec<'a, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T> + Default,
+{
+    /// Returns the logical length of the vector.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the vector has a length of zero.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of stored entries.
+    #[inline]
+    #[must_use]
+    pub fn entries(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns the element at position `i`, which is zero if the vector has no entry for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds.
+    #[must_use]
+    pub fn get(&self, i: usize) -> T {
+        assert!(
+            i < self.len,
+            "ReadSparseVec: index {i} out of bounds for length {}",
+            self.len
+        );
+        let (mut lo, mut hi) = (0, self.positions.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match (self.positions.get(mid) as usize).cmp(&i) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return self.values.get(mid),
+            }
+        }
+        T::default()
+    }
+
+    /// Returns an iterator over the positions and values of the stored entries, in order of
+    /// position.
+    #[inline]
+    pub fn iter_entries(&self) -> impl Iterator<Item = (usize, T)> + Clone + 'a {
+        self.positions
+            .into_iter()
+            .map(|position| position as usize)
+            .zip(self.values)
+    }
+
+    /// Returns an iterator over all elements, including zeros.
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        let mut entries = self.iter_entries().peekable();
+        (0..self.len).map(move |i| {
+            entries
+                .next_if(|(position, _)| *position == i)
+                .map_or_else(T::default, |(_, value)| value)
+        })
+    }
+}
+
+impl<T> Clone for ReadSparseVec<'_, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T>,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadSparseVec<'_, T> where for<'b> T: Index + IntoOwned<'b, Owned = T> {}
+
+impl<T> PartialEq for ReadSparseVec<'_, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T> + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self.positions.iter().eq(other.positions)
+            && self.values.iter().eq(other.values)
+    }
+}
+
+impl<T> Debug for ReadSparseVec<'_, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T> + Default + Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReadSparseVec")
+            .field("len", &self.len)
+            .field("entries", &self.iter_entries().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<'a, T> IntoOwned<'a> for ReadSparseVec<'a, T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T> + Default,
+{
+    type Owned = (usize, Vec<u32>, Vec<T>);
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        (
+            self.len,
+            self.positions.into_owned(),
+            self.values.into_owned(),
+        )
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.0 = self.len;
+        self.positions.clone_onto(&mut other.1);
+        self.values.clone_onto(&mut other.2);
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self {
+            len: owned.0,
+            positions: IntoOwned::borrow_as(&owned.1),
+            values: IntoOwned::borrow_as(&owned.2),
+        }
+    }
+}
+
+impl<T> Push<(usize, &[u32], &[T])> for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T> + Default,
+{
+    /// Pushes a vector of length `len` with entries at `positions` holding `values`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of positions and values differ, or if the positions are not
+    /// strictly increasing and less than `len`.
+    #[inline]
+    fn push(&mut self, (len, positions, values): (usize, &[u32], &[T])) -> Self::Index {
+        assert_eq!(
+            positions.len(),
+            values.len(),
+            "SparseVecRegion: {} positions do not match {} values",
+            positions.len(),
+            values.len()
+        );
+        assert!(
+            positions.windows(2).all(|w| w[0] < w[1])
+                && positions.last().map_or(true, |last| (*last as usize) < len),
+            "SparseVecRegion: positions must be strictly increasing and less than {len}"
+        );
+        let entries = self.positions.push(positions);
+        let values = self.values.push(values);
+        debug_assert_eq!(entries, values);
+        (len, entries)
+    }
+}
+
+impl<T> Push<&(usize, Vec<u32>, Vec<T>)> for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T> + Default,
+{
+    #[inline]
+    fn push(&mut self, item: &(usize, Vec<u32>, Vec<T>)) -> Self::Index {
+        self.push((item.0, item.1.as_slice(), item.2.as_slice()))
+    }
+}
+
+impl<T> Push<(usize, Vec<u32>, Vec<T>)> for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T> + Default,
+{
+    #[inline]
+    fn push(&mut self, item: (usize, Vec<u32>, Vec<T>)) -> Self::Index {
+        self.push(&item)
+    }
+}
+
+impl<T> Push<ReadSparseVec<'_, T>> for SparseVecRegion<T>
+where
+    for<'a> T: Index + IntoOwned<'a, Owned = T> + Default,
+{
+    #[inline]
+    fn push(&mut self, item: ReadSparseVec<'_, T>) -> Self::Index {
+        let entries = self.positions.push(item.positions);
+        let values = self.values.push(item.values);
+        debug_assert_eq!(entries, values);
+        (item.len, entries)
+    }
+}
+
+impl<'a, T> ReserveItems<(usize, &'a [u32], &'a [T])> for SparseVecRegion<T>
+where
+    for<'b> T: Index + IntoOwned<'b, Owned = T> + Default,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = (usize, &'a [u32], &'a [T])> + Clone,
+    {
+        self.positions
+            .reserve_items(items.clone().map(|(_, positions, _)| positions));
+        self.values
+            .reserve_items(items.map(|(_, _, values)| values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_sparse() {
+        let mut r = <SparseVecRegion<i64>>::default();
+        let index = r.push((1000, [7, 500, 999].as_slice(), [3, -1, 8].as_slice()));
+        let v = r.index(index);
+
+        assert_eq!(1000, v.len());
+        assert_eq!(3, v.entries());
+        assert_eq!(3, v.get(7));
+        assert_eq!(-1, v.get(500));
+        assert_eq!(8, v.get(999));
+        assert_eq!(0, v.get(0));
+        assert_eq!(0, v.get(8));
+        assert_eq!(0, v.get(998));
+        assert!(v.iter_entries().eq([(7, 3), (500, -1), (999, 8)]));
+        assert_eq!(1000, v.iter().count());
+        assert_eq!(10, v.iter().sum::<i64>());
+        assert_eq!(v, ReadSparseVec::borrow_as(&v.into_owned()));
+    }
+
+    #[test]
+    fn test_flat_stack() {
+        let vectors = [
+            (4, vec![0, 3], vec![1.0_f64, 2.0]),
+            (0, vec![], vec![]),
+            (2, vec![], vec![]),
+        ];
+        let mut c = FlatStack::<SparseVecRegion<f64>>::default();
+        c.reserve_items(
+            vectors
+                .iter()
+                .map(|(len, p, v)| (*len, p.as_slice(), v.as_slice())),
+        );
+        for vector in &vectors {
+            c.copy(vector);
+        }
+        for (vector, item) in vectors.iter().zip(c.iter()) {
+            assert_eq!(vector, &item.into_owned());
+        }
+        assert!(c.get(0).iter().eq([1.0, 0.0, 0.0, 2.0]));
+        assert!(c.get(2).iter().eq([0.0, 0.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "SparseVecRegion: positions must be strictly increasing")]
+    fn test_unsorted() {
+        let mut r = <SparseVecRegion<u8>>::default();
+        let _ = r.push((10, [4, 2].as_slice(), [1, 1].as_slice()));
+    }
+}
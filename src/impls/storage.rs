@@ -1,5 +1,7 @@
 //! Storage abstractions to represent slices of data.
 
+use std::collections::TryReserveError;
+
 use crate::PushIter;
 
 /// Behavior to allocate storage.
@@ -35,6 +37,26 @@ pub trait Storage<T>: Default {
         self.reserve(regions.map(Self::len).sum());
     }
 
+    /// Reserve space for `additional` elements, or report that the allocation failed.
+    ///
+    /// The default implementation calls [`Storage::reserve`], which aborts on allocation
+    /// failure.
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.reserve(additional);
+        Ok(())
+    }
+
+    /// Reserve space for `regions`, or report that the allocation failed.
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.try_reserve(regions.map(Self::len).sum())
+    }
+
     /// Clear all contents, possibly retaining some allocations.
     fn clear(&mut self);
 
@@ -61,6 +83,11 @@ impl<T> Storage<T> for Vec<T> {
         Vec::reserve(self, additional);
     }
 
+    #[inline]
+    fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        Vec::try_reserve(self, additional)
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.clear();
@@ -73,12 +100,14 @@ impl<T> Storage<T> for Vec<T> {
     }
 
     #[inline]
+    #[allow(unused_attributes)]
     #[must_use]
     fn len(&self) -> usize {
         self.len()
     }
 
     #[inline]
+    #[allow(unused_attributes)]
     #[must_use]
     fn is_empty(&self) -> bool {
         self.is_empty()
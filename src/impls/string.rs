@@ -1,10 +1,15 @@
 //! A region that stores strings.
 
+use std::borrow::Cow;
+use std::collections::TryReserveError;
+use std::io::{self, Write};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+use crate::impls::choose::CanMeasure;
 use crate::impls::slice_owned::OwnedRegion;
-use crate::{Push, Region, RegionPreference, ReserveItems};
+use crate::{Push, PushIter, Region, RegionLen, RegionPreference, ReserveItems};
 
 /// A region to store strings and read `&str`.
 ///
@@ -72,6 +77,12 @@ where
         unsafe { std::str::from_utf8_unchecked(self.inner.index(index)) }
     }
 
+    /// Returns the utf-8 bytes of the string.
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        Some(self.inner.index(index))
+    }
+
     #[inline]
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
@@ -81,6 +92,15 @@ where
         self.inner.reserve_regions(regions.map(|r| &r.inner));
     }
 
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
     #[inline]
     fn clear(&mut self) {
         self.inner.clear();
@@ -100,6 +120,47 @@ where
     }
 }
 
+impl<R> RegionLen for StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + RegionLen + 'a,
+{
+    /// Returns the number of bytes of all strings.
+    #[inline]
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<R> StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
+{
+    /// Writes the utf-8 bytes of the string at `index` to `out`, and returns the number of
+    /// bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `out` if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{Push, StringRegion};
+    /// let mut r = <StringRegion>::default();
+    ///
+    /// let index = r.push("grüezi");
+    /// let mut out = Vec::new();
+    /// assert_eq!(7, r.write_str_to(index, &mut out).unwrap());
+    /// assert_eq!("grüezi".as_bytes(), out.as_slice());
+    /// ```
+    #[inline]
+    pub fn write_str_to(&self, index: R::Index, out: &mut impl Write) -> io::Result<usize> {
+        let bytes = self.inner.index(index);
+        out.write_all(bytes)?;
+        Ok(bytes.len())
+    }
+}
+
 impl RegionPreference for String {
     type Owned = Self;
     type Region = StringRegion;
@@ -130,6 +191,16 @@ where
     }
 }
 
+impl<R> Push<Cow<'_, str>> for StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + Push<&'a [u8]> + 'a,
+{
+    #[inline]
+    fn push(&mut self, item: Cow<'_, str>) -> <StringRegion<R> as Region>::Index {
+        self.push(item.as_ref())
+    }
+}
+
 impl<'b, R> ReserveItems<&'b String> for StringRegion<R>
 where
     for<'a> R: Region<ReadItem<'a> = &'a [u8]> + ReserveItems<&'a [u8]> + 'a,
@@ -153,6 +224,26 @@ where
     }
 }
 
+impl<R> CanMeasure<&str> for StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + CanMeasure<&'a [u8]> + 'a,
+{
+    #[inline]
+    fn measure(&self, item: &&str) -> usize {
+        self.inner.measure(&item.as_bytes())
+    }
+}
+
+impl<R> CanMeasure<&&str> for StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + CanMeasure<&'a [u8]> + 'a,
+{
+    #[inline]
+    fn measure(&self, item: &&&str) -> usize {
+        self.measure(*item)
+    }
+}
+
 impl<R> Push<&&str> for StringRegion<R>
 where
     for<'a> R: Region<ReadItem<'a> = &'a [u8]> + Push<&'a [u8]> + 'a,
@@ -163,6 +254,61 @@ where
     }
 }
 
+/// Pushes the characters of an iterator as a string, without collecting them into a
+/// [`String`] first.
+impl<R, I> Push<PushIter<I>> for StringRegion<R>
+where
+    for<'a> R: Region<ReadItem<'a> = &'a [u8]> + 'a,
+    R: for<'a> Push<&'a mut (dyn Iterator<Item = u8> + 'a)>,
+    I: IntoIterator<Item = char>,
+{
+    #[inline]
+    fn push(&mut self, item: PushIter<I>) -> <StringRegion<R> as Region>::Index {
+        let mut bytes = Utf8Bytes {
+            chars: item.0.into_iter(),
+            buffer: [0; 4],
+            offset: 0,
+            len: 0,
+        };
+        self.inner.push(&mut bytes as &mut dyn Iterator<Item = u8>)
+    }
+}
+
+/// An iterator over the utf-8 bytes of characters, which encodes one character at a time.
+struct Utf8Bytes<I> {
+    chars: I,
+    /// The encoding of the current character.
+    buffer: [u8; 4],
+    /// The offset of the next byte in `buffer`.
+    offset: usize,
+    /// The length of the encoding in `buffer`.
+    len: usize,
+}
+
+impl<I: Iterator<Item = char>> Iterator for Utf8Bytes<I> {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.offset == self.len {
+            self.len = self.chars.next()?.encode_utf8(&mut self.buffer).len();
+            self.offset = 0;
+        }
+        self.offset += 1;
+        Some(self.buffer[self.offset - 1])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.chars.size_hint();
+        let pending = self.len - self.offset;
+        (
+            lower.saturating_add(pending),
+            upper.and_then(|upper| upper.checked_mul(4)?.checked_add(pending)),
+        )
+    }
+}
+
 impl<'b, R> ReserveItems<&'b str> for StringRegion<R>
 where
     for<'a> R: Region<ReadItem<'a> = &'a [u8]> + ReserveItems<&'a [u8]> + 'a,
@@ -191,7 +337,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::{IntoOwned, Push, Region, ReserveItems, StringRegion};
+    use crate::{IntoOwned, Push, PushIter, Region, ReserveItems, StringRegion};
 
     #[test]
     fn test_inner() {
@@ -200,6 +346,20 @@ mod tests {
         assert_eq!(r.index(index), "abc");
     }
 
+    #[test]
+    fn test_push_chars() {
+        let mut r = <StringRegion>::default();
+        let index = r.push(PushIter("héllo".chars()));
+        assert_eq!("héllo", r.index(index));
+
+        let empty = r.push(PushIter(std::iter::empty()));
+        let mixed = r.push(PushIter(['a', 'ß', '€', '😀']));
+        assert_eq!("", r.index(empty));
+        assert_eq!("aß€😀", r.index(mixed));
+        assert_eq!(10, r.index(mixed).len());
+        assert_eq!("héllo", r.index(index));
+    }
+
     #[test]
     fn test_reserve_items_str() {
         let mut r = <StringRegion>::default();
@@ -255,4 +415,15 @@ mod tests {
         let idx = r.push(owned);
         assert_eq!("abc", r.index(idx));
     }
+
+    #[test]
+    fn test_push_cow() {
+        use std::borrow::Cow;
+
+        let mut r = <StringRegion>::default();
+        let borrowed = r.push(Cow::Borrowed("abc"));
+        let owned = r.push(Cow::<str>::Owned("abc".to_string()));
+        assert_eq!("abc", r.index(borrowed));
+        assert_eq!(r.index(borrowed), r.index(owned));
+    }
 }
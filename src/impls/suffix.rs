@@ -0,0 +1,407 @@
+//! A region that shares the storage of common suffixes between slices.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, OwnedRegion, Push, Region};
+
+/// The number of recently pushed slices that a [`SuffixShareRegion`] compares new slices to.
+const WINDOW: usize = 8;
+
+/// A region for slices that often end with the same elements, such as domain names, or
+/// paths that share a file name.
+///
+/// The region compares each pushed slice to the recently pushed slices, and if it ends with
+/// the same elements as one of them, only stores the distinct head of the slice, and refers
+/// to the storage of the shared suffix. A slice that is a suffix of a recent slice does not
+/// occupy any storage. Slices that share a prefix instead, like reversed domain names, can
+/// be reversed before pushing them.
+///
+/// The read item is a [`ReadSuffixShare`], which consists of a head and a tail slice, and
+/// the index records the ranges of both.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::suffix::SuffixShareRegion;
+/// use flatcontainer::{IntoOwned, Push, Region};
+/// let mut r = <SuffixShareRegion<u8>>::default();
+///
+/// let _ = r.push(b"a.example.com".as_slice());
+/// let b = r.push(b"b.example.com".as_slice());
+/// assert_eq!(b"b.example.com".to_vec(), r.index(b).into_owned());
+/// assert_eq!(b"b", r.index(b).head());
+///
+/// let mut size = 0;
+/// r.heap_size(|siz, _| size += siz);
+/// assert_eq!(b"a.example.comb".len(), size);
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "T: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct SuffixShareRegion<T> {
+    /// Storage for heads and suffixes.
+    inner: OwnedRegion<T>,
+    /// The indices of the most recently pushed slices, in no particular order.
+    recent: Vec<((usize, usize), (usize, usize))>,
+    /// The position in `recent` to replace next.
+    next: usize,
+}
+
+impl<T> Default for SuffixShareRegion<T> {
+    fn default() -> Self {
+        Self {
+            inner: OwnedRegion::default(),
+            recent: Vec::default(),
+            next: 0,
+        }
+    }
+}
+
+impl<T: Clone> Clone for SuffixShareRegion<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            recent: self.recent.clone(),
+            next: self.next,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.recent.clone_from(&source.recent);
+        self.next = source.next;
+    }
+}
+
+impl<T: Clone> Region for SuffixShareRegion<T> {
+    type Owned = Vec<T>;
+    type ReadItem<'a> = ReadSuffixShare<'a, T> where Self: 'a;
+    /// The ranges of the head and the tail of the slice.
+    type Index = ((usize, usize), (usize, usize));
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: OwnedRegion::merge_regions(regions.map(|r| &r.inner)),
+            recent: Vec::default(),
+            next: 0,
+        }
+    }
+
+    #[inline]
+    fn index(&self, (head, tail): Self::Index) -> Self::ReadItem<'_> {
+        ReadSuffixShare {
+            head: self.inner.index(head),
+            tail: self.inner.index(tail),
+        }
+    }
+
+    /// Returns the number of elements the slice does not share with other slices.
+    #[inline]
+    fn item_storage_size(&self, ((start, end), _): Self::Index) -> usize {
+        (end - start) * std::mem::size_of::<T>()
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.recent.clear();
+        self.next = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl<T: Clone + PartialEq> SuffixShareRegion<T> {
+    /// Returns the range of the longest suffix of `item` that the storage of a recent slice
+    /// ends with, or `None` if no recent slice ends like `item`.
+    fn shared_suffix(&self, item: &[T]) -> Option<(usize, usize)> {
+        self.recent
+            .iter()
+            .filter_map(|&(head, tail)| {
+                let recent = self.index((head, tail));
+                let mut len = recent
+                    .iter()
+                    .rev()
+                    .zip(item.iter().rev())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                // The suffix must be contiguous in storage.
+                if head.1 != tail.0 {
+                    len = len.min(tail.1 - tail.0);
+                }
+                (len > 0).then_some((tail.1 - len, tail.1))
+            })
+            .max_by_key(|(start, end)| end - start)
+    }
+}
+
+impl<T: Clone + PartialEq> Push<&[T]> for SuffixShareRegion<T> {
+    #[inline]
+    fn push(&mut self, item: &[T]) -> <SuffixShareRegion<T> as Region>::Index {
+        let index = match self.shared_suffix(item) {
+            Some(tail) => {
+                let head = self.inner.push(&item[..item.len() - (tail.1 - tail.0)]);
+                (head, tail)
+            }
+            None => {
+                let head = self.inner.push(item);
+                (head, (head.1, head.1))
+            }
+        };
+        if self.recent.len() < WINDOW {
+            self.recent.push(index);
+        } else {
+            self.recent[self.next] = index;
+            self.next = (self.next + 1) % WINDOW;
+        }
+        index
+    }
+}
+
+impl<T: Clone + PartialEq> Push<Vec<T>> for SuffixShareRegion<T> {
+    #[inline]
+    fn push(&mut self, item: Vec<T>) -> <SuffixShareRegion<T> as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl<T: Clone + PartialEq> Push<&Vec<T>> for SuffixShareRegion<T> {
+    #[inline]
+    fn push(&mut self, item: &Vec<T>) -> <SuffixShareRegion<T> as Region>::Index {
+        self.push(item.as_slice())
+    }
+}
+
+impl<T: Clone + PartialEq> Push<ReadSuffixShare<'_, T>> for SuffixShareRegion<T> {
+    #[inline]
+    fn push(&mut self, item: ReadSuffixShare<'_, T>) -> <SuffixShareRegion<T> as Region>::Index {
+        if item.tail.is_empty() {
+            self.push(item.head)
+        } else {
+            self.push(item.into_owned())
+        }
+    }
+}
+
+/// A slice read from a [`SuffixShareRegion`], consisting of a head followed by a tail.
+pub struct ReadSuffixShare<'a, T> {
+    head: &'a [T],
+    tail: &'a [T],
+}
+
+impl<'a, T> ReadSuffixShare<'a, T> {
+    /// Returns the elements before the tail, which the slice does not share.
+    #[inline]
+    #[must_use]
+    pub fn head(&self) -> &'a [T] {
+        self.head
+    }
+
+    /// Returns the tail, which the slice can share with other slices.
+    #[inline]
+    #[must_use]
+    pub fn tail(&self) -> &'a [T] {
+        self.tail
+    }
+
+    /// Returns the number of elements in the slice.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.head.len() + self.tail.len()
+    }
+
+    /// Returns `true` if the slice is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the element at `offset`, or `None` if `offset` is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, offset: usize) -> Option<&'a T> {
+        self.head
+            .get(offset)
+            .or_else(|| self.tail.get(offset - self.head.len()))
+    }
+
+    /// Returns an iterator over the elements of the slice.
+    #[inline]
+    pub fn iter(&self) -> std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>> {
+        self.head.iter().chain(self.tail.iter())
+    }
+}
+
+impl<T> Clone for ReadSuffixShare<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ReadSuffixShare<'_, T> {}
+
+impl<T: PartialEq> PartialEq for ReadSuffixShare<'_, T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for ReadSuffixShare<'_, T> {}
+
+impl<T: Debug> Debug for ReadSuffixShare<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T: Clone> IntoOwned<'a> for ReadSuffixShare<'a, T> {
+    type Owned = Vec<T>;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        let mut owned = Vec::with_capacity(self.len());
+        self.clone_onto(&mut owned);
+        owned
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        other.clear();
+        other.extend_from_slice(self.head);
+        other.extend_from_slice(self.tail);
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self {
+            head: owned,
+            tail: &[],
+        }
+    }
+}
+
+impl<'a, T> IntoIterator for ReadSuffixShare<'a, T> {
+    type Item = &'a T;
+    type IntoIter = std::iter::Chain<std::slice::Iter<'a, T>, std::slice::Iter<'a, T>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.head.iter().chain(self.tail.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    fn heap_size<R: Region>(region: &R) -> usize {
+        let mut size = 0;
+        region.heap_size(|siz, _| size += siz);
+        size
+    }
+
+    #[test]
+    fn test_reversed_domains() {
+        let domains = [
+            "com.example.a",
+            "com.example.b",
+            "com.example",
+            "org.example.www",
+            "com.example.mail",
+            "com",
+            "",
+        ];
+        // Reversing turns the shared prefixes into shared suffixes.
+        let reversed: Vec<Vec<u8>> = domains
+            .iter()
+            .map(|domain| domain.bytes().rev().collect())
+            .collect();
+
+        let mut c = FlatStack::<SuffixShareRegion<u8>>::default();
+        for domain in &reversed {
+            c.copy(domain);
+        }
+        for (item, domain) in c.iter().zip(&reversed) {
+            assert_eq!(domain, &item.into_owned());
+            assert_eq!(domain.len(), item.len());
+        }
+        assert!(c.get(0).tail().is_empty());
+        assert_eq!(c.get(1).tail(), &reversed[0][1..]);
+        assert_eq!(b"b", c.get(1).head());
+        assert!(c.get(2).head().is_empty());
+        assert_eq!(b"liam", c.get(4).head());
+        assert!(c.get(5).head().is_empty());
+
+        // Stores "com.example.a", "b", "org.example.www", and "liam".
+        assert_eq!(13 + 1 + 15 + 4, heap_size(&c.region));
+
+        let copy = c.clone();
+        c.copy(copy.get(4));
+        assert_eq!(c.get(4), c.get(7));
+        assert_eq!(Some(&b'l'), c.get(4).get(0));
+        assert_eq!(Some(&b'c'), c.get(4).get(15));
+        assert_eq!(None, c.get(4).get(16));
+    }
+
+    #[test]
+    fn test_window() {
+        let mut r = <SuffixShareRegion<u32>>::default();
+        let _ = r.push([1, 2, 3].as_slice());
+        for i in 0..WINDOW as u32 {
+            let _ = r.push(vec![10 + i]);
+        }
+        // The first slice left the window, and the region stores its suffix again.
+        let again = r.push([2, 3].as_slice());
+        assert_eq!(2, again.0 .1 - again.0 .0);
+        assert_eq!(vec![2, 3], r.index(again).into_owned());
+
+        r.clear();
+        assert!(r.recent.is_empty());
+        assert_eq!(0, heap_size(&r));
+    }
+}
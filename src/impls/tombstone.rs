@@ -0,0 +1,350 @@
+//! A region that marks entries as deleted and compacts them away.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// A region to hold an append-only log of entries, some of which can be deleted.
+///
+/// Stores entries in region `R`, and assigns each entry an offset in the order of pushing.
+/// [`mark_deleted`](TombstoneRegion::mark_deleted) records a tombstone for an entry in a
+/// bitmap, after which reading the entry returns `None`. The storage of deleted entries
+/// remains in use until [`compact`](TombstoneRegion::compact) rebuilds the region from the
+/// live entries.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::tombstone::TombstoneRegion;
+/// use flatcontainer::{Push, Region, StringRegion};
+/// let mut r = <TombstoneRegion<StringRegion>>::default();
+///
+/// let a = r.push("a");
+/// let b = r.push("b");
+/// r.mark_deleted(a);
+/// assert_eq!(None, r.index(a));
+/// assert_eq!(Some("b"), r.index(b));
+///
+/// let remap = r.compact();
+/// assert_eq!(vec![b], remap);
+/// assert_eq!(Some("b"), r.index(0));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct TombstoneRegion<R: Region> {
+    /// Inner region.
+    inner: R,
+    /// The index in the inner region per offset.
+    indices: Vec<R::Index>,
+    /// Bitmap of deleted offsets.
+    deleted: Vec<u64>,
+    /// The number of deleted entries.
+    deleted_count: usize,
+}
+
+impl<R: Region + Clone> Clone for TombstoneRegion<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            indices: self.indices.clone(),
+            deleted: self.deleted.clone(),
+            deleted_count: self.deleted_count,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.indices.clone_from(&source.indices);
+        self.deleted.clone_from(&source.deleted);
+        self.deleted_count = source.deleted_count;
+    }
+}
+
+impl<R: Region> Default for TombstoneRegion<R> {
+    fn default() -> Self {
+        Self {
+            inner: R::default(),
+            indices: Vec::default(),
+            deleted: Vec::default(),
+            deleted_count: 0,
+        }
+    }
+}
+
+impl<R: Region> TombstoneRegion<R> {
+    /// Returns the number of entries, including deleted entries.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if the region contains no entries.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the number of entries that are not deleted.
+    #[inline]
+    #[must_use]
+    pub fn live(&self) -> usize {
+        self.len() - self.deleted_count
+    }
+
+    /// Returns `true` if the entry at `offset` is not deleted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn is_live(&self, offset: usize) -> bool {
+        assert!(
+            offset < self.len(),
+            "TombstoneRegion: offset {offset} out of bounds for length {}",
+            self.len()
+        );
+        self.deleted[offset / 64] & (1 << (offset % 64)) == 0
+    }
+
+    /// Marks the entry at `offset` as deleted. Returns `true` if the entry was live.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is out of bounds.
+    #[inline]
+    pub fn mark_deleted(&mut self, offset: usize) -> bool {
+        let live = self.is_live(offset);
+        if live {
+            self.deleted[offset / 64] |= 1 << (offset % 64);
+            self.deleted_count += 1;
+        }
+        live
+    }
+
+    /// Rebuilds the region from its live entries, and releases the storage of deleted
+    /// entries.
+    ///
+    /// Returns the previous offset of each live entry, such that the entry at offset `i`
+    /// after compaction was at offset `remap[i]` before.
+    pub fn compact(&mut self) -> Vec<usize>
+    where
+        for<'a> R: Push<<R as Region>::ReadItem<'a>>,
+    {
+        let remap: Vec<_> = (0..self.len())
+            .filter(|offset| self.is_live(*offset))
+            .collect();
+        let mut inner = R::default();
+        let indices = remap
+            .iter()
+            .map(|offset| inner.push(self.inner.index(self.indices[*offset])))
+            .collect();
+        self.inner = inner;
+        self.indices = indices;
+        self.deleted.clear();
+        self.deleted.resize((self.indices.len() + 63) / 64, 0);
+        self.deleted_count = 0;
+        remap
+    }
+}
+
+impl<R: Region> Region for TombstoneRegion<R> {
+    type Owned = Option<R::Owned>;
+    type ReadItem<'a> = Option<R::ReadItem<'a>> where Self: 'a;
+    /// The offset of the entry.
+    type Index = usize;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        let len = regions.clone().map(Self::len).sum::<usize>();
+        Self {
+            inner: R::merge_regions(regions.map(|r| &r.inner)),
+            indices: Vec::with_capacity(len),
+            deleted: Vec::with_capacity((len + 63) / 64),
+            deleted_count: 0,
+        }
+    }
+
+    #[inline]
+    fn index(&self, offset: Self::Index) -> Self::ReadItem<'_> {
+        self.is_live(offset)
+            .then(|| self.inner.index(self.indices[offset]))
+    }
+
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        self.len().checked_sub(1)
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = regions.clone().map(Self::len).sum::<usize>();
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+        self.indices.reserve(len);
+        self.deleted
+            .reserve(((self.len() + len + 63) / 64).saturating_sub(self.deleted.len()));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        let len = regions.clone().map(Self::len).sum::<usize>();
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))?;
+        self.indices.try_reserve(len)?;
+        self.deleted
+            .try_reserve(((self.len() + len + 63) / 64).saturating_sub(self.deleted.len()))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.indices.clear();
+        self.deleted.clear();
+        self.deleted_count = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_index = std::mem::size_of::<R::Index>();
+        callback(
+            self.indices.len() * size_of_index,
+            self.indices.capacity() * size_of_index,
+        );
+        let size_of_word = std::mem::size_of::<u64>();
+        callback(
+            self.deleted.len() * size_of_word,
+            self.deleted.capacity() * size_of_word,
+        );
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item.map(R::reborrow)
+    }
+}
+
+impl<R, T> Push<T> for TombstoneRegion<R>
+where
+    R: Push<T>,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> Self::Index {
+        let offset = self.len();
+        self.indices.push(self.inner.push(item));
+        if offset % 64 == 0 {
+            self.deleted.push(0);
+        }
+        offset
+    }
+}
+
+impl<R, T> ReserveItems<T> for TombstoneRegion<R>
+where
+    R: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.indices.reserve(items.clone().count());
+        self.inner.reserve_items(items);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoOwned, StringRegion};
+
+    use super::*;
+
+    #[test]
+    fn test_compact() {
+        let mut r = <TombstoneRegion<StringRegion>>::default();
+        let offsets: Vec<_> = ["a", "bb", "ccc", "dddd", "eeeee"]
+            .into_iter()
+            .map(|item| r.push(item))
+            .collect();
+        assert!(r.mark_deleted(offsets[1]));
+        assert!(r.mark_deleted(offsets[3]));
+        assert!(!r.mark_deleted(offsets[3]));
+        assert_eq!((5, 3), (r.len(), r.live()));
+        assert_eq!(None, r.index(offsets[1]));
+        assert_eq!(Some("ccc"), r.index(offsets[2]));
+        assert_eq!(None, r.index(offsets[3]).into_owned());
+
+        let mut before = 0;
+        r.heap_size(|size, _| before += size);
+
+        let remap = r.compact();
+        assert_eq!(vec![0, 2, 4], remap);
+        assert_eq!((3, 3), (r.len(), r.live()));
+        assert!((0..r.len()).map(|offset| r.index(offset)).eq([
+            Some("a"),
+            Some("ccc"),
+            Some("eeeee")
+        ]));
+        for (offset, previous) in remap.into_iter().enumerate() {
+            assert_eq!(offsets[previous], previous);
+            assert!(r.is_live(offset));
+        }
+
+        let mut after = 0;
+        r.heap_size(|size, _| after += size);
+        assert!(after < before);
+
+        let f = r.push("f");
+        assert_eq!(3, f);
+        assert_eq!(Some("f"), r.index(f));
+    }
+
+    #[test]
+    fn test_many() {
+        let mut r = <TombstoneRegion<StringRegion>>::default();
+        for i in 0..200 {
+            let _ = r.push(i.to_string());
+        }
+        for offset in (0..200).step_by(3) {
+            r.mark_deleted(offset);
+        }
+        let remap = r.compact();
+        assert_eq!(133, remap.len());
+        for (offset, previous) in remap.into_iter().enumerate() {
+            assert_eq!(Some(previous.to_string().as_str()), r.index(offset));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "TombstoneRegion: offset 1 out of bounds for length 1")]
+    fn test_out_of_bounds() {
+        let mut r = <TombstoneRegion<StringRegion>>::default();
+        let _ = r.push("a");
+        r.mark_deleted(1);
+    }
+}
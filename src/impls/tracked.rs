@@ -0,0 +1,234 @@
+//! A region that keeps a running estimate of its heap size.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::index::IndexContainer;
+use crate::{FlatStack, Push, Region, ReserveItems};
+
+/// A region that maintains an estimate of the bytes it uses, which it updates on every push.
+///
+/// [`Region::heap_size`] visits every allocation of a region, which is costly for nested
+/// regions when called frequently, for example to decide when to apply backpressure. This
+/// region records the bytes the inner region uses after each push, and
+/// [`approx_heap_size`](TrackedRegion::approx_heap_size) returns them in constant time. A push
+/// costs one visit of the inner region's allocations, which for most regions is a handful of
+/// vectors independent of the number of items.
+///
+/// The estimate is approximate: it ignores capacity slack. Clearing the region resets the
+/// estimate, and merging regions starts from zero, as merged regions are empty.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::tracked::TrackedRegion;
+/// use flatcontainer::{Push, StringRegion};
+/// let mut r = <TrackedRegion<StringRegion>>::default();
+///
+/// let _ = r.push("abc");
+/// let _ = r.push("de");
+/// assert_eq!(5, r.approx_heap_size());
+/// ```
+#[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrackedRegion<R> {
+    /// Inner region.
+    inner: R,
+    /// The bytes the inner region used after the most recent push.
+    size: usize,
+}
+
+impl<R> TrackedRegion<R> {
+    /// Returns the estimated number of bytes the region uses, in constant time.
+    #[inline]
+    #[must_use]
+    pub fn approx_heap_size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the inner region.
+    #[inline]
+    #[must_use]
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+}
+
+impl<R: Clone> Clone for TrackedRegion<R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            size: self.size,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+        self.size = source.size;
+    }
+}
+
+impl<R: Region> Region for TrackedRegion<R> {
+    type Owned = R::Owned;
+    type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+    type Index = R::Index;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: R::merge_regions(regions.map(|r| &r.inner)),
+            size: 0,
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        self.inner.index(index)
+    }
+
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        self.inner.last_index()
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.size = 0;
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        R::reborrow(item)
+    }
+}
+
+impl<R, T> Push<T> for TrackedRegion<R>
+where
+    R: Push<T>,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> Self::Index {
+        let index = self.inner.push(item);
+        self.size = 0;
+        self.inner.heap_size(|size, _| self.size += size);
+        index
+    }
+}
+
+impl<R, T> ReserveItems<T> for TrackedRegion<R>
+where
+    R: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        self.inner.reserve_items(items);
+    }
+}
+
+impl<R: Region, S: IndexContainer<R::Index>> FlatStack<TrackedRegion<R>, S> {
+    /// Returns the estimated number of bytes the region of the stack uses, in constant time.
+    ///
+    /// The estimate excludes the indices of the stack. See [`TrackedRegion`].
+    #[inline]
+    #[must_use]
+    pub fn approx_heap_size(&self) -> usize {
+        self.region.approx_heap_size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{OwnedRegion, SliceRegion, StringRegion};
+
+    use super::*;
+
+    fn exact_heap_size<R: Region>(region: &R) -> usize {
+        let mut size = 0;
+        region.heap_size(|siz, _| size += siz);
+        size
+    }
+
+    #[test]
+    fn test_approx_heap_size() {
+        let mut c = FlatStack::<TrackedRegion<StringRegion>>::default();
+        for i in 0..1000 {
+            c.copy(i.to_string().repeat(i % 7));
+            assert_eq!(exact_heap_size(&c.region), c.approx_heap_size());
+        }
+
+        // Nested regions store indexes in addition to the elements of the pushed items.
+        let mut r = <TrackedRegion<SliceRegion<StringRegion>>>::default();
+        for i in 0..100 {
+            let _ = r.push(vec!["abc"; i]);
+            assert_eq!(exact_heap_size(&r), r.approx_heap_size());
+        }
+        let bytes = 100 * 99 / 2 * 3;
+        assert!(r.approx_heap_size() > 2 * bytes);
+
+        let mut r = <TrackedRegion<OwnedRegion<u32>>>::default();
+        for i in 0..100 {
+            let _ = r.push(vec![i; i as usize]);
+        }
+        assert_eq!(exact_heap_size(&r), r.approx_heap_size());
+        assert_eq!(100 * 99 / 2 * 4, r.approx_heap_size());
+    }
+
+    #[test]
+    fn test_clear_merge() {
+        let mut c = FlatStack::<TrackedRegion<StringRegion>>::default();
+        c.copy("abc");
+        let merged = FlatStack::merge_capacity([&c, &c].into_iter());
+        assert_eq!(0, merged.approx_heap_size());
+        assert_eq!(3, c.approx_heap_size());
+
+        c.clear();
+        assert_eq!(0, c.approx_heap_size());
+        c.copy("de");
+        assert_eq!(2, c.approx_heap_size());
+    }
+}
@@ -0,0 +1,419 @@
+//! A region that stores newtypes in the region of the type they wrap.
+
+use std::collections::TryReserveError;
+use std::fmt::{Debug, Formatter};
+use std::marker::PhantomData;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{IntoOwned, Push, Region, ReserveItems};
+
+/// A newtype that wraps a single value, such as a tuple struct with one field or an enum
+/// with a single variant that holds data.
+///
+/// Implementing this trait allows storing the newtype in a [`TransparentRegion`], which
+/// stores the wrapped value in the region of its type.
+pub trait Transparent: Sized {
+    /// The type of the wrapped value.
+    type Inner;
+
+    /// Wraps a value.
+    fn from_inner(inner: Self::Inner) -> Self;
+
+    /// Returns a reference to the wrapped value.
+    fn as_inner(&self) -> &Self::Inner;
+
+    /// Unwraps the value.
+    fn into_inner(self) -> Self::Inner;
+}
+
+/// A region for newtypes that stores the wrapped values in a region `R`, without any
+/// overhead compared to storing the wrapped values directly.
+///
+/// The region reads a [`ReadTransparent`], which exposes the read item of `R`, and converts
+/// to the newtype when turned into its owned representation. There is no blanket
+/// [`RegionPreference`](crate::RegionPreference) for newtypes, but implementing it to point
+/// at a transparent region makes the newtype available to
+/// [`FlatStack::default_impl`](crate::FlatStack::default_impl).
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::transparent::{Transparent, TransparentRegion};
+/// use flatcontainer::{FlatStack, IntoOwned, MirrorRegion, RegionPreference};
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Id {
+///     Id(u64),
+/// }
+///
+/// impl Transparent for Id {
+///     type Inner = u64;
+///
+///     fn from_inner(inner: u64) -> Self {
+///         Id::Id(inner)
+///     }
+///
+///     fn as_inner(&self) -> &u64 {
+///         let Id::Id(inner) = self;
+///         inner
+///     }
+///
+///     fn into_inner(self) -> u64 {
+///         let Id::Id(inner) = self;
+///         inner
+///     }
+/// }
+///
+/// impl RegionPreference for Id {
+///     type Owned = Self;
+///     type Region = TransparentRegion<Id, MirrorRegion<u64>>;
+/// }
+///
+/// let mut c = FlatStack::default_impl::<Id>();
+/// c.copy(Id::Id(7));
+/// assert_eq!(7, c.get(0).get());
+/// assert_eq!(Id::Id(7), c.get(0).into_owned());
+/// ```
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "R: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct TransparentRegion<T, R> {
+    inner: R,
+    _marker: PhantomData<T>,
+}
+
+impl<T, R: Debug> Debug for TransparentRegion<T, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransparentRegion")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T, R: Clone> Clone for TransparentRegion<T, R> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.inner.clone_from(&source.inner);
+    }
+}
+
+impl<T, R: Default> Default for TransparentRegion<T, R> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            inner: R::default(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, R> Region for TransparentRegion<T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region,
+{
+    type Owned = T;
+    type ReadItem<'a> = ReadTransparent<'a, T, R> where Self: 'a;
+    type Index = R::Index;
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: R::merge_regions(regions.map(|r| &r.inner)),
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        ReadTransparent::new(self.inner.index(index))
+    }
+
+    #[inline]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        self.inner.index_raw_bytes(index)
+    }
+
+    #[inline]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.inner.item_storage_size(index)
+    }
+
+    #[inline]
+    fn last_index(&self) -> Option<Self::Index> {
+        self.inner.last_index()
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        ReadTransparent::new(R::reborrow(item.item))
+    }
+}
+
+/// A newtype read from a [`TransparentRegion`], which holds the read item of the wrapped
+/// value.
+pub struct ReadTransparent<'a, T, R: Region + 'a> {
+    item: R::ReadItem<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T, R: Region + 'a> ReadTransparent<'a, T, R> {
+    /// Wraps the read item of a newtype's value.
+    #[inline]
+    #[must_use]
+    pub fn new(item: R::ReadItem<'a>) -> Self {
+        Self {
+            item,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the read item of the wrapped value.
+    #[inline]
+    #[must_use]
+    pub fn get(self) -> R::ReadItem<'a> {
+        self.item
+    }
+}
+
+impl<'a, T, R: Region + 'a> Clone for ReadTransparent<'a, T, R>
+where
+    R::ReadItem<'a>: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.item.clone())
+    }
+}
+
+impl<'a, T, R: Region + 'a> Copy for ReadTransparent<'a, T, R> where R::ReadItem<'a>: Copy {}
+
+impl<'a, T, R: Region + 'a> PartialEq for ReadTransparent<'a, T, R>
+where
+    R::ReadItem<'a>: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.item == other.item
+    }
+}
+
+impl<'a, T, R: Region + 'a> Debug for ReadTransparent<'a, T, R>
+where
+    R::ReadItem<'a>: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.item.fmt(f)
+    }
+}
+
+impl<'a, T, R> IntoOwned<'a> for ReadTransparent<'a, T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region + 'a,
+{
+    type Owned = T;
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        T::from_inner(self.item.into_owned())
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self.into_owned();
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self::new(IntoOwned::borrow_as(owned.as_inner()))
+    }
+}
+
+impl<T, R> Push<T> for TransparentRegion<T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region + Push<<R as Region>::Owned>,
+{
+    #[inline]
+    fn push(&mut self, item: T) -> <TransparentRegion<T, R> as Region>::Index {
+        self.inner.push(item.into_inner())
+    }
+}
+
+impl<'b, T, R> Push<&'b T> for TransparentRegion<T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region + Push<&'b <R as Region>::Owned>,
+    R::Owned: 'b,
+{
+    #[inline]
+    fn push(&mut self, item: &'b T) -> <TransparentRegion<T, R> as Region>::Index {
+        self.inner.push(item.as_inner())
+    }
+}
+
+impl<'a, T, R> Push<ReadTransparent<'a, T, R>> for TransparentRegion<T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region + Push<<R as Region>::ReadItem<'a>>,
+{
+    #[inline]
+    fn push(
+        &mut self,
+        item: ReadTransparent<'a, T, R>,
+    ) -> <TransparentRegion<T, R> as Region>::Index {
+        self.inner.push(item.item)
+    }
+}
+
+impl<'b, T, R> ReserveItems<&'b T> for TransparentRegion<T, R>
+where
+    T: Transparent<Inner = R::Owned>,
+    R: Region + ReserveItems<&'b <R as Region>::Owned>,
+    R::Owned: 'b,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = &'b T> + Clone,
+    {
+        self.inner.reserve_items(items.map(T::as_inner));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, MirrorRegion, OptionRegion, RegionPreference, StringRegion};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum Id {
+        Id(u64),
+    }
+
+    impl Transparent for Id {
+        type Inner = u64;
+
+        fn from_inner(inner: u64) -> Self {
+            Id::Id(inner)
+        }
+
+        fn as_inner(&self) -> &u64 {
+            let Id::Id(inner) = self;
+            inner
+        }
+
+        fn into_inner(self) -> u64 {
+            let Id::Id(inner) = self;
+            inner
+        }
+    }
+
+    impl RegionPreference for Id {
+        type Owned = Self;
+        type Region = TransparentRegion<Id, MirrorRegion<u64>>;
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Name(String);
+
+    impl Transparent for Name {
+        type Inner = String;
+
+        fn from_inner(inner: String) -> Self {
+            Self(inner)
+        }
+
+        fn as_inner(&self) -> &String {
+            &self.0
+        }
+
+        fn into_inner(self) -> String {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_id() {
+        let ids: Vec<_> = (0..100).map(Id::Id).collect();
+        let mut c = FlatStack::default_impl::<Id>();
+        let mut plain = FlatStack::default_impl::<u64>();
+        for id in &ids {
+            c.copy(id);
+            plain.copy(id.as_inner());
+        }
+        assert!(c.iter().map(IntoOwned::into_owned).eq(ids.iter().copied()));
+        assert_eq!(Id::Id(42), c.get(42).into_owned());
+
+        // The newtype stores exactly like the value it wraps.
+        let (mut size, mut plain_size) = (0, 0);
+        c.heap_size(|siz, _| size += siz);
+        plain.heap_size(|siz, _| plain_size += siz);
+        assert_eq!(plain_size, size);
+        assert_eq!(ids.len() * std::mem::size_of::<u64>(), size);
+    }
+
+    #[test]
+    fn test_nested() {
+        let mut r = <OptionRegion<TransparentRegion<Name, StringRegion>>>::default();
+        let some = r.push(Some(Name("abc".to_string())));
+        let none = r.push(Option::<Name>::None);
+        assert_eq!(Some("abc"), r.index(some).map(ReadTransparent::get));
+        assert_eq!(None, r.index(none));
+
+        let mut c = FlatStack::<TransparentRegion<Name, StringRegion>>::default();
+        c.copy(&Name("x".to_string()));
+        let copy = c.clone();
+        c.copy(copy.get(0));
+        let mut owned = Name(String::new());
+        c.get(1).clone_onto(&mut owned);
+        assert_eq!(Name("x".to_string()), owned);
+        assert_eq!("\"x\"", format!("{:?}", c.get(1)));
+    }
+}
@@ -1,10 +1,13 @@
 //! Regions that stores tuples.
 
+use std::collections::TryReserveError;
+
 use paste::paste;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
-use crate::{IntoOwned, Push, Region, RegionPreference, ReserveItems};
+use crate::impls::choose::CanMeasure;
+use crate::{IntoOwned, Push, Region, RegionLen, RegionPreference, ReserveItems};
 
 /// The macro creates the region implementation for tuples
 macro_rules! tuple_flatcontainer {
@@ -66,6 +69,12 @@ macro_rules! tuple_flatcontainer {
                     )
                 }
 
+                #[inline]
+                fn item_storage_size(&self, index: Self::Index) -> usize {
+                    let ($($name,)*) = index;
+                    0 $(+ self.[<container $name>].item_storage_size($name))*
+                }
+
                 #[inline(always)]
                 fn reserve_regions<'a, It>(&mut self, regions: It)
                 where
@@ -75,6 +84,16 @@ macro_rules! tuple_flatcontainer {
                     $(self.[<container $name>].reserve_regions(regions.clone().map(|r| &r.[<container $name>]));)*
                 }
 
+                #[inline(always)]
+                fn try_reserve_regions<'a, It>(&mut self, regions: It) -> Result<(), TryReserveError>
+                where
+                    Self: 'a,
+                    It: Iterator<Item = &'a Self> + Clone,
+                {
+                    $(self.[<container $name>].try_reserve_regions(regions.clone().map(|r| &r.[<container $name>]))?;)*
+                    Ok(())
+                }
+
                 #[inline(always)]
                 fn clear(&mut self) {
                     $(self.[<container $name>].clear();)*
@@ -94,6 +113,18 @@ macro_rules! tuple_flatcontainer {
                 }
             }
 
+            /// Returns the sum of the number of elements of the regions of all fields.
+            #[allow(non_snake_case)]
+            impl<$($name: RegionLen),*> RegionLen for [<Tuple $($name)* Region>]<$($name),*>
+            where
+               $(<$name as Region>::Index: crate::Index),*
+            {
+                #[inline]
+                fn len(&self) -> usize {
+                    0 $(+ self.[<container $name>].len())*
+                }
+            }
+
             #[allow(non_camel_case_types)]
             #[allow(non_snake_case)]
             impl<$($name, [<$name _C>]: Region ),*> Push<($($name,)*)> for [<Tuple $($name)* Region>]<$([<$name _C>]),*>
@@ -108,6 +139,19 @@ macro_rules! tuple_flatcontainer {
                 }
             }
 
+            #[allow(non_camel_case_types)]
+            #[allow(non_snake_case)]
+            impl<$($name, [<$name _C>]),*> CanMeasure<($($name,)*)> for [<Tuple $($name)* Region>]<$([<$name _C>]),*>
+            where
+                $([<$name _C>]: CanMeasure<$name>),*
+            {
+                #[inline]
+                fn measure(&self, item: &($($name,)*)) -> usize {
+                    let ($($name,)*) = item;
+                    0 $(+ self.[<container $name>].measure($name))*
+                }
+            }
+
             #[allow(non_camel_case_types)]
             #[allow(non_snake_case)]
             impl<'a, $($name, [<$name _C>]),*> Push<&'a ($($name,)*)> for [<Tuple $($name)* Region>]<$([<$name _C>]),*>
@@ -303,7 +347,7 @@ mod tests {
     #[test]
     fn test_reserve_items() {
         let mut c = FlatStack::default_impl::<(usize, String, Vec<String>)>();
-        c.copy((1, format!("Hello"), &["abc"]));
+        c.copy((1, "Hello".to_string(), &["abc"]));
 
         let mut c2 = FlatStack::default_impl::<(usize, String, Vec<String>)>();
         c2.reserve_items(c.iter());
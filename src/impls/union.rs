@@ -0,0 +1,317 @@
+//! A region that stores the variants of an enum in separate columns.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{Push, Region, ReserveItems};
+
+/// A region to hold a column of an enum with `N` variants, storing the payloads of each
+/// variant in a region of its own.
+///
+/// Generalizes [`ResultRegion`](crate::ResultRegion) from two to `N` variants, all of which
+/// store their payloads in a region of type `R`. Items are pushed as a tuple of the variant
+/// and the payload. Each variant keeps the indices of its payloads in insertion order, so
+/// [`iter_variant`](UnionColumnRegion::iter_variant) scans the payloads of one variant
+/// without visiting the others. An index consists of the variant and the position of the
+/// payload within the variant.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::union::UnionColumnRegion;
+/// use flatcontainer::{Push, Region, StringRegion};
+/// let mut r = <UnionColumnRegion<StringRegion, 3>>::default();
+///
+/// let index = r.push((2, "c"));
+/// let _ = r.push((0, "a"));
+/// let _ = r.push((2, "cc"));
+/// assert_eq!((2, "c"), r.index(index));
+/// assert!(r.iter_variant(2).eq(["c", "cc"]));
+/// ```
+#[derive(Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "
+            R: Serialize + for<'a> Deserialize<'a>,
+            R::Index: Serialize + for<'a> Deserialize<'a>,
+            ")
+)]
+pub struct UnionColumnRegion<R: Region, const N: usize> {
+    /// The region per variant.
+    variants: Vec<R>,
+    /// The indices of the payloads per variant, in insertion order.
+    indices: Vec<Vec<R::Index>>,
+}
+
+impl<R: Region + Clone, const N: usize> Clone for UnionColumnRegion<R, N> {
+    fn clone(&self) -> Self {
+        Self {
+            variants: self.variants.clone(),
+            indices: self.indices.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.variants.clone_from(&source.variants);
+        self.indices.clone_from(&source.indices);
+    }
+}
+
+impl<R: Region, const N: usize> Default for UnionColumnRegion<R, N> {
+    fn default() -> Self {
+        Self {
+            variants: std::iter::repeat_with(R::default).take(N).collect(),
+            indices: vec![Vec::new(); N],
+        }
+    }
+}
+
+impl<R: Region, const N: usize> UnionColumnRegion<R, N> {
+    /// Returns the number of payloads of `variant`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variant` is out of bounds.
+    #[inline]
+    #[must_use]
+    pub fn len_variant(&self, variant: usize) -> usize {
+        self.indices[Self::check(variant)].len()
+    }
+
+    /// Iterates the payloads of `variant` in insertion order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `variant` is out of bounds.
+    pub fn iter_variant(&self, variant: usize) -> impl Iterator<Item = R::ReadItem<'_>> + Clone {
+        let region = &self.variants[Self::check(variant)];
+        self.indices[variant]
+            .as_slice()
+            .iter()
+            .map(move |index| region.index(*index))
+    }
+
+    /// Returns `variant` if it is in bounds, and panics otherwise.
+    #[inline]
+    fn check(variant: usize) -> usize {
+        assert!(
+            variant < N,
+            "UnionColumnRegion: variant {variant} out of bounds for {N} variants"
+        );
+        variant
+    }
+}
+
+impl<R: Region, const N: usize> Region for UnionColumnRegion<R, N> {
+    type Owned = (usize, R::Owned);
+    type ReadItem<'a> = (usize, R::ReadItem<'a>) where Self: 'a;
+    /// The variant, and the position of the payload within the variant.
+    type Index = (usize, usize);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            variants: (0..N)
+                .map(|variant| R::merge_regions(regions.clone().map(move |r| &r.variants[variant])))
+                .collect(),
+            indices: (0..N)
+                .map(|variant| {
+                    Vec::with_capacity(regions.clone().map(|r| r.indices[variant].len()).sum())
+                })
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn index(&self, (variant, position): Self::Index) -> Self::ReadItem<'_> {
+        (
+            variant,
+            self.variants[variant].index(self.indices[variant][position]),
+        )
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        for (variant, (region, indices)) in self
+            .variants
+            .iter_mut()
+            .zip(self.indices.iter_mut())
+            .enumerate()
+        {
+            region.reserve_regions(regions.clone().map(|r| &r.variants[variant]));
+            indices.reserve(regions.clone().map(|r| r.indices[variant].len()).sum());
+        }
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        for (variant, (region, indices)) in self
+            .variants
+            .iter_mut()
+            .zip(self.indices.iter_mut())
+            .enumerate()
+        {
+            region.try_reserve_regions(regions.clone().map(|r| &r.variants[variant]))?;
+            indices.try_reserve(regions.clone().map(|r| r.indices[variant].len()).sum())?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        for (region, indices) in self.variants.iter_mut().zip(self.indices.iter_mut()) {
+            region.clear();
+            indices.clear();
+        }
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        let size_of_region = std::mem::size_of::<R>();
+        callback(
+            self.variants.len() * size_of_region,
+            self.variants.capacity() * size_of_region,
+        );
+        let size_of_indices = std::mem::size_of::<Vec<R::Index>>();
+        callback(
+            self.indices.len() * size_of_indices,
+            self.indices.capacity() * size_of_indices,
+        );
+        let size_of_index = std::mem::size_of::<R::Index>();
+        for (region, indices) in self.variants.iter().zip(self.indices.iter()) {
+            callback(
+                indices.len() * size_of_index,
+                indices.capacity() * size_of_index,
+            );
+            region.heap_size(&mut callback);
+        }
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        (item.0, R::reborrow(item.1))
+    }
+}
+
+impl<R, T, const N: usize> Push<(usize, T)> for UnionColumnRegion<R, N>
+where
+    R: Push<T>,
+{
+    /// Pushes the payload of a variant.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variant is out of bounds.
+    #[inline]
+    fn push(&mut self, (variant, item): (usize, T)) -> Self::Index {
+        let index = self.variants[Self::check(variant)].push(item);
+        let indices = &mut self.indices[variant];
+        indices.push(index);
+        (variant, indices.len() - 1)
+    }
+}
+
+impl<R, T, const N: usize> ReserveItems<(usize, T)> for UnionColumnRegion<R, N>
+where
+    R: ReserveItems<T>,
+{
+    #[inline]
+    fn reserve_items<I>(&mut self, items: I)
+    where
+        I: Iterator<Item = (usize, T)> + Clone,
+    {
+        for (variant, (region, indices)) in self
+            .variants
+            .iter_mut()
+            .zip(self.indices.iter_mut())
+            .enumerate()
+        {
+            let payloads = items
+                .clone()
+                .filter(move |(v, _)| *v == variant)
+                .map(|(_, item)| item);
+            indices.reserve(payloads.clone().count());
+            region.reserve_items(payloads);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FlatStack, IntoOwned, StringRegion};
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Log {
+        Info(String),
+        Warn(String),
+        Error(String),
+    }
+
+    impl Log {
+        fn split(&self) -> (usize, &str) {
+            match self {
+                Log::Info(message) => (0, message),
+                Log::Warn(message) => (1, message),
+                Log::Error(message) => (2, message),
+            }
+        }
+    }
+
+    #[test]
+    fn test_iter_variant() {
+        let logs = [
+            Log::Info("start".to_string()),
+            Log::Warn("slow".to_string()),
+            Log::Info("step".to_string()),
+            Log::Error("fail".to_string()),
+            Log::Warn("slower".to_string()),
+            Log::Info("stop".to_string()),
+        ];
+        let mut c = FlatStack::<UnionColumnRegion<StringRegion, 3>>::default();
+        c.reserve_items(logs.iter().map(Log::split));
+        for log in &logs {
+            c.copy(log.split());
+        }
+
+        assert!(c.iter().eq(logs.iter().map(Log::split)));
+        assert!(c.region.iter_variant(0).eq(["start", "step", "stop"]));
+        assert!(c.region.iter_variant(1).eq(["slow", "slower"]));
+        assert!(c.region.iter_variant(2).eq(["fail"]));
+        assert_eq!(3, c.region.len_variant(0));
+        assert_eq!((1, "slow".to_string()), c.get(1).into_owned());
+
+        // The payloads of a variant are adjacent in its region.
+        let mut size = 0;
+        c.region.variants[0].heap_size(|siz, _| size += siz);
+        assert_eq!("startstepstop".len(), size);
+
+        let merged = UnionColumnRegion::merge_regions(std::iter::once(&c.region));
+        assert_eq!(3, merged.indices[0].capacity());
+    }
+
+    #[test]
+    #[should_panic(expected = "UnionColumnRegion: variant 3 out of bounds for 3 variants")]
+    fn test_variant_out_of_bounds() {
+        let mut r = <UnionColumnRegion<StringRegion, 3>>::default();
+        let _ = r.push((3, "d"));
+    }
+}
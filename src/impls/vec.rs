@@ -1,5 +1,7 @@
 //! Definitions to use `Vec<T>` as a region.
 
+use std::collections::TryReserveError;
+
 use crate::{Push, Region, ReserveItems};
 
 impl<T: Clone> Region for Vec<T> {
@@ -26,6 +28,14 @@ impl<T: Clone> Region for Vec<T> {
         self.reserve(regions.map(Vec::len).sum());
     }
 
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.try_reserve(regions.map(Vec::len).sum())
+    }
+
     fn clear(&mut self) {
         self.clear();
     }
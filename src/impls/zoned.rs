@@ -0,0 +1,247 @@
+//! A region that stores timestamps with their UTC offset.
+
+use std::collections::TryReserveError;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impls::tuple::TupleABRegion;
+use crate::{IntoOwned, MirrorRegion, Push, Region, ReserveItems};
+
+/// The number of nanoseconds per second.
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+/// A timestamp with the UTC offset of the time zone it was observed in.
+///
+/// The instant is the number of nanoseconds since the Unix epoch in UTC, and the offset is the
+/// number of seconds the local time is ahead of UTC, which is negative west of Greenwich.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::zoned::Zoned;
+/// // 1970-01-01T01:00:00+02:00
+/// let zoned = Zoned::new(-3_600_000_000_000, 7_200);
+/// assert_eq!(Some(3_600_000_000_000), zoned.local_nanos());
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Zoned {
+    /// Nanoseconds since the Unix epoch, in UTC.
+    utc_nanos: i64,
+    /// Seconds the local time is ahead of UTC.
+    offset_secs: i32,
+}
+
+impl Zoned {
+    /// Constructs a timestamp from an instant in nanoseconds since the Unix epoch, in UTC, and
+    /// the UTC offset in seconds.
+    #[inline]
+    #[must_use]
+    pub const fn new(utc_nanos: i64, offset_secs: i32) -> Self {
+        Self {
+            utc_nanos,
+            offset_secs,
+        }
+    }
+
+    /// Returns the instant as nanoseconds since the Unix epoch, in UTC.
+    #[inline]
+    #[must_use]
+    pub const fn utc_nanos(&self) -> i64 {
+        self.utc_nanos
+    }
+
+    /// Returns the number of seconds the local time is ahead of UTC.
+    #[inline]
+    #[must_use]
+    pub const fn offset_secs(&self) -> i32 {
+        self.offset_secs
+    }
+
+    /// Returns the local time as nanoseconds since the Unix epoch, or `None` if it does not
+    /// fit in an `i64`.
+    #[inline]
+    #[must_use]
+    pub fn local_nanos(&self) -> Option<i64> {
+        i64::from(self.offset_secs)
+            .checked_mul(NANOS_PER_SEC)?
+            .checked_add(self.utc_nanos)
+    }
+}
+
+impl<'a> IntoOwned<'a> for Zoned {
+    type Owned = (i64, i32);
+
+    #[inline]
+    fn into_owned(self) -> Self::Owned {
+        (self.utc_nanos, self.offset_secs)
+    }
+
+    #[inline]
+    fn clone_onto(self, other: &mut Self::Owned) {
+        *other = self.into_owned();
+    }
+
+    #[inline]
+    fn borrow_as(owned: &'a Self::Owned) -> Self {
+        Self::new(owned.0, owned.1)
+    }
+}
+
+/// A region for timestamps with the UTC offset of their time zone.
+///
+/// Stores the instant in UTC nanoseconds and the offset in seconds in a tuple of mirror
+/// regions, and reads them as a [`Zoned`]. The owned type is a tuple `(utc_nanos, offset_secs)`,
+/// which avoids depending on a date-time library.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::impls::zoned::ZonedRegion;
+/// use flatcontainer::{IntoOwned, Push, Region};
+/// let mut r = ZonedRegion::default();
+///
+/// let index = r.push((1_700_000_000_000_000_000_i64, -18_000_i32));
+/// assert_eq!(-18_000, r.index(index).offset_secs());
+/// assert_eq!((1_700_000_000_000_000_000, -18_000), r.index(index).into_owned());
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ZonedRegion {
+    /// Storage for the instants and offsets.
+    inner: TupleABRegion<MirrorRegion<i64>, MirrorRegion<i32>>,
+}
+
+impl Region for ZonedRegion {
+    type Owned = (i64, i32);
+    type ReadItem<'a> = Zoned;
+    type Index = (i64, i32);
+
+    #[inline]
+    fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+    where
+        Self: 'a,
+    {
+        Self {
+            inner: TupleABRegion::merge_regions(regions.map(|r| &r.inner)),
+        }
+    }
+
+    #[inline]
+    fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+        let (utc_nanos, offset_secs) = self.inner.index(index);
+        Zoned::new(utc_nanos, offset_secs)
+    }
+
+    #[inline]
+    fn reserve_regions<'a, I>(&mut self, regions: I)
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.reserve_regions(regions.map(|r| &r.inner));
+    }
+
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.inner.try_reserve_regions(regions.map(|r| &r.inner))
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[inline]
+    fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+        self.inner.heap_size(callback);
+    }
+
+    #[inline]
+    fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+    where
+        Self: 'a,
+    {
+        item
+    }
+}
+
+impl Push<(i64, i32)> for ZonedRegion {
+    #[inline]
+    fn push(&mut self, item: (i64, i32)) -> (i64, i32) {
+        self.inner.push(item)
+    }
+}
+
+impl Push<&(i64, i32)> for ZonedRegion {
+    #[inline]
+    fn push(&mut self, item: &(i64, i32)) -> (i64, i32) {
+        self.push(*item)
+    }
+}
+
+impl Push<Zoned> for ZonedRegion {
+    #[inline]
+    fn push(&mut self, item: Zoned) -> (i64, i32) {
+        self.push(item.into_owned())
+    }
+}
+
+impl<T> ReserveItems<T> for ZonedRegion {
+    #[inline(always)]
+    fn reserve_items<I>(&mut self, _items: I)
+    where
+        I: Iterator<Item = T> + Clone,
+    {
+        // No storage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::FlatStack;
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let items = [
+            (0, 0),
+            (1_700_000_000_123_456_789, 3_600),
+            (1_700_000_000_123_456_789, -18_000),
+            (-86_400 * NANOS_PER_SEC, -34_200),
+            (i64::MAX, 50_400),
+            (i64::MIN, -43_200),
+        ];
+
+        let mut c = FlatStack::<ZonedRegion>::default();
+        for item in &items {
+            c.copy(item);
+        }
+        c.copy(Zoned::new(42, 19_800));
+
+        for (item, zoned) in items.iter().zip(c.iter()) {
+            assert_eq!(item.0, zoned.utc_nanos());
+            assert_eq!(item.1, zoned.offset_secs());
+            assert_eq!(*item, zoned.into_owned());
+            assert_eq!(zoned, IntoOwned::borrow_as(item));
+        }
+        assert_eq!((42, 19_800), c.get(items.len()).into_owned());
+    }
+
+    #[test]
+    fn test_local() {
+        let mut r = ZonedRegion::default();
+        let west = r.push((NANOS_PER_SEC, -3_600));
+        let east = r.push((NANOS_PER_SEC, 5_400));
+        assert_eq!(Some(-3_599 * NANOS_PER_SEC), r.index(west).local_nanos());
+        assert_eq!(Some(5_401 * NANOS_PER_SEC), r.index(east).local_nanos());
+
+        let overflow = r.push((i64::MAX, 1));
+        assert_eq!(None, r.index(overflow).local_nanos());
+    }
+}
@@ -2,7 +2,12 @@
 #![deny(missing_docs)]
 
 use std::borrow::Borrow;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, HashSet, TryReserveError};
 use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -12,6 +17,8 @@ pub mod impls;
 use crate::impls::index::IndexContainer;
 pub use impls::columns::ColumnsRegion;
 pub use impls::mirror::MirrorRegion;
+pub use impls::nullable_columns::NullableColumnsRegion;
+pub use impls::nullable_slice::NullableSliceRegion;
 pub use impls::option::OptionRegion;
 pub use impls::result::ResultRegion;
 pub use impls::slice::SliceRegion;
@@ -64,16 +71,83 @@ pub trait Region: Default {
     #[must_use]
     fn index(&self, index: Self::Index) -> Self::ReadItem<'_>;
 
+    /// Returns the bytes that represent the item at `index`, if the region stores it as a
+    /// contiguous byte slice.
+    ///
+    /// Regions without a flat byte representation return `None`, which is the default.
+    #[inline]
+    #[must_use]
+    fn index_raw_bytes(&self, index: Self::Index) -> Option<&[u8]> {
+        let _ = index;
+        None
+    }
+
+    /// Returns the number of bytes the region uses to store the item at `index`.
+    ///
+    /// Regions that encode their items report the encoded size, which allows comparing how
+    /// well individual items compress, and composite regions report the sizes of their parts.
+    /// The default reports the length of [`Self::index_raw_bytes`], or, for regions without a
+    /// flat byte representation, the size of the read item, which is only an estimate.
+    #[inline]
+    #[must_use]
+    fn item_storage_size(&self, index: Self::Index) -> usize {
+        self.index_raw_bytes(index)
+            .map_or_else(std::mem::size_of::<Self::ReadItem<'_>>, <[u8]>::len)
+    }
+
+    /// Returns the index of the most recently pushed item, or `None` if the region is empty
+    /// or cannot recover the index.
+    ///
+    /// Regions that assign indices in push order, or remember the last index they returned,
+    /// report it until they are cleared. The default returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::impls::deduplicate::CollapseSequence;
+    /// use flatcontainer::{Push, Region, StringRegion};
+    /// let mut r = <CollapseSequence<StringRegion>>::default();
+    /// assert_eq!(None, r.last_index());
+    ///
+    /// let index = r.push("abc");
+    /// assert_eq!(Some(index), r.last_index());
+    /// ```
+    #[inline]
+    #[must_use]
+    fn last_index(&self) -> Option<Self::Index> {
+        None
+    }
+
     /// Ensure that the region can absorb the items of `regions` without reallocation
     fn reserve_regions<'a, I>(&mut self, regions: I)
     where
         Self: 'a,
         I: Iterator<Item = &'a Self> + Clone;
 
+    /// Ensure that the region can absorb the items of `regions` without reallocation, or
+    /// report that an allocation failed.
+    ///
+    /// The default implementation calls [`Region::reserve_regions`], which aborts on
+    /// allocation failure. Regions that own their storage override it to reserve fallibly.
+    #[inline]
+    fn try_reserve_regions<'a, I>(&mut self, regions: I) -> Result<(), TryReserveError>
+    where
+        Self: 'a,
+        I: Iterator<Item = &'a Self> + Clone,
+    {
+        self.reserve_regions(regions);
+        Ok(())
+    }
+
     /// Remove all elements from this region, but retain allocations if possible.
     fn clear(&mut self);
 
     /// Heap size, size - capacity
+    ///
+    /// Reports the heap allocations owned by the region by calling `callback` with the
+    /// number of bytes in use and the number of bytes allocated. The inline size of `Self`
+    /// is not included, but values stored inline in an owned allocation are, for example the
+    /// structs in a `Vec` of inner regions. Each allocation must be reported exactly once.
     fn heap_size<F: FnMut(usize, usize)>(&self, callback: F);
 
     /// Converts a read item into one with a narrower lifetime.
@@ -81,6 +155,39 @@ pub trait Region: Default {
     fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
     where
         Self: 'a;
+
+    /// Copies the items at `indices` into `into`, and returns a map from each of `indices` to
+    /// the index of its copy.
+    ///
+    /// A region does not know the indices of its items, so the caller passes the indices it
+    /// holds, for example the indices of a [`FlatStack`]. An index that appears more than once
+    /// is copied once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{Push, Region, StringRegion};
+    /// let mut r = <StringRegion>::default();
+    /// let indices = [r.push("abc"), r.push("def")];
+    ///
+    /// let mut into = StringRegion::merge_regions(std::iter::once(&r));
+    /// let map = r.rebuild_with_map(indices, &mut into);
+    /// assert_eq!("def", into.index(map.translate(indices[1])));
+    /// ```
+    fn rebuild_with_map<I>(&self, indices: I, into: &mut Self) -> IndexMap<Self::Index>
+    where
+        I: IntoIterator<Item = Self::Index>,
+        Self::Index: Hash + Eq,
+        for<'a> Self: Push<<Self as Region>::ReadItem<'a>>,
+    {
+        let mut map = IndexMap::default();
+        for index in indices {
+            map.map
+                .entry(index)
+                .or_insert_with(|| into.push(self.index(index)));
+        }
+        map
+    }
 }
 
 /// A trait to let types express a default container type and an owned type, which can
@@ -104,6 +211,43 @@ impl<T: RegionPreference + ?Sized> RegionPreference for &T {
     type Region = T::Region;
 }
 
+/// Report the number of elements a region stores, independently of a [`FlatStack`].
+///
+/// An element is a value the region stores itself, which is not necessarily a pushed item:
+/// * Regions that keep an index entry per item, such as [`ColumnsRegion`], report the number
+///   of pushed items.
+/// * Regions that store slices flat, such as [`OwnedRegion`] and [`SliceRegion`], report the
+///   number of elements of all slices, as they do not record where slices start. A
+///   [`StringRegion`] reports the number of bytes.
+/// * Tuple regions report the sum over their fields. A [`MirrorRegion`] reports zero, because
+///   it stores items in their indices.
+///
+/// Use [`FlatStack::len`] to count pushed items.
+///
+/// # Examples
+///
+/// ```
+/// use flatcontainer::{OwnedRegion, Push, RegionLen};
+/// let mut r = <OwnedRegion<u8>>::default();
+/// assert!(r.is_empty());
+///
+/// let _ = r.push(b"abc");
+/// let _ = r.push(b"de");
+/// assert_eq!(5, r.len());
+/// ```
+pub trait RegionLen: Region {
+    /// Returns the number of elements the region stores.
+    #[must_use]
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the region stores no elements.
+    #[inline]
+    #[must_use]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// Push an item `T` into a region.
 pub trait Push<T>: Region {
     /// Push `item` into self, returning an index that allows to look up the
@@ -112,6 +256,34 @@ pub trait Push<T>: Region {
     fn push(&mut self, item: T) -> Self::Index;
 }
 
+/// Push an item `T` into a region, or fail if the region cannot represent the item.
+///
+/// Unlike [`Push`], which panics when a region reaches a limit, implementations report the
+/// condition as [`TryPush::Error`] and leave previously returned indexes valid.
+pub trait TryPush<T>: Region {
+    /// The error returned when the region cannot accept the item.
+    type Error;
+
+    /// Push `item` into self, returning an index that allows to look up the corresponding read
+    /// item, or an error if the region cannot accept the item.
+    fn try_push(&mut self, item: T) -> Result<Self::Index, Self::Error>;
+}
+
+/// An index or offset exceeds the range of the type that stores it.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct IndexOverflow {
+    /// The largest value that can be stored.
+    pub max: usize,
+}
+
+impl std::fmt::Display for IndexOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "index exceeds the maximum of {}", self.max)
+    }
+}
+
+impl std::error::Error for IndexOverflow {}
+
 /// Reserve space in the receiving region.
 ///
 /// Closely related to [`Push`], but separate because target type is likely different.
@@ -216,85 +388,829 @@ impl<R: Region, S: IndexContainer<<R as Region>::Index>> FlatStack<R, S> {
         }
     }
 
-    /// Appends the element to the back of the stack.
-    #[inline]
-    pub fn copy<T>(&mut self, item: T)
-    where
-        R: Push<T>,
-    {
-        let index = self.region.push(item);
-        self.indices.push(index);
+    /// Returns a flat stack that can absorb the contents of `iter` without reallocation, or
+    /// an error if an allocation fails.
+    ///
+    /// Like [`Self::merge_capacity`], constructs the region with [`Region::merge_regions`],
+    /// which carries over state such as the bounds of a
+    /// [`BoundedRegion`](impls::bounded::BoundedRegion), and then reserves the remaining space
+    /// fallibly. Regions that allocate in [`Region::merge_regions`] do so infallibly, while the
+    /// index container reserves its space fallibly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index container or the region fails to allocate.
+    pub fn try_merge_capacity<'a, I: Iterator<Item = &'a Self> + Clone + 'a>(
+        stacks: I,
+    ) -> Result<Self, TryReserveError>
+    where
+        Self: 'a,
+    {
+        let mut stack = Self {
+            indices: S::default(),
+            region: R::merge_regions(stacks.clone().map(|s| &s.region)),
+        };
+        stack
+            .indices
+            .try_reserve_regions(stacks.clone().map(|s| &s.indices))?;
+        stack.try_reserve_regions(stacks.map(|s| &s.region))?;
+        Ok(stack)
+    }
+
+    /// Appends the element to the back of the stack.
+    #[inline]
+    pub fn copy<T>(&mut self, item: T)
+    where
+        R: Push<T>,
+    {
+        let index = self.region.push(item);
+        self.indices.push(index);
+    }
+
+    /// Returns the element at the `index` position.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, index: usize) -> R::ReadItem<'_> {
+        self.region.index(self.indices.index(index))
+    }
+
+    /// Returns a reference to the element at the `index` position, which remains valid as the
+    /// stack grows, and can be resolved with [`FlatStack::get_ref`].
+    #[inline]
+    #[must_use]
+    pub fn ref_at(&self, index: usize) -> Ref<R> {
+        Ref::new(self.indices.index(index))
+    }
+
+    /// Returns the element a reference points at.
+    ///
+    /// The reference must originate from this stack, or from a stack whose region contains
+    /// the same data, such as a clone. Otherwise, the result is unspecified, and the call might
+    /// panic.
+    #[inline]
+    #[must_use]
+    pub fn get_ref(&self, r: Ref<R>) -> R::ReadItem<'_> {
+        self.region.index(r.index)
+    }
+
+    /// Returns the number of indices in the stack.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Returns `true` if the stack contains no elements.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Removes the last element and returns its owned representation, or `None` if the stack
+    /// is empty.
+    ///
+    /// Only removes the element's index. The region retains the storage of the element, which
+    /// it cannot reclaim in general, until the stack is cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let mut c: FlatStack<StringRegion> = ["a", "b"].into_iter().collect();
+    ///
+    /// assert_eq!(Some("b".to_string()), c.pop());
+    /// assert_eq!(1, c.len());
+    /// ```
+    #[inline]
+    pub fn pop(&mut self) -> Option<R::Owned> {
+        let index = self.indices.pop()?;
+        Some(self.region.index(index).into_owned())
+    }
+
+    /// Replaces the contents of `out` with the owned representations of the stack's elements.
+    ///
+    /// Clones elements onto the values `out` already holds, which reuses their allocations
+    /// when collecting repeatedly into the same vector, and appends the remaining elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "b"].into_iter().collect();
+    ///
+    /// let mut out = vec!["x".to_string(); 3];
+    /// c.collect_owned_into(&mut out);
+    /// assert_eq!(vec!["a".to_string(), "b".to_string()], out);
+    /// ```
+    pub fn collect_owned_into(&self, out: &mut Vec<R::Owned>) {
+        out.truncate(self.len());
+        out.reserve(self.len() - out.len());
+        let mut items = self.iter();
+        for (slot, item) in out.iter_mut().zip(items.by_ref()) {
+            item.clone_onto(slot);
+        }
+        out.extend(items.map(IntoOwned::into_owned));
+    }
+
+    /// Returns the positions of `k` elements sampled uniformly at random without replacement,
+    /// or of all elements if the stack holds at most `k` elements.
+    ///
+    /// Performs reservoir sampling, which reads no elements and calls `rng` once for every
+    /// element after the first `k`. `rng(n)` must return a uniformly distributed value in
+    /// `0..n`. The positions are in no particular order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "b", "c", "d"].into_iter().collect();
+    ///
+    /// // Replaces the first sampled position with every later element.
+    /// let sample = c.sample(2, |_| 0);
+    /// assert_eq!(vec![3, 1], sample);
+    /// ```
+    #[must_use]
+    pub fn sample(&self, k: usize, mut rng: impl FnMut(usize) -> usize) -> Vec<usize> {
+        let mut reservoir: Vec<_> = (0..k.min(self.len())).collect();
+        for position in k..self.len() {
+            let slot = rng(position + 1);
+            if slot < k {
+                reservoir[slot] = position;
+            }
+        }
+        reservoir
+    }
+
+    /// Returns the owned representations of `k` elements sampled uniformly at random without
+    /// replacement, see [`sample`](FlatStack::sample).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "b", "c", "d"].into_iter().collect();
+    ///
+    /// assert_eq!(vec!["d", "b"], c.sample_owned(2, |_| 0));
+    /// ```
+    #[must_use]
+    pub fn sample_owned(&self, k: usize, rng: impl FnMut(usize) -> usize) -> Vec<R::Owned> {
+        self.sample(k, rng)
+            .into_iter()
+            .map(|position| self.get(position).into_owned())
+            .collect()
+    }
+
+    /// Shortens the stack to `len` elements, for example to roll back to an earlier length.
+    /// Has no effect if `len` is greater or equal to the stack's length.
+    ///
+    /// Only shrinks the index container. Like [`pop`](FlatStack::pop), the region retains the
+    /// storage of the removed elements until the stack is cleared.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let mut c: FlatStack<StringRegion> = ["a", "b", "c"].into_iter().collect();
+    ///
+    /// c.truncate(1);
+    /// assert!(c.iter().eq(["a"]));
+    /// ```
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        self.indices.truncate(len);
+    }
+
+    /// Reserves space to hold `additional` indices.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.indices.reserve(additional);
+    }
+
+    /// Reserves space to hold `additional` indices, or reports that the allocation failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the index container fails to allocate.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.indices.try_reserve(additional)
+    }
+
+    /// Remove all elements while possibly retaining allocations.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.indices.clear();
+        self.region.clear();
+    }
+
+    /// Reserve space for the items returned by the iterator.
+    #[inline]
+    pub fn reserve_items<T>(&mut self, items: impl Iterator<Item = T> + Clone)
+    where
+        R: ReserveItems<T>,
+    {
+        ReserveItems::reserve_items(&mut self.region, items);
+    }
+
+    /// Appends the items of `items` to the stack, reserving space for them up front.
+    ///
+    /// In contrast to [`Extend::extend`], this function requires a cloneable iterator, which
+    /// allows it to reserve space in the region before pushing the items, avoiding reallocations.
+    #[inline]
+    pub fn extend_reserved<T, I>(&mut self, items: I)
+    where
+        R: Push<T> + ReserveItems<T>,
+        I: Iterator<Item = T> + Clone,
+    {
+        self.reserve(items.size_hint().0);
+        self.reserve_items(items.clone());
+        for item in items {
+            self.copy(item);
+        }
+    }
+
+    /// Reserve space for the regions returned by the iterator.
+    #[inline]
+    pub fn reserve_regions<'a>(&mut self, regions: impl Iterator<Item = &'a R> + Clone)
+    where
+        R: 'a,
+    {
+        self.region.reserve_regions(regions);
+    }
+
+    /// Reserve space for the regions returned by the iterator, or report that an allocation
+    /// failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the region fails to allocate.
+    #[inline]
+    pub fn try_reserve_regions<'a>(
+        &mut self,
+        regions: impl Iterator<Item = &'a R> + Clone,
+    ) -> Result<(), TryReserveError>
+    where
+        R: 'a,
+    {
+        self.region.try_reserve_regions(regions)
+    }
+
+    /// Extends the stack by the items of all `stacks`, in order, reserving space for them
+    /// before copying.
+    pub fn extend_from_stacks<'a>(&mut self, stacks: impl Iterator<Item = &'a Self> + Clone)
+    where
+        Self: 'a,
+        for<'b> R: Push<<R as Region>::ReadItem<'b>>,
+    {
+        self.reserve(stacks.clone().map(Self::len).sum());
+        self.reserve_regions(stacks.clone().map(|stack| &stack.region));
+        for stack in stacks {
+            for item in stack.indices.iter() {
+                self.copy(stack.region.index(item));
+            }
+        }
+    }
+
+    /// Appends the elements of `other` to this stack, in order.
+    ///
+    /// Indices of one region have no meaning in another region, so this copies each element
+    /// of `other` into this stack's region, unlike [`Self::merge_capacity`], which only
+    /// allocates space. See [`Self::extend_from_stacks`] for appending several stacks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let mut c: FlatStack<StringRegion> = ["a"].into_iter().collect();
+    /// let other: FlatStack<StringRegion> = ["b", "c"].into_iter().collect();
+    ///
+    /// c.append(&other);
+    /// assert!(c.iter().eq(["a", "b", "c"]));
+    /// ```
+    pub fn append(&mut self, other: &Self)
+    where
+        for<'a> R: Push<<R as Region>::ReadItem<'a>>,
+    {
+        self.extend_from_stacks(std::iter::once(other));
+    }
+
+    /// Retains only the elements for which `f` returns `true`, preserving their order.
+    ///
+    /// Regions do not support removing items, so this copies the retained elements into a
+    /// fresh region and index container, and replaces the current ones. Costs time linear in
+    /// the number of elements, and requires the region to absorb its own read items.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let mut c: FlatStack<StringRegion> = ["a", "bb", "ccc"].into_iter().collect();
+    ///
+    /// c.retain(|item| item.len() != 2);
+    /// assert!(c.iter().eq(["a", "ccc"]));
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: for<'a> FnMut(R::ReadItem<'a>) -> bool,
+        for<'a> R: Push<<R as Region>::ReadItem<'a>>,
+    {
+        let mut retained = Self::merge_capacity(std::iter::once(&*self));
+        for index in self.indices.iter() {
+            if f(self.region.index(index)) {
+                retained.copy(self.region.index(index));
+            }
+        }
+        *self = retained;
+    }
+
+    /// Merges `stacks`, each sorted by `key`, into a single stack sorted by `key`.
+    ///
+    /// Performs a k-way merge over a binary heap of cursors, one per stack. Items with equal
+    /// keys retain the order of their stacks. The result is unspecified if a stack is not
+    /// sorted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let a: FlatStack<StringRegion> = ["a", "ccc"].into_iter().collect();
+    /// let b: FlatStack<StringRegion> = ["bb", "dddd"].into_iter().collect();
+    ///
+    /// let c = FlatStack::merge_sorted([&a, &b].into_iter(), |item| item.len());
+    /// assert!(c.iter().eq(["a", "bb", "ccc", "dddd"]));
+    /// ```
+    #[must_use]
+    pub fn merge_sorted<'a, K, F>(
+        stacks: impl Iterator<Item = &'a Self> + Clone + 'a,
+        key: F,
+    ) -> Self
+    where
+        Self: 'a,
+        K: Ord,
+        F: for<'b> Fn(&R::ReadItem<'b>) -> K,
+        for<'b> R: Push<<R as Region>::ReadItem<'b>>,
+    {
+        let mut merged = Self::merge_capacity(stacks.clone());
+        let stacks: Vec<_> = stacks.collect();
+        let mut cursors: BinaryHeap<_> = stacks
+            .iter()
+            .enumerate()
+            .filter(|(_, stack)| !stack.is_empty())
+            .map(|(stack, items)| Reverse((key(&items.get(0)), stack, 0)))
+            .collect();
+        while let Some(Reverse((_, stack, offset))) = cursors.pop() {
+            merged.copy(stacks[stack].get(offset));
+            let next = offset + 1;
+            if next < stacks[stack].len() {
+                cursors.push(Reverse((key(&stacks[stack].get(next)), stack, next)));
+            }
+        }
+        merged
+    }
+
+    /// Heap size, size - capacity
+    #[inline]
+    pub fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
+        self.region.heap_size(&mut callback);
+        self.indices.heap_size(callback);
+    }
+}
+
+impl<R, S> FlatStack<R, S>
+where
+    R: Region,
+    S: IndexContainer<<R as Region>::Index>,
+{
+    /// Iterate the items in this stack.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, R, S::Iter<'_>> {
+        self.into_iter()
+    }
+
+    /// Iterate the items in this stack together with their offset in the stack.
+    #[inline]
+    pub fn iter_indexed(&self) -> IterIndexed<'_, R, S::Iter<'_>> {
+        IterIndexed {
+            inner: self.iter(),
+            offset: 0,
+        }
+    }
+
+    /// Iterate the items in this stack without reading them from the region. Each item is a
+    /// [`LazyReadItem`], which reads the item from the region when decoded, so items that
+    /// are skipped based on their index never pay the cost of decoding.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "bb", "ccc"].into_iter().collect();
+    ///
+    /// let item = c.iter_decode_lazy().nth(1).unwrap();
+    /// assert_eq!("bb", item.decode());
+    /// ```
+    #[inline]
+    pub fn iter_decode_lazy(&self) -> impl Iterator<Item = LazyReadItem<'_, R>> + Clone + '_ {
+        self.indices
+            .iter()
+            .map(move |index| LazyReadItem::new(&self.region, index))
+    }
+}
+
+impl<R, S> FlatStack<R, S>
+where
+    R: Region,
+    for<'a> R::ReadItem<'a>: AsRef<[u8]>,
+    S: IndexContainer<<R as Region>::Index>,
+{
+    /// Writes the bytes of all elements to `out` in order, without separators, and returns
+    /// the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `out` if writing fails, in which case a prefix of the elements
+    /// might have been written.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "bb"].into_iter().collect();
+    ///
+    /// let mut out = Vec::new();
+    /// assert_eq!(3, c.write_all_to(&mut out).unwrap());
+    /// assert_eq!(b"abb", out.as_slice());
+    /// ```
+    pub fn write_all_to(&self, out: &mut impl std::io::Write) -> std::io::Result<usize> {
+        let mut written = 0;
+        for item in self.iter() {
+            let bytes = item.as_ref();
+            out.write_all(bytes)?;
+            written += bytes.len();
+        }
+        Ok(written)
+    }
+}
+
+impl<R, S> FlatStack<R, S>
+where
+    R: Region,
+    S: IndexContainer<<R as Region>::Index>,
+{
+    /// Builds a [`SortedIndex`] over the elements of this stack, ordered by `key`.
+    ///
+    /// The index refers to elements by their position in the stack, and does not reflect
+    /// later changes to the stack, which requires building a new index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["cherry", "apple", "banana"].into_iter().collect();
+    ///
+    /// let index = c.build_index(|item| item.len());
+    /// assert!(index.range(5..).eq([1, 0, 2]));
+    /// ```
+    #[must_use]
+    pub fn build_index<K, F>(&self, key: F) -> SortedIndex<K>
+    where
+        K: Ord,
+        F: for<'a> Fn(R::ReadItem<'a>) -> K,
+    {
+        let mut entries: Vec<_> = self
+            .iter()
+            .enumerate()
+            .map(|(offset, item)| (key(item), offset))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        SortedIndex { entries }
+    }
+
+    /// Feeds the logical contents of this stack into `hasher`, which are the number of
+    /// elements followed by each element in order.
+    ///
+    /// The hash depends only on the read items, and not on how the region represents them.
+    /// Stacks with equal elements produce the same hash, even if one of them deduplicates or
+    /// encodes its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::Hasher;
+    /// use flatcontainer::impls::deduplicate::CollapseSequence;
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let plain: FlatStack<StringRegion> = ["a", "a", "b"].into_iter().collect();
+    /// let collapsed: FlatStack<CollapseSequence<StringRegion>> =
+    ///     ["a", "a", "b"].into_iter().collect();
+    ///
+    /// let (mut left, mut right) = (DefaultHasher::new(), DefaultHasher::new());
+    /// plain.content_hash(&mut left);
+    /// collapsed.content_hash(&mut right);
+    /// assert_eq!(left.finish(), right.finish());
+    /// ```
+    pub fn content_hash<H: Hasher>(&self, hasher: &mut H)
+    where
+        for<'a> R::ReadItem<'a>: Hash,
+    {
+        hasher.write_usize(self.len());
+        for item in self.iter() {
+            item.hash(hasher);
+        }
+    }
+
+    /// Returns the number of distinct elements in this stack.
+    ///
+    /// Collects the owned representation of all elements, which allocates.
+    #[must_use]
+    pub fn distinct_count(&self) -> usize
+    where
+        R::Owned: Hash + Eq,
+    {
+        self.iter()
+            .map(IntoOwned::into_owned)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Returns the fraction of elements that are equal to an earlier element, between `0.0`
+    /// for a stack without duplicates and close to `1.0` for a stack of a single repeated
+    /// element. Returns `0.0` for an empty stack.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["a", "b", "a", "a"].into_iter().collect();
+    ///
+    /// assert_eq!(2, c.distinct_count());
+    /// assert_eq!(0.5, c.duplication_ratio());
+    /// ```
+    #[must_use]
+    pub fn duplication_ratio(&self) -> f64
+    where
+        R::Owned: Hash + Eq,
+    {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let duplicates = self.len() - self.distinct_count();
+        duplicates as f64 / self.len() as f64
+    }
+
+    /// Returns the distinct elements of this stack in ascending order.
+    ///
+    /// Collects the owned representation of all elements, which allocates.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use flatcontainer::{FlatStack, StringRegion};
+    /// let c: FlatStack<StringRegion> = ["b", "a", "b"].into_iter().collect();
+    ///
+    /// assert_eq!(vec!["a", "b"], c.distinct_values());
+    /// ```
+    #[must_use]
+    pub fn distinct_values(&self) -> Vec<R::Owned>
+    where
+        R::Owned: Ord,
+    {
+        self.iter()
+            .map(IntoOwned::into_owned)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Offsets of a [`FlatStack`]'s elements, sorted by a key. Built by [`FlatStack::build_index`].
+#[derive(Debug, Clone)]
+pub struct SortedIndex<K> {
+    /// Pairs of key and offset, sorted by key, and by offset for equal keys.
+    entries: Vec<(K, usize)>,
+}
+
+impl<K: Ord> SortedIndex<K> {
+    /// Returns the offsets of the elements whose key is in `range`, in key order. Elements with
+    /// equal keys appear in the order of the stack.
+    pub fn range<Q, B>(&self, range: B) -> impl Iterator<Item = usize> + '_
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        B: RangeBounds<Q>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.entries.partition_point(|(k, _)| k.borrow() < start),
+            Bound::Excluded(start) => self.entries.partition_point(|(k, _)| k.borrow() <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.entries.partition_point(|(k, _)| k.borrow() <= end),
+            Bound::Excluded(end) => self.entries.partition_point(|(k, _)| k.borrow() < end),
+            Bound::Unbounded => self.entries.len(),
+        };
+        self.entries[start..end.max(start)]
+            .iter()
+            .map(|(_, offset)| *offset)
+    }
+
+    /// Returns the number of elements in the index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index contains no elements.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// A typed reference to an element of a [`FlatStack`], created by [`FlatStack::ref_at`].
+///
+/// Wraps the region's index of the element, and records the region type to prevent resolving
+/// it against a stack of a different region type. A reference does not borrow the stack.
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(bound = "R::Index: Serialize + for<'a> Deserialize<'a>")
+)]
+pub struct Ref<R: Region> {
+    /// The index of the element in the region.
+    index: R::Index,
+    /// Marker for the region type, which does not require `R` to be `Send` or `Sync`.
+    _marker: PhantomData<fn() -> R>,
+}
+
+impl<R: Region> Ref<R> {
+    /// Wraps an index into `R`.
+    #[inline]
+    #[must_use]
+    pub fn new(index: R::Index) -> Self {
+        Self {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the wrapped index.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> R::Index {
+        self.index
+    }
+}
+
+impl<R: Region> Clone for Ref<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: Region> Copy for Ref<R> {}
+
+impl<R: Region> PartialEq for Ref<R>
+where
+    R::Index: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<R: Region> Eq for Ref<R> where R::Index: Eq {}
+
+impl<R: Region> Debug for Ref<R>
+where
+    R::Index: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Ref").field(&self.index).finish()
     }
+}
 
-    /// Returns the element at the `index` position.
+/// An element of a region whose read item is not obtained until it is decoded, created by
+/// [`FlatStack::iter_decode_lazy`].
+///
+/// Regions that encode their contents, such as
+/// [`CodecRegion`](impls::codec::CodecRegion), decode an item when indexing it. A lazy read
+/// item defers indexing the region to [`LazyReadItem::decode`], and exposes the index as cheap
+/// metadata to filter on beforehand. Decoding does not cache its result, so every call indexes
+/// the region again.
+pub struct LazyReadItem<'a, R: Region> {
+    /// The region that contains the element.
+    region: &'a R,
+    /// The index of the element in the region.
+    index: R::Index,
+}
+
+impl<'a, R: Region> LazyReadItem<'a, R> {
+    /// Wraps an index into `region`.
     #[inline]
     #[must_use]
-    pub fn get(&self, index: usize) -> R::ReadItem<'_> {
-        self.region.index(self.indices.index(index))
+    pub fn new(region: &'a R, index: R::Index) -> Self {
+        Self { region, index }
     }
 
-    /// Returns the number of indices in the stack.
+    /// Returns the index of the element in the region.
     #[inline]
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.indices.len()
+    pub fn index(&self) -> R::Index {
+        self.index
     }
 
-    /// Returns `true` if the stack contains no elements.
+    /// Reads the element from the region.
     #[inline]
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.indices.is_empty()
+    pub fn decode(&self) -> R::ReadItem<'a> {
+        self.region.index(self.index)
     }
+}
 
-    /// Reserves space to hold `additional` indices.
+impl<R: Region> Clone for LazyReadItem<'_, R> {
     #[inline]
-    pub fn reserve(&mut self, additional: usize) {
-        self.indices.reserve(additional);
+    fn clone(&self) -> Self {
+        *self
     }
+}
 
-    /// Remove all elements while possibly retaining allocations.
+impl<R: Region> Copy for LazyReadItem<'_, R> {}
+
+impl<R: Region> Debug for LazyReadItem<'_, R>
+where
+    R::Index: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("LazyReadItem").field(&self.index).finish()
+    }
+}
+
+/// A map from the indices of a region to the indices of their copies in another region,
+/// created by [`Region::rebuild_with_map`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IndexMap<I: Hash + Eq> {
+    /// Maps an index to the index of its copy.
+    map: std::collections::HashMap<I, I>,
+}
+
+impl<I: Hash + Eq> Default for IndexMap<I> {
+    fn default() -> Self {
+        Self {
+            map: std::collections::HashMap::default(),
+        }
+    }
+}
+
+impl<I: Index + Hash + Eq> IndexMap<I> {
+    /// Returns the index of the copy of `index`, or `None` if it was not copied.
     #[inline]
-    pub fn clear(&mut self) {
-        self.indices.clear();
-        self.region.clear();
+    #[must_use]
+    pub fn get(&self, index: I) -> Option<I> {
+        self.map.get(&index).copied()
     }
 
-    /// Reserve space for the items returned by the iterator.
+    /// Returns the index of the copy of `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` was not copied.
     #[inline]
-    pub fn reserve_items<T>(&mut self, items: impl Iterator<Item = T> + Clone)
-    where
-        R: ReserveItems<T>,
-    {
-        ReserveItems::reserve_items(&mut self.region, items);
+    #[must_use]
+    pub fn translate(&self, index: I) -> I {
+        self.get(index).expect("IndexMap: index was not copied")
     }
 
-    /// Reserve space for the regions returned by the iterator.
+    /// Replaces each of `indices` by the index of its copy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index was not copied.
     #[inline]
-    pub fn reserve_regions<'a>(&mut self, regions: impl Iterator<Item = &'a R> + Clone)
+    pub fn translate_all<'a>(&self, indices: impl IntoIterator<Item = &'a mut I>)
     where
-        R: 'a,
+        I: 'a,
     {
-        self.region.reserve_regions(regions);
+        for index in indices {
+            *index = self.translate(*index);
+        }
     }
 
-    /// Heap size, size - capacity
+    /// Returns the number of copied indices.
     #[inline]
-    pub fn heap_size<F: FnMut(usize, usize)>(&self, mut callback: F) {
-        self.region.heap_size(&mut callback);
-        self.indices.heap_size(callback);
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.map.len()
     }
-}
 
-impl<R, S> FlatStack<R, S>
-where
-    R: Region,
-    S: IndexContainer<<R as Region>::Index>,
-{
-    /// Iterate the items in this stack.
+    /// Returns `true` if no index was copied.
     #[inline]
-    pub fn iter(&self) -> Iter<R, S::Iter<'_>> {
-        self.into_iter()
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
     }
 }
 
@@ -387,6 +1303,57 @@ where
     }
 }
 
+/// An iterator over the items of a [`FlatStack`] and their offsets, see
+/// [`FlatStack::iter_indexed`].
+pub struct IterIndexed<'a, R, S>
+where
+    R: Region,
+{
+    /// Iterator over items.
+    inner: Iter<'a, R, S>,
+    /// Offset of the next item.
+    offset: usize,
+}
+
+impl<'a, R, S> Iterator for IterIndexed<'a, R, S>
+where
+    R: Region,
+    S: Iterator<Item = <R as Region>::Index>,
+{
+    type Item = (usize, R::ReadItem<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        let offset = self.offset;
+        self.offset += 1;
+        Some((offset, item))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, R, S> ExactSizeIterator for IterIndexed<'a, R, S>
+where
+    R: Region,
+    S: ExactSizeIterator<Item = <R as Region>::Index>,
+{
+}
+
+impl<'a, R, S> Clone for IterIndexed<'a, R, S>
+where
+    R: Region,
+    S: Iterator<Item = <R as Region>::Index> + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            offset: self.offset,
+        }
+    }
+}
+
 impl<R, S, T> FromIterator<T> for FlatStack<R, S>
 where
     R: Region + Push<T>,
@@ -425,7 +1392,9 @@ pub struct PushIter<I>(pub I);
 #[cfg(test)]
 mod tests {
     use crate::impls::deduplicate::{CollapseSequence, ConsecutiveIndexPairs};
-    use crate::impls::tuple::TupleARegion;
+    use crate::impls::index::IndexOptimized;
+    use crate::impls::option::OptionIndex;
+    use crate::impls::tuple::{TupleABRegion, TupleARegion};
 
     use super::*;
 
@@ -485,9 +1454,9 @@ mod tests {
 
     #[test]
     fn all_types() {
-        fn test_copy<T, R: Region + Clone>(t: T)
+        fn test_copy<T, R>(t: T)
         where
-            for<'a> R: Push<T> + Push<<R as Region>::ReadItem<'a>>,
+            for<'a> R: Region + Clone + Push<T> + Push<<R as Region>::ReadItem<'a>>,
             // Make sure that types are debug, even if we don't use this in the test.
             for<'a> R::ReadItem<'a>: Debug,
         {
@@ -559,6 +1528,43 @@ mod tests {
         test_copy::<_, MirrorRegion<std::num::Wrapping<isize>>>(std::num::Wrapping(0isize));
         test_copy::<_, MirrorRegion<std::num::Wrapping<isize>>>(&std::num::Wrapping(0isize));
 
+        test_copy::<_, MirrorRegion<std::num::NonZeroU8>>(std::num::NonZeroU8::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU8>>(&std::num::NonZeroU8::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU16>>(std::num::NonZeroU16::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU16>>(&std::num::NonZeroU16::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU32>>(std::num::NonZeroU32::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU32>>(&std::num::NonZeroU32::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU64>>(std::num::NonZeroU64::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU64>>(&std::num::NonZeroU64::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU128>>(std::num::NonZeroU128::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroU128>>(
+            &std::num::NonZeroU128::new(1).unwrap(),
+        );
+        test_copy::<_, MirrorRegion<std::num::NonZeroUsize>>(
+            std::num::NonZeroUsize::new(1).unwrap(),
+        );
+        test_copy::<_, MirrorRegion<std::num::NonZeroUsize>>(
+            &std::num::NonZeroUsize::new(1).unwrap(),
+        );
+        test_copy::<_, MirrorRegion<std::num::NonZeroI8>>(std::num::NonZeroI8::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI8>>(&std::num::NonZeroI8::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI16>>(std::num::NonZeroI16::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI16>>(&std::num::NonZeroI16::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI32>>(std::num::NonZeroI32::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI32>>(&std::num::NonZeroI32::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI64>>(std::num::NonZeroI64::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI64>>(&std::num::NonZeroI64::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI128>>(std::num::NonZeroI128::new(1).unwrap());
+        test_copy::<_, MirrorRegion<std::num::NonZeroI128>>(
+            &std::num::NonZeroI128::new(1).unwrap(),
+        );
+        test_copy::<_, MirrorRegion<std::num::NonZeroIsize>>(
+            std::num::NonZeroIsize::new(1).unwrap(),
+        );
+        test_copy::<_, MirrorRegion<std::num::NonZeroIsize>>(
+            &std::num::NonZeroIsize::new(1).unwrap(),
+        );
+
         test_copy::<_, ResultRegion<MirrorRegion<u8>, MirrorRegion<u8>>>(Result::<u8, u8>::Ok(0));
         test_copy::<_, ResultRegion<MirrorRegion<u8>, MirrorRegion<u8>>>(&Result::<u8, u8>::Ok(0));
         test_copy::<_, ResultRegion<MirrorRegion<u8>, MirrorRegion<u8>>>(Result::<u8, u8>::Err(0));
@@ -578,6 +1584,10 @@ mod tests {
 
         test_copy::<_, OwnedRegion<_>>([0u8].as_slice());
         test_copy::<_, OwnedRegion<_>>(&[0u8].as_slice());
+        test_copy::<_, OwnedRegion<_>>([std::num::Wrapping(0i32)].as_slice());
+        test_copy::<_, SliceRegion<MirrorRegion<std::num::Wrapping<i32>>>>(
+            [std::num::Wrapping(0i32)].as_slice(),
+        );
 
         test_copy::<_, <(u8, u8) as RegionPreference>::Region>((1, 2));
         test_copy::<_, <(u8, u8) as RegionPreference>::Region>(&(1, 2));
@@ -606,6 +1616,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_wrapping_slices() {
+        use std::num::Wrapping;
+
+        let items = [Wrapping(i32::MAX), Wrapping(-1), Wrapping(0)];
+
+        let mut owned = FlatStack::<OwnedRegion<Wrapping<i32>>>::default();
+        owned.copy(items.as_slice());
+        owned.copy(&items.to_vec());
+        assert_eq!(items, owned.get(0));
+        assert_eq!(items.to_vec(), owned.get(1).into_owned());
+        assert_eq!(Wrapping(i32::MIN), owned.get(0)[0] + Wrapping(1));
+
+        let mut sliced = FlatStack::<SliceRegion<MirrorRegion<Wrapping<i32>>>>::default();
+        sliced.copy(items.as_slice());
+        sliced.copy(owned.get(1));
+        for item in &sliced {
+            assert!(item.iter().eq(items));
+            assert_eq!(items.to_vec(), item.into_owned());
+        }
+    }
+
     #[test]
     fn slice_region_read_item() {
         fn is_clone<T: Clone>(_: &T) {}
@@ -661,6 +1693,606 @@ mod tests {
         owned_roundtrip::<StringRegion, String>(&mut c, index);
     }
 
+    #[test]
+    fn test_extend_reserved() {
+        let data = ["a", "bc", "def", "ghij"];
+
+        let mut c = FlatStack::default_impl::<String>();
+        c.extend_reserved(data.iter());
+        assert!(data.iter().copied().eq(c.iter()));
+
+        let mut cap = 0;
+        c.heap_size(|_, ca| cap += ca);
+
+        // Reserving exactly means that pushing the same items again requires no more memory.
+        let mut reserved = FlatStack::default_impl::<String>();
+        reserved.reserve(data.len());
+        reserved.reserve_items(data.iter());
+        let mut expected = 0;
+        reserved.heap_size(|_, ca| expected += ca);
+        assert_eq!(expected, cap);
+    }
+
+    #[test]
+    fn test_clone_from_custom_index() {
+        let mut source =
+            FlatStack::<ConsecutiveIndexPairs<StringRegion>, IndexOptimized>::default();
+        // Strings of different lengths spill the offsets into the index list.
+        for i in 0..100 {
+            source.copy("x".repeat(i % 7));
+        }
+
+        let mut target =
+            FlatStack::<ConsecutiveIndexPairs<StringRegion>, IndexOptimized>::default();
+        for i in 0..1000 {
+            target.copy("y".repeat(i % 5 + 1));
+        }
+        let mut capacity = 0;
+        target.heap_size(|_, cap| capacity += cap);
+
+        target.clone_from(&source);
+        assert_eq!(source.len(), target.len());
+        assert!(source.iter().eq(target.iter()));
+
+        // Cloning into the larger stack reuses its allocations.
+        let mut reused = 0;
+        target.heap_size(|_, cap| reused += cap);
+        assert_eq!(capacity, reused);
+    }
+
+    #[test]
+    fn test_ref() {
+        let mut c = FlatStack::<StringRegion>::default();
+        c.copy("abc");
+        let r = c.ref_at(0);
+        for i in 0..100 {
+            c.copy(format!("item {i}"));
+        }
+        let last = c.ref_at(c.len() - 1);
+        assert_eq!("abc", c.get_ref(r));
+        assert_eq!("item 99", c.get_ref(last));
+        assert_ne!(r, last);
+
+        // References resolve against clones of the stack.
+        let clone = c.clone();
+        assert_eq!("abc", clone.get_ref(r));
+        assert_eq!(r, Ref::new(r.index()));
+    }
+
+    #[test]
+    fn test_extend_from_stacks() {
+        let sources: Vec<FlatStack<StringRegion>> = [&["a", "bc"][..], &[], &["def", "ghij", "k"]]
+            .iter()
+            .map(|items| items.iter().copied().collect())
+            .collect();
+
+        let mut c = FlatStack::<StringRegion>::default();
+        c.extend_from_stacks(sources.iter());
+        assert!(c.iter().eq(["a", "bc", "def", "ghij", "k"]));
+
+        // The stack reserved exactly the space it needed, and did not grow while copying.
+        let (mut size, mut capacity) = (0, 0);
+        c.heap_size(|siz, cap| {
+            size += siz;
+            capacity += cap;
+        });
+        assert_eq!(size, capacity);
+    }
+
+    #[test]
+    fn test_append() {
+        let mut c: FlatStack<StringRegion> = ["abc", "de"].into_iter().collect();
+        let other: FlatStack<StringRegion> = ["", "fghi"].into_iter().collect();
+        c.append(&other);
+        c.append(&FlatStack::default());
+        assert_eq!(4, c.len());
+        assert!(c.iter().eq(["abc", "de", "", "fghi"]));
+        assert_eq!(2, other.len());
+    }
+
+    #[test]
+    fn test_item_storage_size() {
+        let mut r = <SliceRegion<StringRegion>>::default();
+        let index = r.push(["abc", "de"]);
+        let indexes = 2 * std::mem::size_of::<(usize, usize)>();
+        assert_eq!(5 + indexes, r.item_storage_size(index));
+
+        let mut r = <MirrorRegion<u64>>::default();
+        let index = r.push(1);
+        assert_eq!(0, r.item_storage_size(index));
+
+        let mut r = <TupleABRegion<StringRegion, MirrorRegion<u8>>>::default();
+        let index = r.push(("abc", 1));
+        assert_eq!(3, r.item_storage_size(index));
+
+        let mut r = <OptionRegion<StringRegion>>::default();
+        let some = r.push(Some("abc"));
+        let none = r.push(None::<&str>);
+        assert_eq!(
+            (3, 0),
+            (r.item_storage_size(some), r.item_storage_size(none))
+        );
+
+        let mut r = <ResultRegion<StringRegion, OwnedRegion<u32>>>::default();
+        let ok = r.push(Ok::<_, &[u32]>("abc"));
+        let err = r.push(Err::<&str, _>([1, 2].as_slice()));
+        assert_eq!((3, 8), (r.item_storage_size(ok), r.item_storage_size(err)));
+
+        let mut r = <crate::impls::columns::ColumnsRegion<StringRegion>>::default();
+        let index = r.push(["a", "bc"]);
+        assert_eq!(3 + indexes, r.item_storage_size(index));
+    }
+
+    #[test]
+    fn test_region_len() {
+        let mut r = <OwnedRegion<u8>>::default();
+        assert!(r.is_empty());
+        let _ = r.push([1, 2, 3]);
+        let _ = r.push([4]);
+        assert_eq!(4, r.len());
+        r.clear();
+        assert!(r.is_empty());
+
+        let mut r = <StringRegion>::default();
+        let _ = r.push("abc");
+        let _ = r.push("");
+        assert_eq!(3, r.len());
+
+        let mut r = <SliceRegion<StringRegion>>::default();
+        let _ = r.push(["a", "bc"]);
+        let _ = r.push(["d"]);
+        assert_eq!(3, r.len());
+
+        let mut r = <MirrorRegion<u8>>::default();
+        let _ = r.push(1);
+        assert!(r.is_empty());
+
+        let mut r = <TupleABRegion<StringRegion, OwnedRegion<u8>>>::default();
+        let _ = r.push(("ab", [1, 2, 3]));
+        assert_eq!(5, r.len());
+
+        let mut r = <crate::impls::columns::ColumnsRegion<StringRegion>>::default();
+        assert!(r.is_empty());
+        let _ = r.push(["a", "b", "c"]);
+        let _ = r.push(["d"]);
+        assert_eq!(2, r.len());
+        r.clear();
+        assert!(r.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut c: FlatStack<MirrorRegion<u64>> = (0..10).collect();
+        c.retain(|item| item % 2 == 0);
+        assert!(c.iter().eq([0, 2, 4, 6, 8]));
+
+        c.retain(|_| false);
+        assert!(c.is_empty());
+
+        let mut c: FlatStack<SliceRegion<StringRegion>> =
+            [vec!["a"], vec![], vec!["b", "c"]].iter().collect();
+        c.retain(|item| !item.is_empty());
+        assert_eq!(2, c.len());
+        assert!(c.get(1).iter().eq(["b", "c"]));
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let a: FlatStack<MirrorRegion<u8>> = [1, 4, 4, 9, 200].into_iter().collect();
+        let b: FlatStack<MirrorRegion<u8>> = [0, 4, 5, 255].into_iter().collect();
+        let empty = FlatStack::<MirrorRegion<u8>>::default();
+
+        let c = FlatStack::merge_sorted([&a, &empty, &b].into_iter(), |item| *item);
+        assert_eq!(a.len() + b.len(), c.len());
+        assert!(c.iter().eq([0, 1, 4, 4, 4, 5, 9, 200, 255]));
+
+        // Merging with a descending key keeps items with equal keys in stack order.
+        let a: FlatStack<MirrorRegion<u8>> = [9, 5, 1].into_iter().collect();
+        let b: FlatStack<MirrorRegion<u8>> = [8, 4, 2].into_iter().collect();
+        let c = FlatStack::merge_sorted([&a, &b].into_iter(), |item| Reverse(*item / 2));
+        assert!(c.iter().eq([9, 8, 5, 4, 2, 1]));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        use crate::impls::slice::ReadSlice;
+
+        fn hash<R: Region, S: IndexContainer<R::Index>>(c: &FlatStack<R, S>) -> u64
+        where
+            for<'a> R::ReadItem<'a>: Hash,
+        {
+            let mut hasher = DefaultHasher::new();
+            c.content_hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let items = ["abc", "abc", "", "abc", "de"];
+        let plain: FlatStack<StringRegion> = items.into_iter().collect();
+        let collapsed: FlatStack<CollapseSequence<StringRegion>> = items.into_iter().collect();
+        assert_eq!(hash(&plain), hash(&collapsed));
+
+        let mut shorter = plain.clone();
+        shorter.truncate(items.len() - 1);
+        assert_ne!(hash(&plain), hash(&shorter));
+
+        // Slices hash equally whether read from the region or borrowed from owned data.
+        let slices = [vec![1_u8, 2], vec![], vec![3]];
+        let c: FlatStack<SliceRegion<MirrorRegion<u8>>> = slices.iter().collect();
+        let (mut left, mut right) = (DefaultHasher::new(), DefaultHasher::new());
+        c.get(0).hash(&mut left);
+        <ReadSlice<MirrorRegion<u8>> as IntoOwned>::borrow_as(&slices[0]).hash(&mut right);
+        assert_eq!(left.finish(), right.finish());
+    }
+
+    #[test]
+    fn test_distinct_count() {
+        let c: FlatStack<StringRegion> = ["x", "y", "x", "z", "y", "x", "w", "x"]
+            .into_iter()
+            .collect();
+        assert_eq!(4, c.distinct_count());
+        assert_eq!(0.5, c.duplication_ratio());
+
+        let unique: FlatStack<MirrorRegion<u32>> = (0..10).collect();
+        assert_eq!(10, unique.distinct_count());
+        assert_eq!(0.0, unique.duplication_ratio());
+
+        let empty = FlatStack::<StringRegion>::default();
+        assert_eq!(0, empty.distinct_count());
+        assert_eq!(0.0, empty.duplication_ratio());
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut c: FlatStack<StringRegion> = ["a", "bb", "ccc"].into_iter().collect();
+        assert_eq!(Some("ccc".to_string()), c.pop());
+        assert_eq!(2, c.len());
+        c.copy("d");
+        assert!(c.iter().eq(["a", "bb", "d"]));
+        assert_eq!(Some("d".to_string()), c.pop());
+        assert_eq!(Some("bb".to_string()), c.pop());
+        assert_eq!(Some("a".to_string()), c.pop());
+        assert_eq!(None, c.pop());
+        assert!(c.is_empty());
+
+        let mut c = FlatStack::<OptionRegion<MirrorRegion<u8>>, OptionIndex<Vec<u8>>>::default();
+        c.copy(Some(1));
+        c.copy(None::<u8>);
+        assert_eq!(Some(None), c.pop());
+        assert_eq!(Some(Some(1)), c.pop());
+        assert_eq!(None, c.pop());
+    }
+
+    #[test]
+    fn test_sample() {
+        /// A linear congruential generator with a fixed seed.
+        fn lcg() -> impl FnMut(usize) -> usize {
+            let mut state = 42_u64;
+            move |n| {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                ((state >> 33) % n as u64) as usize
+            }
+        }
+
+        let c: FlatStack<StringRegion> = (0..1000).map(|i| i.to_string()).collect();
+
+        let sample = c.sample(10, lcg());
+        assert_eq!(10, sample.len());
+        assert_eq!(sample, c.sample(10, lcg()));
+        assert!(sample.as_slice().iter().all(|&position| position < 1000));
+        let distinct: BTreeSet<_> = sample.as_slice().iter().collect();
+        assert_eq!(10, distinct.len());
+
+        let owned = c.sample_owned(10, lcg());
+        let expected: Vec<_> = sample.as_slice().iter().map(ToString::to_string).collect();
+        assert_eq!(expected, owned);
+
+        let small: FlatStack<StringRegion> = ["a", "b", "c"].into_iter().collect();
+        assert_eq!(vec![0, 1, 2], small.sample(5, lcg()));
+        assert!(c.sample(0, lcg()).is_empty());
+    }
+
+    #[test]
+    fn test_collect_owned_into() {
+        let slices = [vec![1, 2, 3], vec![], vec![4, 5]];
+        let mut c: FlatStack<SliceRegion<MirrorRegion<u8>>> = slices.iter().collect();
+        let mut out = Vec::new();
+        c.collect_owned_into(&mut out);
+        assert_eq!(slices.to_vec(), out);
+
+        // Collecting again reuses the allocations of the inner vectors.
+        let pointers: Vec<_> = out.iter().map(Vec::as_ptr).collect();
+        c.clear();
+        c.copy([6, 7, 8]);
+        c.copy([9]);
+        c.collect_owned_into(&mut out);
+        assert_eq!(vec![vec![6, 7, 8], vec![9]], out);
+        assert_eq!(pointers[0], out[0].as_ptr());
+
+        c.copy([10]);
+        c.copy([11, 12]);
+        c.collect_owned_into(&mut out);
+        assert_eq!(vec![vec![6, 7, 8], vec![9], vec![10], vec![11, 12]], out);
+        assert_eq!(pointers[0], out[0].as_ptr());
+    }
+
+    #[test]
+    fn test_iter_decode_lazy() {
+        use crate::impls::huffman_container::HuffmanContainer;
+        use std::cell::Cell;
+
+        /// Counts how often the inner region decodes an item.
+        #[derive(Default)]
+        struct Counting<R> {
+            inner: R,
+            decodes: Cell<usize>,
+        }
+
+        impl<R: Region> Region for Counting<R> {
+            type Owned = R::Owned;
+            type ReadItem<'a> = R::ReadItem<'a> where Self: 'a;
+            type Index = R::Index;
+
+            fn merge_regions<'a>(regions: impl Iterator<Item = &'a Self> + Clone) -> Self
+            where
+                Self: 'a,
+            {
+                Self {
+                    inner: R::merge_regions(regions.map(|r| &r.inner)),
+                    decodes: Cell::default(),
+                }
+            }
+
+            fn index(&self, index: Self::Index) -> Self::ReadItem<'_> {
+                self.decodes.set(self.decodes.get() + 1);
+                self.inner.index(index)
+            }
+
+            fn reserve_regions<'a, I>(&mut self, regions: I)
+            where
+                Self: 'a,
+                I: Iterator<Item = &'a Self> + Clone,
+            {
+                self.inner.reserve_regions(regions.map(|r| &r.inner));
+            }
+
+            fn clear(&mut self) {
+                self.inner.clear();
+            }
+
+            fn heap_size<F: FnMut(usize, usize)>(&self, callback: F) {
+                self.inner.heap_size(callback);
+            }
+
+            fn reborrow<'b, 'a: 'b>(item: Self::ReadItem<'a>) -> Self::ReadItem<'b>
+            where
+                Self: 'a,
+            {
+                R::reborrow(item)
+            }
+        }
+
+        impl<R: Push<T>, T> Push<T> for Counting<R> {
+            fn push(&mut self, item: T) -> Self::Index {
+                self.inner.push(item)
+            }
+        }
+
+        let items = [[1, 2, 3], [2, 3, 4], [1, 2, 3], [1, 1, 1]];
+        let mut stats = Counting::<HuffmanContainer<u8>>::default();
+        for item in items {
+            let _ = stats.push(item);
+        }
+        // Merging encodes the contents with the statistics of `stats`.
+        let mut c = FlatStack::<Counting<HuffmanContainer<u8>>> {
+            indices: Vec::default(),
+            region: Counting::merge_regions(std::iter::once(&stats)),
+        };
+        for item in items {
+            c.copy(item);
+        }
+
+        // Filtering on the index decodes nothing, and only the first match decodes.
+        let mut matches = c
+            .iter_decode_lazy()
+            .filter(|item| item.index().0 > 0)
+            .map(|item| item.decode().into_owned());
+        assert_eq!(Some(vec![2, 3, 4]), matches.next());
+        assert_eq!(1, c.region.decodes.get());
+    }
+
+    #[test]
+    fn test_write_all_to() {
+        let items = [&b"abc"[..], b"", b"de"];
+        let c: FlatStack<OwnedRegion<u8>> = items.into_iter().collect();
+        let mut out = Vec::new();
+        assert_eq!(5, c.write_all_to(&mut out).unwrap());
+        assert_eq!(items.concat(), out);
+
+        let c: FlatStack<StringRegion> = ["grüezi", " ", "mitenand"].into_iter().collect();
+        out.clear();
+        assert_eq!(16, c.write_all_to(&mut out).unwrap());
+        assert_eq!("grüezi mitenand".as_bytes(), out.as_slice());
+    }
+
+    #[test]
+    fn test_truncate() {
+        let mut c: FlatStack<StringRegion> = ["a", "bb", "ccc"].into_iter().collect();
+        let checkpoint = c.len();
+        c.copy("d");
+        c.copy("ee");
+        c.truncate(checkpoint);
+        assert!(c.iter().eq(["a", "bb", "ccc"]));
+        c.truncate(10);
+        assert_eq!(3, c.len());
+
+        // The region retains the storage of truncated elements.
+        let mut size = 0;
+        c.heap_size(|siz, _| size += siz);
+        c.truncate(0);
+        assert!(c.is_empty());
+        let mut truncated_size = 0;
+        c.heap_size(|siz, _| truncated_size += siz);
+        assert!(truncated_size > 0);
+        assert!(truncated_size < size);
+    }
+
+    #[test]
+    fn test_try_merge_capacity() {
+        let a: FlatStack<StringRegion> = ["a", "bb"].into_iter().collect();
+        let b: FlatStack<StringRegion> = ["ccc"].into_iter().collect();
+
+        let mut c = FlatStack::try_merge_capacity([&a, &b].into_iter()).unwrap();
+        let mut capacity = 0;
+        c.heap_size(|_, cap| capacity += cap);
+        assert!(capacity > 0);
+        c.extend_from_stacks([&a, &b].into_iter());
+        assert!(c.iter().eq(["a", "bb", "ccc"]));
+
+        let mut d = FlatStack::<SliceRegion<StringRegion>>::default();
+        d.copy(["a", "bb"]);
+        let mut e = FlatStack::<SliceRegion<StringRegion>>::default();
+        e.try_reserve_regions(std::iter::once(&d.region)).unwrap();
+        e.extend_from_stacks(std::iter::once(&d));
+        assert!(e.get(0).iter().eq(["a", "bb"]));
+
+        // The merged region keeps the bounds of the source regions.
+        let bounded = FlatStack {
+            indices: Vec::new(),
+            region: crate::impls::bounded::BoundedRegion::<OwnedRegion<u8>>::new(2, 16),
+        };
+        let merged = FlatStack::try_merge_capacity(std::iter::once(&bounded)).unwrap();
+        assert_eq!(
+            (2, 16),
+            (merged.region.max_len(), merged.region.max_bytes())
+        );
+
+        // Reserving more than the address space fails instead of aborting.
+        assert!(c.try_reserve(usize::MAX).is_err());
+        assert!(c.try_reserve(1).is_ok());
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let c: FlatStack<StringRegion> = ["x", "y", "x", "z", "y", "x", "w", "x"]
+            .into_iter()
+            .collect();
+        assert_eq!(vec!["w", "x", "y", "z"], c.distinct_values());
+        assert_eq!(c.distinct_count(), c.distinct_values().len());
+
+        let empty = FlatStack::<StringRegion>::default();
+        assert!(empty.distinct_values().is_empty());
+    }
+
+    #[test]
+    fn test_index_raw_bytes() {
+        fn raw_bytes<R: Region>(region: &R, index: R::Index) -> Option<&[u8]> {
+            region.index_raw_bytes(index)
+        }
+
+        let mut bytes = <impls::bytes::BytesRegion>::default();
+        let index = bytes.push(b"abc".as_slice());
+        assert_eq!(Some(b"abc".as_slice()), raw_bytes(&bytes, index));
+
+        let mut strings = <StringRegion>::default();
+        let index = strings.push("zwölf");
+        assert_eq!(Some("zwölf".as_bytes()), strings.index_raw_bytes(index));
+
+        let mut deduped = <impls::deduplicate::Dedup<StringRegion>>::default();
+        let index = deduped.push("abc");
+        assert_eq!(Some(b"abc".as_slice()), deduped.index_raw_bytes(index));
+
+        let mut signed = <OwnedRegion<i8>>::default();
+        let index = signed.push([1, -1].as_slice());
+        assert_eq!(None, signed.index_raw_bytes(index));
+
+        let mut slices = <SliceRegion<MirrorRegion<u32>>>::default();
+        let index = slices.push([1, 2, 3].as_slice());
+        assert_eq!(None, slices.index_raw_bytes(index));
+    }
+
+    #[test]
+    fn test_rebuild_with_map() {
+        let mut strings = <StringRegion>::default();
+        let mut held: Vec<_> = ["x", "yy", "x", "zzz"]
+            .into_iter()
+            .map(|item| strings.push(item))
+            .collect();
+        held.push(held[1]);
+
+        let mut into = <StringRegion>::default();
+        let _ = into.push("prefix");
+        let map = strings.rebuild_with_map(held.clone(), &mut into);
+        assert_eq!(4, map.len());
+        for index in &held {
+            assert_eq!(strings.index(*index), into.index(map.translate(*index)));
+        }
+        let mut translated = held.clone();
+        map.translate_all(&mut translated);
+        assert!(translated
+            .into_iter()
+            .map(|index| into.index(index))
+            .eq(["x", "yy", "x", "zzz", "yy"]));
+        assert_eq!(None, map.get((100, 101)));
+
+        let mut owned = <OwnedRegion<u8>>::default();
+        let index = owned.push(b"abc".as_slice());
+        let mut into = <OwnedRegion<u8>>::default();
+        let map = owned.rebuild_with_map([index], &mut into);
+        assert_eq!(b"abc", into.index(map.translate(index)));
+
+        let mut slices = <SliceRegion<StringRegion>>::default();
+        let index = slices.push(["a", "b"].as_slice());
+        let mut into = <SliceRegion<StringRegion>>::default();
+        let map = slices.rebuild_with_map([index], &mut into);
+        assert!(into.index(map.translate(index)).iter().eq(["a", "b"]));
+    }
+
+    #[test]
+    fn test_iter_indexed() {
+        let c: FlatStack<StringRegion> = ["a", "b", "c"].into_iter().collect();
+
+        let mut iter = c.iter_indexed();
+        assert_eq!(3, iter.len());
+        assert_eq!(Some((0, "a")), iter.next());
+        assert!(iter.eq([(1, "b"), (2, "c")]));
+
+        // Offsets look up the same items.
+        for (offset, item) in c.iter_indexed() {
+            assert_eq!(c.get(offset), item);
+        }
+    }
+
+    #[test]
+    fn test_sorted_index() {
+        let words = [
+            "delta", "bravo", "charlie", "alpha", "beta", "echo", "bravo", "carrot",
+        ];
+        let c: FlatStack<StringRegion> = words.iter().collect();
+
+        let index = c.build_index(ToString::to_string);
+        assert_eq!(words.len(), index.len());
+
+        // All words starting with 'b' or 'c', in key order, duplicates in stack order.
+        let band: Vec<_> = index
+            .range::<str, _>((Bound::Included("b"), Bound::Excluded("d")))
+            .collect();
+        assert_eq!(vec![4, 1, 6, 7, 2], band);
+        assert!(band
+            .into_iter()
+            .map(|offset| c.get(offset))
+            .eq(["beta", "bravo", "bravo", "carrot", "charlie"]));
+
+        let bravo = "bravo".to_string();
+        assert!(index.range(bravo.clone()..=bravo).eq([1, 6]));
+        assert!(index.range::<String, _>(..).eq([3, 4, 1, 6, 7, 2, 0, 5]));
+        assert_eq!(0, index.range("x".to_string()..).count());
+        assert_eq!(0, index.range("d".to_string().."b".to_string()).count());
+    }
+
     /// Test that items and owned variants can be reborrowed to shorten their lifetimes.
     fn _test_reborrow<R>(item: R::ReadItem<'_>, owned: &R::Owned)
     where
@@ -678,4 +2310,43 @@ mod tests {
         // let _ = item == IntoOwned::borrow_as(owned);
         let _ = R::reborrow(item) == R::reborrow(IntoOwned::borrow_as(owned));
     }
+
+    #[test]
+    fn test_reborrow_nested() {
+        type Inner = TupleABRegion<StringRegion, SliceRegion<MirrorRegion<u8>>>;
+
+        let mut r = <SliceRegion<Inner>>::default();
+        let data = vec![("a".to_string(), vec![1, 2]), ("b".to_string(), vec![])];
+        let index = r.push(&data);
+        {
+            // The owned value lives shorter than the region.
+            let owned = data.clone();
+            _test_reborrow::<SliceRegion<Inner>>(r.index(index), &owned);
+            assert!(SliceRegion::<Inner>::reborrow(r.index(index))
+                == SliceRegion::<Inner>::reborrow(IntoOwned::borrow_as(&owned)));
+
+            // Reborrowing composes through the read items of the nested regions.
+            for (item, owned) in r.index(index).iter().zip(&owned) {
+                let (a, b) = Inner::reborrow(item);
+                assert_eq!(a, <StringRegion>::reborrow(IntoOwned::borrow_as(&owned.0)));
+                assert_eq!(
+                    b,
+                    <SliceRegion<MirrorRegion<u8>>>::reborrow(IntoOwned::borrow_as(&owned.1))
+                );
+            }
+        }
+
+        let mut r = <ColumnsRegion<Inner>>::default();
+        let data = vec![("x".to_string(), vec![3]), ("yz".to_string(), vec![4, 5])];
+        let index = r.push(&data);
+        {
+            let owned = data.clone();
+            _test_reborrow::<ColumnsRegion<Inner>>(r.index(index), &owned);
+            assert!(ColumnsRegion::<Inner>::reborrow(r.index(index))
+                == ColumnsRegion::<Inner>::reborrow(IntoOwned::borrow_as(&owned)));
+            let other = vec![("x".to_string(), vec![3])];
+            assert!(ColumnsRegion::<Inner>::reborrow(r.index(index))
+                != ColumnsRegion::<Inner>::reborrow(IntoOwned::borrow_as(&other)));
+        }
+    }
 }
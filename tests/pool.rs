@@ -0,0 +1,67 @@
+//! Test that many small regions drawing from one pool allocate far less than once each.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use flatcontainer::impls::pooled::{IndexPool, PooledIndex};
+use flatcontainer::{MirrorRegion, Push, Region, SliceRegion};
+
+/// An allocator that counts allocations.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+type PooledRegion = SliceRegion<MirrorRegion<u32>, PooledIndex<u32>>;
+
+#[test]
+fn test_pooled_allocations() {
+    let pool = IndexPool::default();
+    let mut regions = Vec::with_capacity(1000);
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for group in 0..1000 {
+        let mut region = PooledRegion::with_index_container(PooledIndex::new_in(&pool));
+        let index = region.push([group, group + 1, group + 2].as_slice());
+        regions.push((region, index));
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    // The pool grows by doubling, which reallocates a logarithmic number of times.
+    assert!(allocations < 50, "{allocations} allocations");
+    for (group, (region, index)) in (0..).zip(&regions) {
+        assert!(region
+            .index(*index)
+            .iter()
+            .eq([group, group + 1, group + 2]));
+    }
+
+    // Without a pool, each region allocates its own offsets.
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let unpooled: Vec<_> = (0..1000)
+        .map(|group| {
+            let mut region = <SliceRegion<MirrorRegion<u32>>>::default();
+            let _ = region.push([group, group + 1, group + 2].as_slice());
+            region
+        })
+        .collect();
+    assert!(ALLOCATIONS.load(Ordering::Relaxed) - before >= unpooled.len());
+}
@@ -6,9 +6,9 @@ use flatcontainer::{IntoOwned, Push, Region, StringRegion};
 #[derive(Clone)]
 struct List<T>(T, Option<Box<List<T>>>);
 
-struct ListRef<'a, C: Region>(
-    Result<(&'a ListRegion<C>, <C as Region>::Index, Option<usize>), &'a List<C::Owned>>,
-);
+type ListRefInner<'a, C> = (&'a ListRegion<C>, <C as Region>::Index, Option<usize>);
+
+struct ListRef<'a, C: Region>(Result<ListRefInner<'a, C>, &'a List<C::Owned>>);
 
 impl<'a, C: Region> ListRef<'a, C>
 where